@@ -0,0 +1,33 @@
+DROP TYPE IF EXISTS twa_notify_type;
+
+CREATE TYPE twa_notify_type as ENUM (
+    'Transaction',
+    'TokenTransaction'
+    );
+
+DROP TYPE IF EXISTS twa_webhook_delivery_status;
+
+CREATE TYPE twa_webhook_delivery_status as ENUM (
+    'Pending',
+    'Delivered',
+    'Failed'
+    );
+
+CREATE TABLE webhook_deliveries (
+                                    id                          UUID NOT NULL,
+                                    service_id                  UUID NOT NULL,
+                                    event_id                    UUID NOT NULL,
+                                    notify_type                 twa_notify_type NOT NULL,
+                                    payload                     JSONB NOT NULL,
+                                    status                      twa_webhook_delivery_status NOT NULL DEFAULT 'Pending',
+                                    attempts                    INT NOT NULL DEFAULT 0,
+                                    next_attempt_at             TIMESTAMP NOT NULL DEFAULT current_timestamp,
+                                    last_error                  TEXT,
+                                    created_at                  TIMESTAMP NOT NULL DEFAULT current_timestamp,
+                                    updated_at                  TIMESTAMP NOT NULL DEFAULT current_timestamp,
+                                    CONSTRAINT webhook_deliveries_pk PRIMARY KEY (id),
+                                    CONSTRAINT webhook_deliveries_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id)
+);
+
+CREATE INDEX webhook_deliveries_event_id_idx ON webhook_deliveries (event_id);
+CREATE INDEX webhook_deliveries_pending_idx ON webhook_deliveries (next_attempt_at) WHERE status = 'Pending';
@@ -0,0 +1,25 @@
+DROP INDEX api_service_callback_service_id_idx;
+
+CREATE INDEX api_service_callback_service_id_idx ON api_service_callback (service_id);
+CREATE UNIQUE INDEX api_service_callback_service_id_callback_idx ON api_service_callback (service_id, callback);
+
+ALTER TABLE api_service_callback ADD COLUMN notify_type twa_notify_type;
+ALTER TABLE api_service_callback ADD COLUMN transaction_direction twa_transaction_direction;
+ALTER TABLE api_service_callback ADD COLUMN root_address VARCHAR;
+ALTER TABLE api_service_callback ADD COLUMN account_workchain_id INT;
+ALTER TABLE api_service_callback ADD COLUMN account_hex VARCHAR(64);
+ALTER TABLE api_service_callback ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT current_timestamp;
+
+ALTER TABLE webhook_deliveries ADD COLUMN callback_id UUID;
+
+UPDATE webhook_deliveries wd SET callback_id = c.id
+FROM api_service_callback c
+WHERE c.service_id = wd.service_id;
+
+DELETE FROM webhook_deliveries WHERE callback_id IS NULL;
+
+ALTER TABLE webhook_deliveries ALTER COLUMN callback_id SET NOT NULL;
+ALTER TABLE webhook_deliveries ADD CONSTRAINT webhook_deliveries_to_api_service_callback_fk
+    FOREIGN KEY (callback_id) REFERENCES api_service_callback (id) ON DELETE CASCADE;
+
+CREATE INDEX webhook_deliveries_callback_id_idx ON webhook_deliveries (callback_id);
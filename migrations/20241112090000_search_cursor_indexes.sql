@@ -0,0 +1,3 @@
+CREATE INDEX transactions_service_id_created_at_id_idx ON transactions (service_id, created_at, id);
+CREATE INDEX transaction_events_service_id_created_at_id_idx ON transaction_events (service_id, created_at, id);
+CREATE INDEX token_transaction_events_service_id_created_at_id_idx ON token_transaction_events (service_id, created_at, id);
@@ -0,0 +1,9 @@
+DROP TYPE IF EXISTS twa_signer_type;
+
+CREATE TYPE twa_signer_type as ENUM (
+    'Local',
+    'Remote'
+    );
+
+ALTER TABLE address ADD COLUMN signer_type twa_signer_type NOT NULL DEFAULT 'Local';
+ALTER TABLE address ALTER COLUMN private_key DROP NOT NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE address ADD COLUMN watch_only BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE address ALTER COLUMN public_key DROP NOT NULL;
@@ -0,0 +1,49 @@
+DROP TYPE IF EXISTS twa_payout_batch_status;
+
+CREATE TYPE twa_payout_batch_status as ENUM (
+    'InProgress',
+    'Done',
+    'PartiallyFailed',
+    'Failed'
+    );
+
+DROP TYPE IF EXISTS twa_payout_output_status;
+
+CREATE TYPE twa_payout_output_status as ENUM (
+    'Pending',
+    'Sent',
+    'Done',
+    'Error'
+    );
+
+CREATE TABLE payout_batches (
+                                id                          UUID NOT NULL,
+                                service_id                  UUID NOT NULL,
+                                account_workchain_id        INT NOT NULL,
+                                account_hex                 VARCHAR(64) NOT NULL,
+                                bounce                      BOOLEAN NOT NULL,
+                                payload                     VARCHAR,
+                                status                      twa_payout_batch_status NOT NULL DEFAULT 'InProgress',
+                                created_at                  TIMESTAMP NOT NULL DEFAULT current_timestamp,
+                                updated_at                  TIMESTAMP NOT NULL DEFAULT current_timestamp,
+                                CONSTRAINT payout_batches_pk PRIMARY KEY (id),
+                                CONSTRAINT payout_batches_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id),
+                                CONSTRAINT payout_batches_wc_hex_to_address_fk FOREIGN KEY (account_workchain_id, account_hex) REFERENCES address(workchain_id, hex)
+);
+
+CREATE INDEX payout_batches_in_progress_idx ON payout_batches (created_at) WHERE status = 'InProgress';
+
+CREATE TABLE payout_outputs (
+                                batch_id                    UUID NOT NULL,
+                                position                    INT NOT NULL,
+                                chunk                       INT NOT NULL,
+                                transaction_id              UUID NOT NULL,
+                                output                      JSONB NOT NULL,
+                                status                      twa_payout_output_status NOT NULL DEFAULT 'Pending',
+                                error                       TEXT,
+                                updated_at                  TIMESTAMP NOT NULL DEFAULT current_timestamp,
+                                CONSTRAINT payout_outputs_pk PRIMARY KEY (batch_id, position),
+                                CONSTRAINT payout_outputs_to_payout_batches_fk FOREIGN KEY (batch_id) REFERENCES payout_batches (id)
+);
+
+CREATE INDEX payout_outputs_transaction_id_idx ON payout_outputs (transaction_id);
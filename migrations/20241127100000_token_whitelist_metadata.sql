@@ -0,0 +1,4 @@
+ALTER TABLE token_whitelist ADD COLUMN symbol VARCHAR(255);
+ALTER TABLE token_whitelist ADD COLUMN decimals INT;
+ALTER TABLE token_whitelist ADD COLUMN total_supply NUMERIC;
+ALTER TABLE token_whitelist ADD COLUMN metadata_updated_at TIMESTAMP;
@@ -0,0 +1 @@
+ALTER TABLE token_whitelist ADD COLUMN enabled BOOLEAN NOT NULL DEFAULT TRUE;
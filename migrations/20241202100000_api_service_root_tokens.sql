@@ -0,0 +1,8 @@
+CREATE TABLE api_service_root_token
+(
+    service_id   UUID      NOT NULL,
+    root_address VARCHAR   NOT NULL,
+    created_at   TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    CONSTRAINT api_service_root_token_pk PRIMARY KEY (service_id, root_address),
+    CONSTRAINT api_service_root_token_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id)
+);
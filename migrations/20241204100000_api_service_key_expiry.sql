@@ -0,0 +1 @@
+ALTER TABLE api_service_key ADD COLUMN expires_at TIMESTAMP;
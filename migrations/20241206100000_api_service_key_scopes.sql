@@ -0,0 +1,5 @@
+ALTER TABLE api_service_key ADD COLUMN scopes VARCHAR[];
+
+-- Keys created before the scopes keep full access, the ones created later without scopes get the default ones
+UPDATE api_service_key
+SET scopes = ARRAY ['Read', 'CreateAddress', 'Send', 'TokensMint', 'Approve', 'Admin'];
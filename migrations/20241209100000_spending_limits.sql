@@ -0,0 +1,20 @@
+CREATE TABLE spending_limits
+(
+    id                   UUID      NOT NULL,
+    service_id           UUID      NOT NULL,
+    account_workchain_id INT,
+    account_hex          VARCHAR(64),
+    root_address         VARCHAR,
+    max_per_transaction  NUMERIC,
+    hourly_limit         NUMERIC,
+    daily_limit          NUMERIC,
+    allowed_recipients   VARCHAR[],
+    created_at           TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    updated_at           TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    CONSTRAINT spending_limits_pk PRIMARY KEY (id),
+    CONSTRAINT spending_limits_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id)
+);
+
+CREATE INDEX spending_limits_service_id_idx ON spending_limits (service_id);
+CREATE INDEX transactions_service_id_direction_created_at_idx ON transactions (service_id, direction, created_at);
+CREATE INDEX token_transactions_service_id_direction_created_at_idx ON token_transactions (service_id, direction, created_at);
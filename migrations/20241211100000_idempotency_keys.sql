@@ -0,0 +1,13 @@
+CREATE TABLE idempotency_keys
+(
+    service_id      UUID         NOT NULL,
+    key             VARCHAR(255) NOT NULL,
+    request_hash    VARCHAR(64)  NOT NULL,
+    response_status INT,
+    response_body   BYTEA,
+    created_at      TIMESTAMP    NOT NULL DEFAULT current_timestamp,
+    CONSTRAINT idempotency_keys_pk PRIMARY KEY (service_id, key),
+    CONSTRAINT idempotency_keys_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id)
+);
+
+CREATE INDEX idempotency_keys_created_at_idx ON idempotency_keys (created_at);
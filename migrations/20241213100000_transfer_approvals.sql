@@ -0,0 +1,29 @@
+ALTER TYPE twa_transaction_status ADD VALUE 'PendingApproval';
+ALTER TYPE twa_transaction_status ADD VALUE 'Rejected';
+ALTER TYPE twa_token_transaction_status ADD VALUE 'PendingApproval';
+ALTER TYPE twa_token_transaction_status ADD VALUE 'Rejected';
+
+ALTER TABLE spending_limits ADD COLUMN approval_threshold NUMERIC;
+
+CREATE TABLE transfer_approvals
+(
+    id                   UUID                   NOT NULL,
+    service_id           UUID                   NOT NULL,
+    notify_type          twa_notify_type        NOT NULL,
+    account_workchain_id INT                    NOT NULL,
+    account_hex          VARCHAR(64)            NOT NULL,
+    root_address         VARCHAR,
+    value                NUMERIC                NOT NULL,
+    input                JSONB                  NOT NULL,
+    status               twa_transaction_status NOT NULL,
+    requested_by         UUID                   NOT NULL,
+    decided_by           UUID,
+    error                TEXT,
+    created_at           TIMESTAMP              NOT NULL DEFAULT current_timestamp,
+    updated_at           TIMESTAMP              NOT NULL DEFAULT current_timestamp,
+    CONSTRAINT transfer_approvals_pk PRIMARY KEY (id),
+    CONSTRAINT transfer_approvals_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id),
+    CONSTRAINT transfer_approvals_account_wc_hex_to_address_fk FOREIGN KEY (account_workchain_id, account_hex) REFERENCES address (workchain_id, hex)
+);
+
+CREATE INDEX transfer_approvals_service_id_status_idx ON transfer_approvals (service_id, status);
@@ -0,0 +1,37 @@
+ALTER TYPE twa_transaction_status ADD VALUE 'PendingConfirmation';
+
+DROP TYPE IF EXISTS twa_multisig_proposal_status;
+
+CREATE TYPE twa_multisig_proposal_status as ENUM (
+    'Pending',
+    'Rejected',
+    'Executed',
+    'Expired'
+    );
+
+CREATE TABLE multisig_proposals
+(
+    id                   UUID                         NOT NULL,
+    service_id           UUID                         NOT NULL,
+    account_workchain_id INT                          NOT NULL,
+    account_hex          VARCHAR(64)                  NOT NULL,
+    transaction_id       BIGINT                       NOT NULL,
+    creator              VARCHAR(64)                  NOT NULL,
+    destination          VARCHAR                      NOT NULL,
+    value                NUMERIC                      NOT NULL,
+    bounce               BOOLEAN                      NOT NULL,
+    signs_required       INT                          NOT NULL,
+    signs_received       INT                          NOT NULL,
+    confirmations        VARCHAR(64)[]                NOT NULL,
+    expire_at            TIMESTAMP                    NOT NULL,
+    status               twa_multisig_proposal_status NOT NULL,
+    created_at           TIMESTAMP                    NOT NULL DEFAULT current_timestamp,
+    updated_at           TIMESTAMP                    NOT NULL DEFAULT current_timestamp,
+    CONSTRAINT multisig_proposals_pk PRIMARY KEY (id),
+    CONSTRAINT multisig_proposals_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id),
+    CONSTRAINT multisig_proposals_account_wc_hex_to_address_fk FOREIGN KEY (account_workchain_id, account_hex) REFERENCES address (workchain_id, hex)
+);
+
+CREATE UNIQUE INDEX multisig_proposals_account_transaction_id_idx
+    ON multisig_proposals (account_workchain_id, account_hex, transaction_id);
+CREATE INDEX multisig_proposals_service_id_status_idx ON multisig_proposals (service_id, status);
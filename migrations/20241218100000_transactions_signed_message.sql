@@ -0,0 +1,3 @@
+ALTER TABLE transactions
+    ADD COLUMN message_boc BYTEA,
+    ADD COLUMN expire_at   TIMESTAMP;
@@ -0,0 +1,3 @@
+ALTER TABLE transactions
+    ADD COLUMN resend_input  JSONB,
+    ADD COLUMN send_attempts INTEGER NOT NULL DEFAULT 1;
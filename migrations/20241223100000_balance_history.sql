@@ -0,0 +1,30 @@
+DROP TYPE IF EXISTS twa_account_status;
+
+CREATE TYPE twa_account_status as ENUM (
+    'Active',
+    'UnInit',
+    'Frozen'
+    );
+
+CREATE TABLE balance_history
+(
+    id                   UUID               NOT NULL,
+    service_id           UUID               NOT NULL,
+    account_workchain_id INT                NOT NULL,
+    account_hex          VARCHAR(64)        NOT NULL,
+    root_address         VARCHAR,
+    balance              NUMERIC            NOT NULL,
+    last_transaction_lt  NUMERIC            NOT NULL,
+    account_status       twa_account_status NOT NULL,
+    block_time           TIMESTAMP          NOT NULL,
+    created_at           TIMESTAMP          NOT NULL DEFAULT current_timestamp,
+    CONSTRAINT balance_history_pk PRIMARY KEY (id),
+    CONSTRAINT balance_history_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id),
+    CONSTRAINT balance_history_account_wc_hex_to_address_fk FOREIGN KEY (account_workchain_id, account_hex) REFERENCES address (workchain_id, hex)
+);
+
+-- Several token transactions of one block produce the same snapshot
+CREATE UNIQUE INDEX balance_history_account_lt_idx
+    ON balance_history (account_workchain_id, account_hex, COALESCE(root_address, ''), last_transaction_lt);
+CREATE INDEX balance_history_account_block_time_idx
+    ON balance_history (account_workchain_id, account_hex, root_address, block_time);
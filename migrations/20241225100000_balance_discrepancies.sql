@@ -0,0 +1,31 @@
+DROP TYPE IF EXISTS twa_discrepancy_status;
+
+CREATE TYPE twa_discrepancy_status as ENUM (
+    'Open',
+    'Resolved'
+    );
+
+CREATE TABLE balance_discrepancies
+(
+    id                   UUID                   NOT NULL,
+    service_id           UUID                   NOT NULL,
+    account_workchain_id INT                    NOT NULL,
+    account_hex          VARCHAR(64)            NOT NULL,
+    root_address         VARCHAR,
+    ledger_balance       NUMERIC                NOT NULL,
+    network_balance      NUMERIC                NOT NULL,
+    status               twa_discrepancy_status NOT NULL DEFAULT 'Open',
+    rescan_requested     BOOLEAN                NOT NULL DEFAULT FALSE,
+    created_at           TIMESTAMP              NOT NULL DEFAULT current_timestamp,
+    updated_at           TIMESTAMP              NOT NULL DEFAULT current_timestamp,
+    CONSTRAINT balance_discrepancies_pk PRIMARY KEY (id),
+    CONSTRAINT balance_discrepancies_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id),
+    CONSTRAINT balance_discrepancies_account_wc_hex_to_address_fk FOREIGN KEY (account_workchain_id, account_hex) REFERENCES address (workchain_id, hex)
+);
+
+-- At most one open discrepancy per account balance
+CREATE UNIQUE INDEX balance_discrepancies_open_account_idx
+    ON balance_discrepancies (account_workchain_id, account_hex, COALESCE(root_address, ''))
+    WHERE status = 'Open';
+CREATE INDEX balance_discrepancies_service_id_status_idx
+    ON balance_discrepancies (service_id, status, created_at);
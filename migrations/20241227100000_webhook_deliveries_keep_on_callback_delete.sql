@@ -0,0 +1,5 @@
+ALTER TABLE webhook_deliveries ALTER COLUMN callback_id DROP NOT NULL;
+
+ALTER TABLE webhook_deliveries DROP CONSTRAINT webhook_deliveries_to_api_service_callback_fk;
+ALTER TABLE webhook_deliveries ADD CONSTRAINT webhook_deliveries_to_api_service_callback_fk
+    FOREIGN KEY (callback_id) REFERENCES api_service_callback (id) ON DELETE SET NULL;
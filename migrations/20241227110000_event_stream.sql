@@ -0,0 +1,13 @@
+CREATE TABLE event_stream (
+                              seq                         BIGSERIAL NOT NULL,
+                              service_id                  UUID NOT NULL,
+                              event_id                    UUID NOT NULL,
+                              notify_type                 twa_notify_type NOT NULL,
+                              payload                     JSONB NOT NULL,
+                              created_at                  TIMESTAMP NOT NULL DEFAULT current_timestamp,
+                              CONSTRAINT event_stream_pk PRIMARY KEY (seq),
+                              CONSTRAINT event_stream_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id)
+);
+
+CREATE INDEX event_stream_service_id_seq_idx ON event_stream (service_id, seq);
+CREATE INDEX event_stream_created_at_idx ON event_stream (created_at);
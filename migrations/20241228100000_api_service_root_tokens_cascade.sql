@@ -0,0 +1,6 @@
+DELETE FROM api_service_root_token
+    WHERE root_address NOT IN (SELECT address FROM token_whitelist);
+
+ALTER TABLE api_service_root_token
+    ADD CONSTRAINT api_service_root_token_to_token_whitelist_fk FOREIGN KEY (root_address)
+        REFERENCES token_whitelist (address) ON DELETE CASCADE;
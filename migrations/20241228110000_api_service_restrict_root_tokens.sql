@@ -0,0 +1,5 @@
+ALTER TABLE api_service
+    ADD COLUMN restrict_root_tokens BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE api_service SET restrict_root_tokens = TRUE
+    WHERE id IN (SELECT service_id FROM api_service_root_token);
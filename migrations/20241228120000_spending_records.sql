@@ -0,0 +1,30 @@
+CREATE TABLE spending_records
+(
+    id                   UUID      NOT NULL,
+    service_id           UUID      NOT NULL,
+    account_workchain_id INT       NOT NULL,
+    account_hex          VARCHAR   NOT NULL,
+    root_address         VARCHAR,
+    value                NUMERIC   NOT NULL,
+    transaction_id       UUID,
+    created_at           TIMESTAMP NOT NULL DEFAULT current_timestamp,
+    CONSTRAINT spending_records_pk PRIMARY KEY (id),
+    CONSTRAINT spending_records_to_api_service_fk FOREIGN KEY (service_id) REFERENCES api_service (id)
+);
+
+CREATE INDEX spending_records_service_id_created_at_idx ON spending_records (service_id, created_at);
+
+-- Transfers of the last day are still counted by the limits
+INSERT INTO spending_records (id, service_id, account_workchain_id, account_hex, value, transaction_id, created_at)
+SELECT uuid_generate_v4(), t.service_id, t.account_workchain_id, t.account_hex, t.original_value, t.id, t.created_at
+FROM transactions t
+WHERE t.direction = 'Send' AND t.original_value IS NOT NULL
+    AND t.created_at >= current_timestamp - INTERVAL '1 day'
+    AND t.service_id IN (SELECT id FROM api_service);
+
+INSERT INTO spending_records (id, service_id, account_workchain_id, account_hex, root_address, value, created_at)
+SELECT uuid_generate_v4(), t.service_id, t.account_workchain_id, t.account_hex, t.root_address, -t.value, t.created_at
+FROM token_transactions t
+WHERE t.direction = 'Send' AND t.status != 'Error'
+    AND t.created_at >= current_timestamp - INTERVAL '1 day'
+    AND t.service_id IN (SELECT id FROM api_service);
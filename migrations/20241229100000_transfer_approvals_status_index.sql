@@ -0,0 +1 @@
+CREATE INDEX transfer_approvals_status_created_at_idx ON transfer_approvals (status, created_at);
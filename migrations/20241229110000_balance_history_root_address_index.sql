@@ -0,0 +1,4 @@
+-- Native balances have no root address, so the snapshots are looked up by COALESCE(root_address, '')
+DROP INDEX balance_history_account_block_time_idx;
+CREATE INDEX balance_history_account_block_time_idx
+    ON balance_history (account_workchain_id, account_hex, COALESCE(root_address, ''), block_time);
@@ -1,4 +1,4 @@
-use axum::extract::Path;
+use axum::extract::{Path, Query};
 use axum::{Extension, Json};
 use tokio::time::Instant;
 
@@ -30,6 +30,26 @@ pub async fn post_address_create(
     Ok(Json(AddressResponse::from(address)))
 }
 
+pub async fn post_address_import(
+    Json(req): Json<ImportAddressRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<AddressResponse>> {
+    let start = Instant::now();
+
+    let address = ctx
+        .ton_service
+        .import_address(&service_id, req.into())
+        .await
+        .map(From::from);
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "importAddress");
+    increment_counter!("requests_processed", "method" => "importAddress");
+
+    Ok(Json(AddressResponse::from(address)))
+}
+
 pub async fn post_address_check(
     Json(req): Json<AddressCheckRequest>,
     Extension(ctx): Extension<Arc<ApiContext>>,
@@ -71,6 +91,64 @@ pub async fn get_address_info(
     Ok(Json(AddressInfoResponse::from(address)))
 }
 
+pub async fn get_address_balance_at(
+    Path(address): Path<Address>,
+    Query(req): Query<BalanceAtRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<BalanceSnapshotResponse>> {
+    let snapshot = ctx
+        .ton_service
+        .get_balance_at(&service_id, address, req.try_into()?)
+        .await
+        .map(|snapshot| snapshot.map(From::from));
+
+    Ok(Json(BalanceSnapshotResponse::from(snapshot)))
+}
+
+pub async fn get_address_balance_history(
+    Path(address): Path<Address>,
+    Query(req): Query<BalanceHistoryRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<BalanceHistoryResponse>> {
+    let snapshots = ctx
+        .ton_service
+        .get_balance_history(&service_id, address, req.try_into()?)
+        .await
+        .map(|snapshots| snapshots.into_iter().map(From::from).collect());
+
+    Ok(Json(BalanceHistoryResponse::from(snapshots)))
+}
+
+pub async fn post_address_rescan(
+    Path(address): Path<Address>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<RescanTasksResponse>> {
+    let tasks = ctx
+        .ton_service
+        .start_rescan(&service_id, address)
+        .await
+        .map(|tasks| tasks.into_iter().map(From::from).collect());
+
+    Ok(Json(RescanTasksResponse::from(tasks)))
+}
+
+pub async fn get_address_rescan(
+    Path(address): Path<Address>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<RescanTasksResponse>> {
+    let tasks = ctx
+        .ton_service
+        .get_rescan_tasks(&service_id, address)
+        .await
+        .map(|tasks| tasks.into_iter().map(From::from).collect());
+
+    Ok(Json(RescanTasksResponse::from(tasks)))
+}
+
 pub async fn get_token_address_balance(
     Path(address): Path<Address>,
     Extension(ctx): Extension<Arc<ApiContext>>,
@@ -82,7 +160,10 @@ pub async fn get_token_address_balance(
         .await
         .map(|a| {
             a.into_iter()
-                .map(|(a, b)| TokenBalanceDataResponse::new(a, b))
+                .map(|(a, b)| {
+                    let metadata = ctx.ton_service.token_metadata(&a.root_address);
+                    TokenBalanceDataResponse::new(a, b, metadata)
+                })
                 .collect::<Vec<TokenBalanceDataResponse>>()
         });
 
@@ -0,0 +1,169 @@
+use axum::extract::Path;
+use axum::{Extension, Json};
+use uuid::Uuid;
+
+use crate::api::requests::*;
+use crate::api::responses::*;
+use crate::api::*;
+use crate::models::*;
+use crate::services::TonService;
+
+pub async fn get_admin_services(
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> Result<Json<ApiServicesResponse>> {
+    let items: Vec<_> = auth_service
+        .get_services()
+        .await?
+        .into_iter()
+        .map(ApiServiceResponse::from)
+        .collect();
+
+    Ok(Json(ApiServicesResponse {
+        count: items.len() as i32,
+        items,
+    }))
+}
+
+pub async fn post_admin_services(
+    Json(req): Json<CreateApiServiceRequest>,
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> Result<Json<ApiServiceResponse>> {
+    let service = auth_service.create_service(req.id, &req.name).await?;
+
+    Ok(Json(service.into()))
+}
+
+pub async fn get_admin_service(
+    Path(id): Path<Uuid>,
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> Result<Json<ApiServiceResponse>> {
+    let service = auth_service.get_service(&ServiceId::new(id)).await?;
+
+    Ok(Json(service.into()))
+}
+
+pub async fn post_admin_service(
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateApiServiceRequest>,
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> Result<Json<ApiServiceResponse>> {
+    let service = auth_service
+        .update_service(&ServiceId::new(id), &req.name)
+        .await?;
+
+    Ok(Json(service.into()))
+}
+
+pub async fn post_admin_service_delete(
+    Path(id): Path<Uuid>,
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> Result<Json<ApiServiceResponse>> {
+    let service = auth_service.delete_service(&ServiceId::new(id)).await?;
+
+    Ok(Json(service.into()))
+}
+
+pub async fn get_admin_service_keys(
+    Path(id): Path<Uuid>,
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> Result<Json<ApiServiceKeysResponse>> {
+    let items: Vec<_> = auth_service
+        .get_service_keys(&ServiceId::new(id))
+        .await?
+        .into_iter()
+        .map(ApiServiceKeyResponse::from)
+        .collect();
+
+    Ok(Json(ApiServiceKeysResponse {
+        count: items.len() as i32,
+        items,
+    }))
+}
+
+pub async fn post_admin_service_keys(
+    Path(id): Path<Uuid>,
+    Json(req): Json<CreateApiServiceKeyRequest>,
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> Result<Json<ApiServiceKeyResponse>> {
+    let key = auth_service
+        .create_service_key(&ServiceId::new(id), req.try_into()?)
+        .await?;
+
+    Ok(Json(ApiServiceKeyResponse::with_secret(key)))
+}
+
+pub async fn post_admin_key(
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateApiServiceKeyRequest>,
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> Result<Json<ApiServiceKeyResponse>> {
+    let key = auth_service
+        .update_service_key(&id, req.try_into()?)
+        .await?;
+
+    Ok(Json(key.into()))
+}
+
+pub async fn post_admin_key_rotate(
+    Path(id): Path<Uuid>,
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> Result<Json<ApiServiceKeyResponse>> {
+    let key = auth_service.rotate_service_key_secret(&id).await?;
+
+    Ok(Json(ApiServiceKeyResponse::with_secret(key)))
+}
+
+pub async fn post_admin_key_delete(
+    Path(id): Path<Uuid>,
+    Extension(auth_service): Extension<Arc<AuthService>>,
+) -> Result<Json<ApiServiceKeyResponse>> {
+    let key = auth_service.delete_service_key(&id).await?;
+
+    Ok(Json(key.into()))
+}
+
+pub async fn post_admin_root_token(
+    Json(req): Json<AddRootTokenRequest>,
+    Extension(ton_service): Extension<Arc<TonService>>,
+) -> Result<Json<WhitelistedTokenResponse>> {
+    let token = ton_service.add_root_token(req.into()).await?;
+
+    Ok(Json(token.into()))
+}
+
+pub async fn post_admin_root_token_update(
+    Path(address): Path<Address>,
+    Json(req): Json<UpdateRootTokenRequest>,
+    Extension(ton_service): Extension<Arc<TonService>>,
+) -> Result<Json<WhitelistedTokenResponse>> {
+    let token = ton_service.update_root_token(&address, req.enabled).await?;
+
+    Ok(Json(token.into()))
+}
+
+pub async fn post_admin_root_token_delete(
+    Path(address): Path<Address>,
+    Extension(ton_service): Extension<Arc<TonService>>,
+) -> Result<Json<WhitelistedTokenResponse>> {
+    let token = ton_service.delete_root_token(&address).await?;
+
+    Ok(Json(token.into()))
+}
+
+pub async fn post_admin_service_root_tokens(
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetServiceRootTokensRequest>,
+    Extension(ton_service): Extension<Arc<TonService>>,
+) -> Result<Json<TokenWhitelistResponse>> {
+    let items: Vec<_> = ton_service
+        .set_service_root_tokens(&ServiceId::new(id), req.root_addresses)
+        .await?
+        .into_iter()
+        .map(WhitelistedTokenResponse::from)
+        .collect();
+
+    Ok(Json(TokenWhitelistResponse {
+        count: items.len() as i32,
+        items,
+    }))
+}
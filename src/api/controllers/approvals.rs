@@ -0,0 +1,102 @@
+use axum::extract::{Path, Query};
+use axum::{Extension, Json};
+use metrics::{histogram, increment_counter};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::api::controllers::*;
+use crate::api::requests::*;
+use crate::api::responses::*;
+use crate::api::*;
+
+pub async fn get_transfer_approvals(
+    Query(req): Query<TransferApprovalsRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<TransferApprovalsResponse>> {
+    let start = Instant::now();
+
+    let approvals = ctx
+        .ton_service
+        .get_transfer_approvals(&service_id, req.status)
+        .await
+        .map(|approvals| {
+            let approvals: Vec<_> = approvals
+                .into_iter()
+                .map(TransferApprovalResponse::from)
+                .collect();
+            TransferApprovalsResponse {
+                count: approvals.len() as i32,
+                items: approvals,
+            }
+        })?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "getTransferApprovals");
+    increment_counter!("requests_processed", "method" => "getTransferApprovals");
+
+    Ok(Json(approvals))
+}
+
+pub async fn get_transfer_approval(
+    Path(id): Path<Uuid>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<TransferApprovalResponse>> {
+    let start = Instant::now();
+
+    let approval = ctx
+        .ton_service
+        .get_transfer_approval(&service_id, &id)
+        .await
+        .map(TransferApprovalResponse::from)?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "getTransferApproval");
+    increment_counter!("requests_processed", "method" => "getTransferApproval");
+
+    Ok(Json(approval))
+}
+
+pub async fn post_approve_transfer(
+    Path(id): Path<Uuid>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+    KeyIdExtractor(key_id): KeyIdExtractor,
+) -> Result<Json<TransactionResponse>> {
+    let start = Instant::now();
+
+    let transaction = ctx
+        .ton_service
+        .approve_transfer(&service_id, &key_id, &id)
+        .await
+        .map(From::from);
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "approveTransfer");
+    increment_counter!("requests_processed", "method" => "approveTransfer");
+
+    Ok(Json(TransactionResponse::from(transaction)))
+}
+
+pub async fn post_reject_transfer(
+    Path(id): Path<Uuid>,
+    Json(req): Json<RejectTransferRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+    KeyIdExtractor(key_id): KeyIdExtractor,
+) -> Result<Json<TransferApprovalResponse>> {
+    let start = Instant::now();
+
+    let approval = ctx
+        .ton_service
+        .reject_transfer(&service_id, &key_id, &id, req.reason)
+        .await
+        .map(TransferApprovalResponse::from)?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "rejectTransfer");
+    increment_counter!("requests_processed", "method" => "rejectTransfer");
+
+    Ok(Json(approval))
+}
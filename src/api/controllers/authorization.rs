@@ -1,13 +1,19 @@
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use axum::async_trait;
 use axum::body::{boxed, Body, Full};
 use axum::extract::{FromRequest, OriginalUri, RequestParts};
 use axum::http::Request;
 use axum::middleware::Next;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
+use futures::future::{Either, Ready};
 use http::{Method, StatusCode};
+use subtle::ConstantTimeEq;
+use tower::{Layer, Service};
+use uuid::Uuid;
 
+use crate::api::Error;
 use crate::models::*;
 use crate::services::*;
 
@@ -25,6 +31,26 @@ pub async fn verify_auth(
     }
 }
 
+pub async fn verify_admin_auth(
+    req: Request<Body>,
+    next: Next<Body>,
+    auth_token: Arc<String>,
+) -> impl IntoResponse {
+    let authorized = req
+        .headers()
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| bool::from(token.as_bytes().ct_eq(auth_token.as_bytes())))
+        .unwrap_or_default();
+
+    if authorized {
+        next.run(req).await
+    } else {
+        Rejection("Failed to authorize".to_string(), StatusCode::UNAUTHORIZED).into_response()
+    }
+}
+
 async fn check_api_key(
     req: Request<Body>,
     auth_service: Arc<AuthService>,
@@ -90,12 +116,14 @@ async fn check_api_key(
         }
     };
 
-    let service_id = auth_service
+    let key = auth_service
         .authenticate(&api_key, &timestamp, &signature, &path, &body, real_ip)
         .await?;
 
-    // Forward service id to request handler
-    parts.extensions_mut().insert(IdExtractor(service_id));
+    // Forward service id, key id and key scopes to request handler
+    parts.extensions_mut().insert(IdExtractor(key.service_id));
+    parts.extensions_mut().insert(KeyIdExtractor(key.id));
+    parts.extensions_mut().insert(key.scopes());
 
     Ok(Request::from_request(&mut parts).await.expect("can't fail"))
 }
@@ -123,6 +151,77 @@ where
     }
 }
 
+/// Id of the API key the request is signed with
+#[derive(Debug, Clone, Copy)]
+pub struct KeyIdExtractor(pub Uuid);
+
+#[async_trait]
+impl<B> FromRequest<B> for KeyIdExtractor
+where
+    B: Send, // required by `async_trait`
+{
+    type Rejection = Rejection;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        match req.extensions().get::<KeyIdExtractor>() {
+            Some(key_id) => Ok(*key_id),
+            None => Err(Rejection(
+                "Key id not found".to_string(),
+                StatusCode::UNAUTHORIZED,
+            )),
+        }
+    }
+}
+
+/// Rejects requests made with a key that lacks the scope
+#[derive(Debug, Clone, Copy)]
+pub struct RequireScope(pub KeyScope);
+
+impl<S> Layer<S> for RequireScope {
+    type Service = RequireScopeService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequireScopeService {
+            inner,
+            scope: self.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequireScopeService<S> {
+    inner: S,
+    scope: KeyScope,
+}
+
+impl<S> Service<Request<Body>> for RequireScopeService<S>
+where
+    S: Service<Request<Body>, Response = Response>,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Either<Ready<Result<Response, S::Error>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let allowed = req
+            .extensions()
+            .get::<KeyScopes>()
+            .map(|scopes| scopes.contains(self.scope))
+            .unwrap_or_default();
+
+        if allowed {
+            Either::Right(self.inner.call(req))
+        } else {
+            let response = Error::Forbidden(self.scope).into_response();
+            Either::Left(futures::future::ok(response))
+        }
+    }
+}
+
 pub struct Rejection(String, StatusCode);
 
 impl IntoResponse for Rejection {
@@ -23,6 +23,10 @@ pub fn swagger(prod_url: &str) -> String {
             events,
             tokens,
             misc,
+            limits,
+            approvals,
+            multisig,
+            reconciliation,
             metrics,
             blockchain,
         },
@@ -72,6 +76,29 @@ pub fn swagger(prod_url: &str) -> String {
                     body: requests::CreateAddressRequest,
                     200: responses::AddressResponse,
                 }
+            },
+            ("address" / "import"): {
+                POST: {
+                    tags: { address },
+                    summary: "Address import",
+                    description: "Import existing address. Address without private key is imported as watch-only.",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    body: requests::ImportAddressRequest,
+                    200: responses::AddressResponse,
+                }
             },
              ("address" / { address: String }): {
                 GET: {
@@ -117,6 +144,119 @@ pub fn swagger(prod_url: &str) -> String {
                     200: responses::AddressInfoResponse,
                 }
             },
+            ("address" / { address: String } / "balance"): {
+                GET: {
+                    tags: { address },
+                    summary: "Address balance at time",
+                    description: "Get the last recorded balance of the address at or before the timestamp, \
+                    the token balance if `rootAddress` is set. Balances are recorded after each block with \
+                    transactions of the address.",
+                    parameters: {
+                        (query rootAddress: String): {
+                            required: false,
+                            description: "Root token address",
+                        },
+                        (query timestamp: i64): {
+                            description: "UTC timestamp in milliseconds",
+                        },
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::BalanceSnapshotResponse,
+                }
+            },
+            ("address" / { address: String } / "balance" / "history"): {
+                GET: {
+                    tags: { address },
+                    summary: "Address balance history",
+                    description: "Get the recorded balances of the address in the time range, oldest first, \
+                    the token balances if `rootAddress` is set.",
+                    parameters: {
+                        (query rootAddress: String): {
+                            required: false,
+                            description: "Root token address",
+                        },
+                        (query from: i64): {
+                            required: false,
+                            description: "UTC timestamp in milliseconds",
+                        },
+                        (query to: i64): {
+                            required: false,
+                            description: "UTC timestamp in milliseconds",
+                        },
+                        (query limit: i64): {
+                            required: false,
+                            description: "Max number of balances, 100 by default and 1000 at most",
+                        },
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::BalanceHistoryResponse,
+                }
+            },
+            ("address" / { address: String } / "rescan"): {
+                GET: {
+                    tags: { address },
+                    summary: "Address rescan progress",
+                    description: "Get progress of the address transactions history rescan.",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::RescanTasksResponse,
+                },
+                POST: {
+                    tags: { address },
+                    summary: "Address rescan",
+                    description: "Load transactions of the address and its known token wallets that happened before \
+                    it was added. History is available as far as the node keeps the blocks.",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::RescanTasksResponse,
+                }
+            },
             ("transactions"): {
                 POST: {
                     tags: { transactions },
@@ -180,6 +320,30 @@ pub fn swagger(prod_url: &str) -> String {
                     }
                 }
             },
+            ("transactions" / "estimate"): {
+                POST: {
+                    tags: { transactions },
+                    summary: "Estimate transaction fees",
+                    description: "Execute the transaction locally against the current account state \
+                    and return the estimated fees.",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    body: requests::TonTransactionSendRequest,
+                    200: responses::EstimateFeesResponse,
+                }
+            },
             ("transactions" / "confirm"): {
                 POST: {
                     tags: { transactions },
@@ -308,6 +472,36 @@ pub fn swagger(prod_url: &str) -> String {
                     200: responses::TransactionEventResponse,
                 }
             },
+            ("events" / "stream"): {
+                GET: {
+                    tags: { events },
+                    summary: "Stream events",
+                    description: "Server-sent events stream of account transaction events. Each event has the `transaction` or `tokenTransaction` type, its id is a cursor. Events after the cursor passed in `cursor` query parameter or in `Last-Event-ID` header are replayed first.",
+                    parameters: {
+                        (query cursor: String): {
+                            required: false,
+                            description: "Resume cursor",
+                        },
+                        (header "last-event-id"): {
+                            required: false,
+                            description: "Resume cursor",
+                        },
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: AccountTransactionEvent,
+                }
+            },
             ("events" ): {
                 POST: {
                     tags: { events },
@@ -529,6 +723,30 @@ pub fn swagger(prod_url: &str) -> String {
                     }
                 }
             },
+            ("tokens" / "transactions" / "estimate"): {
+                POST: {
+                    tags: { transactions, tokens },
+                    summary: "Estimate token transaction fees",
+                    description: "Execute the token transaction locally against the current \
+                    account state and return the estimated fees of the sender wallet.",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    body: requests::TonTokenTransactionSendRequest,
+                    200: responses::EstimateFeesResponse,
+                }
+            },
             ("tokens" / "transactions" / "burn"): {
                 POST: {
                     tags: { transactions, tokens },
@@ -609,7 +827,8 @@ pub fn swagger(prod_url: &str) -> String {
                 GET: {
                     tags: { tokens },
                     summary: "Get list of allowed non-native tokens",
-                    description: "It provides tokenRoots of TIP3 tokens which may be accepted by wallets.",
+                    description: "It provides tokenRoots of TIP3 tokens which may be accepted by wallets \
+                    of the service.",
                     parameters: {
                         (header "api-key"): {
                             description: "API Key",
@@ -743,10 +962,30 @@ pub fn swagger(prod_url: &str) -> String {
                 }
             },
             ("callback"): {
+                GET: {
+                    tags: { misc  },
+                    summary: "Get service callback endpoints",
+                    description: "Get all webhooks of the service with their filters",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::CallbacksResponse,
+                },
                 POST: {
                     tags: { misc  },
                     summary: "Set service callback endpoint",
-                    description: "Provides Everwallet API with webhook to call on each network event",
+                    description: "Provides Everwallet API with webhook to call on each network event matching the filter. Updates the filter if the webhook already exists",
                     parameters: {
                         (header "api-key"): {
                             description: "API Key",
@@ -765,6 +1004,387 @@ pub fn swagger(prod_url: &str) -> String {
                     200: responses::SetCallbackResponse,
                 }
             },
+            ("callback" / "id" / { id: String }): {
+                GET: {
+                    tags: { misc  },
+                    summary: "Get service callback endpoint",
+                    description: "Get webhook by id",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::CallbackResponse,
+                },
+                POST: {
+                    tags: { misc  },
+                    summary: "Update service callback endpoint",
+                    description: "Update webhook url and filter by id",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    body: requests::SetCallbackRequest,
+                    200: responses::CallbackResponse,
+                }
+            },
+            ("callback" / "id" / { id: String } / "delete"): {
+                POST: {
+                    tags: { misc  },
+                    summary: "Delete service callback endpoint",
+                    description: "Delete webhook by id. Its pending deliveries are failed",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::CallbackResponse,
+                }
+            },
+            ("approvals"): {
+                GET: {
+                    tags: { approvals },
+                    summary: "Get transfer approvals",
+                    description: "Get transfers of the service which required an approval, newest first",
+                    parameters: {
+                        (query status: String): {
+                            required: false,
+                            description: "Approval status",
+                        },
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::TransferApprovalsResponse,
+                }
+            },
+            ("approvals" / { id: String }): {
+                GET: {
+                    tags: { approvals },
+                    summary: "Get transfer approval",
+                    description: "Get the transfer which required an approval by id",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::TransferApprovalResponse,
+                }
+            },
+            ("approvals" / { id: String } / "approve"): {
+                POST: {
+                    tags: { approvals },
+                    summary: "Approve transfer",
+                    description: "Sign and send the pending transfer. It must be approved with another key than \
+                    the one that requested it, within 24 hours",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::TransactionResponse,
+                }
+            },
+            ("approvals" / { id: String } / "reject"): {
+                POST: {
+                    tags: { approvals },
+                    summary: "Reject transfer",
+                    description: "Reject the pending transfer",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    body: requests::RejectTransferRequest,
+                    200: responses::TransferApprovalResponse,
+                }
+            },
+            ("multisig" / "proposals"): {
+                GET: {
+                    tags: { multisig },
+                    summary: "Get multisig proposals",
+                    description: "Get pending transactions of the SafeMultisig addresses of the service, newest first",
+                    parameters: {
+                        (query address: String): {
+                            required: false,
+                            description: "Multisig address",
+                        },
+                        (query status: String): {
+                            required: false,
+                            description: "Proposal status",
+                        },
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::MultisigProposalsResponse,
+                }
+            },
+            ("multisig" / "proposals" / { id: String }): {
+                GET: {
+                    tags: { multisig },
+                    summary: "Get multisig proposal",
+                    description: "Get the pending multisig transaction by id",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::MultisigProposalResponse,
+                }
+            },
+            ("multisig" / "proposals" / { id: String } / "confirm"): {
+                POST: {
+                    tags: { multisig },
+                    summary: "Confirm multisig proposal",
+                    description: "Confirm the pending multisig transaction with the custodian key of the address",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::TransactionResponse,
+                }
+            },
+            ("multisig" / "proposals" / { id: String } / "reject"): {
+                POST: {
+                    tags: { multisig },
+                    summary: "Reject multisig proposal",
+                    description: "Dismiss the pending multisig transaction. It stays in the contract until it's \
+                    executed or expires",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::MultisigProposalResponse,
+                }
+            },
+            ("reconciliation" / "discrepancies"): {
+                GET: {
+                    tags: { reconciliation },
+                    summary: "Get balance discrepancies",
+                    description: "Get mismatches between the ledger balances of the service addresses and their \
+                    network balances, newest first. Token balances have `rootAddress` set",
+                    parameters: {
+                        (query status: String): {
+                            required: false,
+                            description: "Discrepancy status",
+                        },
+                        (query limit: i64): {
+                            required: false,
+                            description: "Max number of discrepancies, 100 by default and at most",
+                        },
+                        (query offset: i64): {
+                            required: false,
+                            description: "Number of discrepancies to skip",
+                        },
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::BalanceDiscrepanciesResponse,
+                }
+            },
+            ("limits"): {
+                GET: {
+                    tags: { limits },
+                    summary: "Get spending limits",
+                    description: "Get all spending rules of the service",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::SpendingLimitsResponse,
+                },
+                POST: {
+                    tags: { limits },
+                    summary: "Create spending limit",
+                    description: "Create a spending rule. The rule applies to all addresses of the service unless \
+                    `account` is set and to native transfers unless `rootAddress` is set. Limits are in nano units \
+                    of the currency, hourly and daily limits are rolling windows",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    body: requests::SpendingLimitRequest,
+                    200: responses::SpendingLimitResponse,
+                }
+            },
+            ("limits" / { id: String }): {
+                POST: {
+                    tags: { limits },
+                    summary: "Update spending limit",
+                    description: "Replace the spending rule by id",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    body: requests::SpendingLimitRequest,
+                    200: responses::SpendingLimitResponse,
+                }
+            },
+            ("limits" / { id: String } / "delete"): {
+                POST: {
+                    tags: { limits },
+                    summary: "Delete spending limit",
+                    description: "Delete the spending rule by id",
+                    parameters: {
+                        (header "api-key"): {
+                            description: "API Key",
+                        },
+                        (header "sign"): {
+                            description: "Signature",
+                        },
+                        (header "timestamp"): {
+                            description: "Timestamp in ms",
+                        },
+                        (header "x-real-ip"): {
+                            required: false
+                        },
+                    },
+                    200: responses::SpendingLimitResponse,
+                }
+            },
             ("metrics"): {
                 GET: {
                     tags: { metrics  },
@@ -1,5 +1,9 @@
-use axum::extract::Path;
+use std::str::FromStr;
+
+use axum::extract::{Path, Query};
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::{Extension, Json};
+use http::HeaderMap;
 use uuid::Uuid;
 
 use crate::api::controllers::*;
@@ -7,6 +11,7 @@ use crate::api::requests::*;
 use crate::api::responses::*;
 use crate::api::*;
 use crate::models::*;
+use crate::prelude::*;
 
 pub async fn post_events(
     Json(req): Json<TonTransactionEventsRequest>,
@@ -15,17 +20,12 @@ pub async fn post_events(
 ) -> Result<Json<TonEventsResponse>> {
     let transactions_events = ctx
         .ton_service
-        .search_events(&service_id, &req.into())
+        .search_events(&service_id, &req.try_into()?)
         .await
-        .map(|transactions_events| {
-            let events: Vec<_> = transactions_events
-                .into_iter()
-                .map(AccountTransactionEvent::from)
-                .collect();
-            EventsResponse {
-                count: events.len() as i32,
-                items: events,
-            }
+        .map(|(events, next_cursor)| EventsResponse {
+            count: events.len() as i32,
+            items: events,
+            next_cursor: next_cursor.map(|cursor| cursor.to_string()),
         });
 
     Ok(Json(TonEventsResponse::from(transactions_events)))
@@ -59,19 +59,25 @@ pub async fn post_tokens_events(
     Extension(ctx): Extension<Arc<ApiContext>>,
     IdExtractor(service_id): IdExtractor,
 ) -> Result<Json<TonTokenEventsResponse>> {
-    let transactions_events = ctx
+    let (events, next_cursor) = ctx
         .ton_service
-        .search_token_events(&service_id, &req.into())
+        .search_token_events(&service_id, &req.try_into()?)
         .await?;
-    let events: Vec<_> = transactions_events
-        .into_iter()
-        .map(AccountTransactionEvent::from)
-        .collect();
     let res = TonTokenEventsResponse {
         status: TonStatus::Ok,
         data: Some(TokenEventsResponse {
             count: events.len() as i32,
-            items: events,
+            items: events
+                .into_iter()
+                .map(|event| {
+                    let metadata = event
+                        .root_address
+                        .as_deref()
+                        .and_then(|root_address| ctx.ton_service.token_metadata(root_address));
+                    event.with_metadata(metadata)
+                })
+                .collect(),
+            next_cursor: next_cursor.map(|cursor| cursor.to_string()),
         }),
         error_message: None,
     };
@@ -94,11 +100,47 @@ pub async fn get_events_id(
     Extension(ctx): Extension<Arc<ApiContext>>,
     IdExtractor(service_id): IdExtractor,
 ) -> Result<Json<TransactionEventResponse>> {
-    let event = ctx
-        .ton_service
-        .get_event_by_id(&service_id, &id)
-        .await
-        .map(From::from);
+    let event = ctx.ton_service.get_event_by_id(&service_id, &id).await;
 
     Ok(Json(TransactionEventResponse::from(event)))
 }
+
+pub async fn get_events_stream(
+    Query(req): Query<EventsStreamRequest>,
+    headers: HeaderMap,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>> {
+    // Browsers send the id of the last received event on reconnect
+    let cursor = req.cursor.or_else(|| {
+        headers
+            .get("last-event-id")
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string)
+    });
+
+    let cursor = match cursor {
+        Some(cursor) => Some(
+            EventCursor::from_str(&cursor)
+                .map_err(|_| ControllersError::WrongInput("Invalid cursor".to_string()))?,
+        ),
+        None => None,
+    };
+
+    let events =
+        ctx.ton_service
+            .stream_events(&service_id, cursor)
+            .map(|(cursor, notify_type, event)| {
+                let name = match notify_type {
+                    NotifyType::Transaction => "transaction",
+                    NotifyType::TokenTransaction => "tokenTransaction",
+                };
+
+                Event::default()
+                    .id(cursor.to_string())
+                    .event(name)
+                    .json_data(&event)
+            });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use axum::body::{boxed, Body, Full};
+use axum::extract::OriginalUri;
+use axum::http::{HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http::{header, Method, StatusCode};
+
+use crate::api::controllers::*;
+use crate::api::Error;
+use crate::prelude::*;
+use crate::services::*;
+
+const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Makes POST requests with the `Idempotency-Key` header safe to retry: the response is stored
+/// per service and returned again for the same request, another request with the same key
+/// is rejected. The key stays in progress until the handler finishes, or until it expires
+/// if the handler is interrupted. Must be placed after `verify_auth`
+pub async fn verify_idempotency(
+    req: Request<Body>,
+    next: Next<Body>,
+    ton_service: Arc<TonService>,
+) -> Response {
+    if req.method() != Method::POST || !req.headers().contains_key(IDEMPOTENCY_KEY_HEADER) {
+        return next.run(req).await;
+    }
+
+    match process_idempotent_request(req, next, ton_service).await {
+        Ok(response) => response,
+        Err(err) => err.into_response(),
+    }
+}
+
+async fn process_idempotent_request(
+    req: Request<Body>,
+    next: Next<Body>,
+    ton_service: Arc<TonService>,
+) -> Result<Response, Error> {
+    let key = req
+        .headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(parse_idempotency_key)
+        .ok_or_else(|| {
+            ControllersError::WrongInput("Invalid Idempotency-Key header".to_string())
+        })?;
+
+    let service_id = req
+        .extensions()
+        .get::<IdExtractor>()
+        .map(|id| id.0)
+        .ok_or_else(|| anyhow::Error::msg("Service id not found"))?;
+
+    let path = match req.extensions().get::<OriginalUri>() {
+        Some(path) => path.0.path().to_owned(),
+        None => req.uri().path().to_owned(),
+    };
+
+    let (parts, body) = req.into_parts();
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    let request_hash = request_hash(&path, &body);
+
+    if let Some(previous) = ton_service
+        .lock_idempotency_key(&service_id, &key, &request_hash)
+        .await?
+    {
+        if previous.request_hash != request_hash {
+            return Err(Error::IdempotencyKeyMismatch);
+        }
+
+        return match (previous.response_status, previous.response_body) {
+            (Some(status), Some(body)) => {
+                let status = StatusCode::from_u16(status as u16)
+                    .map_err(|_| anyhow::Error::msg("Invalid stored response status"))?;
+                Ok((
+                    status,
+                    [
+                        (
+                            header::CONTENT_TYPE,
+                            HeaderValue::from_static("application/json"),
+                        ),
+                        (
+                            header::HeaderName::from_static(IDEMPOTENT_REPLAYED_HEADER),
+                            HeaderValue::from_static("true"),
+                        ),
+                    ],
+                    body,
+                )
+                    .into_response())
+            }
+            _ => Err(Error::IdempotencyKeyInProgress),
+        };
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    // Server errors are stored as well, since the handler could fail after the state change.
+    // The key is left in progress if the response is lost
+    let (parts, body) = response.into_parts();
+    let body = hyper::body::to_bytes(body)
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    ton_service
+        .save_idempotent_response(&service_id, &key, parts.status, &body)
+        .await?;
+
+    Ok(Response::from_parts(parts, boxed(Full::from(body))))
+}
+
+fn parse_idempotency_key(value: &HeaderValue) -> Option<String> {
+    value
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= IDEMPOTENCY_KEY_MAX_LEN)
+        .map(|key| key.to_owned())
+}
+
+/// Hex encoded SHA-256 of the request path followed by the body
+fn request_hash(path: &str, body: &[u8]) -> String {
+    let mut fingerprint = Vec::with_capacity(path.len() + body.len());
+    fingerprint.extend_from_slice(path.as_bytes());
+    fingerprint.extend_from_slice(body);
+    hex::encode(hmac_sha256::Hash::hash(&fingerprint))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idempotency_key_length_is_limited() {
+        assert_eq!(
+            parse_idempotency_key(&HeaderValue::from_static("payout-1")),
+            Some("payout-1".to_owned())
+        );
+        assert_eq!(parse_idempotency_key(&HeaderValue::from_static("")), None);
+
+        let key = "k".repeat(IDEMPOTENCY_KEY_MAX_LEN);
+        assert_eq!(
+            parse_idempotency_key(&HeaderValue::from_str(&key).unwrap()),
+            Some(key.clone())
+        );
+
+        let key = "k".repeat(IDEMPOTENCY_KEY_MAX_LEN + 1);
+        assert_eq!(
+            parse_idempotency_key(&HeaderValue::from_str(&key).unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn request_hash_depends_on_path_and_body() {
+        let hash = request_hash("/ton/v3/transactions/create", br#"{"id":"1"}"#);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(
+            hash,
+            request_hash("/ton/v3/transactions/create", br#"{"id":"1"}"#)
+        );
+        assert_ne!(
+            hash,
+            request_hash("/ton/v3/transactions/create", br#"{"id":"2"}"#)
+        );
+        assert_ne!(
+            hash,
+            request_hash("/ton/v3/tokens/transactions/create", br#"{"id":"1"}"#)
+        );
+    }
+}
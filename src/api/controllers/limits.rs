@@ -0,0 +1,99 @@
+use axum::extract::Path;
+use axum::{Extension, Json};
+use metrics::{histogram, increment_counter};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::api::controllers::*;
+use crate::api::requests::*;
+use crate::api::responses::*;
+use crate::api::*;
+
+pub async fn get_spending_limits(
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<SpendingLimitsResponse>> {
+    let start = Instant::now();
+
+    let limits = ctx
+        .ton_service
+        .get_spending_limits(&service_id)
+        .await
+        .map(|limits| {
+            let limits: Vec<_> = limits
+                .into_iter()
+                .map(SpendingLimitResponse::from)
+                .collect();
+            SpendingLimitsResponse {
+                count: limits.len() as i32,
+                items: limits,
+            }
+        })?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "getSpendingLimits");
+    increment_counter!("requests_processed", "method" => "getSpendingLimits");
+
+    Ok(Json(limits))
+}
+
+pub async fn post_create_spending_limit(
+    Json(req): Json<SpendingLimitRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<SpendingLimitResponse>> {
+    let start = Instant::now();
+
+    let response = ctx
+        .ton_service
+        .create_spending_limit(&service_id, req.into())
+        .await
+        .map(SpendingLimitResponse::from)?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "createSpendingLimit");
+    increment_counter!("requests_processed", "method" => "createSpendingLimit");
+
+    Ok(Json(response))
+}
+
+pub async fn post_update_spending_limit(
+    Path(id): Path<Uuid>,
+    Json(req): Json<SpendingLimitRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<SpendingLimitResponse>> {
+    let start = Instant::now();
+
+    let response = ctx
+        .ton_service
+        .update_spending_limit(&service_id, &id, req.into())
+        .await
+        .map(SpendingLimitResponse::from)?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "updateSpendingLimit");
+    increment_counter!("requests_processed", "method" => "updateSpendingLimit");
+
+    Ok(Json(response))
+}
+
+pub async fn post_delete_spending_limit(
+    Path(id): Path<Uuid>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<SpendingLimitResponse>> {
+    let start = Instant::now();
+
+    let response = ctx
+        .ton_service
+        .delete_spending_limit(&service_id, &id)
+        .await
+        .map(SpendingLimitResponse::from)?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "deleteSpendingLimit");
+    increment_counter!("requests_processed", "method" => "deleteSpendingLimit");
+
+    Ok(Json(response))
+}
@@ -1,3 +1,4 @@
+use axum::extract::Path;
 use axum::{Extension, Json};
 use metrics::{histogram, increment_counter};
 use tokio::time::Instant;
@@ -8,6 +9,7 @@ use crate::api::requests::*;
 use crate::api::responses::*;
 use crate::api::*;
 use crate::models::*;
+use crate::services::PreparedTransfer;
 
 pub async fn post_read_contract(
     Json(req): Json<ExecuteContractRequest>,
@@ -79,6 +81,11 @@ pub async fn post_prepare_generic_message(
         headers: d.headers,
     });
 
+    let transfer = PreparedTransfer {
+        value: req.value.clone(),
+        recipient: req.target_account_addr.clone(),
+    };
+
     let unsigned_message = ctx
         .ton_service
         .prepare_generic_message(
@@ -94,7 +101,8 @@ pub async fn post_prepare_generic_message(
         )
         .await?;
 
-    ctx.memory_storage.add_message(unsigned_message.clone());
+    ctx.memory_storage
+        .add_message(unsigned_message.clone(), transfer);
 
     let elapsed = start.elapsed();
     histogram!("execution_time_seconds", elapsed, "method" => "prepareGenericMessage");
@@ -108,11 +116,12 @@ pub async fn post_prepare_generic_message(
 pub async fn post_send_signed_message(
     Json(req): Json<SignedMessageRequest>,
     Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
 ) -> Result<Json<SignedMessageHashResponse>> {
     let start = Instant::now();
 
     let res = match ctx.memory_storage.get_message(&req.hash) {
-        Some(message) => {
+        Some((message, transfer)) => {
             let signature: [u8; 64] = hex::decode(req.signature)
                 .map_err(|_| ControllersError::WrongInput("Bad signature format".to_string()))?
                 .try_into()
@@ -124,7 +133,13 @@ pub async fn post_send_signed_message(
 
             let hash = ctx
                 .ton_service
-                .send_signed_message(req.sender_addr, req.hash, signed_message)
+                .send_signed_message(
+                    &service_id,
+                    req.sender_addr,
+                    req.hash,
+                    signed_message,
+                    &transfer,
+                )
                 .await?;
 
             Ok(SignedMessageHashResponse {
@@ -191,35 +206,123 @@ pub async fn post_set_callback(
     IdExtractor(service_id): IdExtractor,
 ) -> Result<Json<SetCallbackResponse>> {
     let start = Instant::now();
-    let callback = req;
 
     let response = ctx
         .ton_service
-        .set_callback(&service_id, callback.callback)
-        .await?;
+        .set_callback(&service_id, req.into())
+        .await
+        .map(|c| SetCallbackResponse {
+            callback: c.callback,
+        })?;
 
     let elapsed = start.elapsed();
     histogram!("execution_time_seconds", elapsed, "method" => "setCallback");
     increment_counter!("requests_processed", "method" => "setCallback");
 
-    Ok(Json(SetCallbackResponse { callback: response }))
+    Ok(Json(response))
+}
+
+pub async fn get_callbacks(
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<CallbacksResponse>> {
+    let start = Instant::now();
+
+    let callbacks = ctx
+        .ton_service
+        .get_callbacks(&service_id)
+        .await?
+        .into_iter()
+        .map(CallbackResponse::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let callbacks = CallbacksResponse {
+        count: callbacks.len() as i32,
+        items: callbacks,
+    };
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "getCallbacks");
+    increment_counter!("requests_processed", "method" => "getCallbacks");
+
+    Ok(Json(callbacks))
+}
+
+pub async fn get_callback_id(
+    Path(id): Path<Uuid>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<CallbackResponse>> {
+    let start = Instant::now();
+
+    let callback = ctx.ton_service.get_callback(&service_id, &id).await?;
+    let response = CallbackResponse::try_from(callback)?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "getCallback");
+    increment_counter!("requests_processed", "method" => "getCallback");
+
+    Ok(Json(response))
+}
+
+pub async fn post_update_callback(
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetCallbackRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<CallbackResponse>> {
+    let start = Instant::now();
+
+    let callback = ctx
+        .ton_service
+        .update_callback(&service_id, &id, req.into())
+        .await?;
+    let response = CallbackResponse::try_from(callback)?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "updateCallback");
+    increment_counter!("requests_processed", "method" => "updateCallback");
+
+    Ok(Json(response))
+}
+
+pub async fn post_delete_callback(
+    Path(id): Path<Uuid>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<CallbackResponse>> {
+    let start = Instant::now();
+
+    let callback = ctx.ton_service.delete_callback(&service_id, &id).await?;
+    let response = CallbackResponse::try_from(callback)?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "deleteCallback");
+    increment_counter!("requests_processed", "method" => "deleteCallback");
+
+    Ok(Json(response))
 }
 
 pub async fn get_token_whitelist(
     Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
 ) -> Result<Json<TokenWhitelistResponse>> {
     let start = Instant::now();
 
-    let whitelist = ctx.ton_service.token_whitelist().await.map(|tokens| {
-        let tokens: Vec<_> = tokens
-            .into_iter()
-            .map(WhitelistedTokenResponse::from)
-            .collect();
-        TokenWhitelistResponse {
-            count: tokens.len() as i32,
-            items: tokens,
-        }
-    })?;
+    let whitelist = ctx
+        .ton_service
+        .token_whitelist(&service_id)
+        .await
+        .map(|tokens| {
+            let tokens: Vec<_> = tokens
+                .into_iter()
+                .map(WhitelistedTokenResponse::from)
+                .collect();
+            TokenWhitelistResponse {
+                count: tokens.len() as i32,
+                items: tokens,
+            }
+        })?;
 
     let elapsed = start.elapsed();
     histogram!("execution_time_seconds", elapsed, "method" => "getTokenWhitelist");
@@ -2,20 +2,32 @@ use axum::response::IntoResponse;
 use http::StatusCode;
 
 pub use self::address::*;
+pub use self::admin::*;
+pub use self::approvals::*;
 pub use self::authorization::*;
 pub use self::blockchain::*;
 pub use self::docs::*;
 pub use self::events::*;
+pub use self::idempotency::*;
+pub use self::limits::*;
 pub use self::misc::*;
+pub use self::multisig::*;
+pub use self::reconciliation::*;
 pub use self::ton_metrics::*;
 pub use self::transactions::*;
 
 mod address;
+mod admin;
+mod approvals;
 mod authorization;
 mod blockchain;
 mod docs;
 mod events;
+mod idempotency;
+mod limits;
 mod misc;
+mod multisig;
+mod reconciliation;
 mod ton_metrics;
 mod transactions;
 
@@ -0,0 +1,99 @@
+use axum::extract::{Path, Query};
+use axum::{Extension, Json};
+use metrics::{histogram, increment_counter};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::api::controllers::*;
+use crate::api::requests::*;
+use crate::api::responses::*;
+use crate::api::*;
+
+pub async fn get_multisig_proposals(
+    Query(req): Query<MultisigProposalsRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<MultisigProposalsResponse>> {
+    let start = Instant::now();
+
+    let proposals = ctx
+        .ton_service
+        .get_multisig_proposals(&service_id, req.address, req.status)
+        .await
+        .map(|proposals| {
+            let proposals: Vec<_> = proposals
+                .into_iter()
+                .map(MultisigProposalResponse::from)
+                .collect();
+            MultisigProposalsResponse {
+                count: proposals.len() as i32,
+                items: proposals,
+            }
+        })?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "getMultisigProposals");
+    increment_counter!("requests_processed", "method" => "getMultisigProposals");
+
+    Ok(Json(proposals))
+}
+
+pub async fn get_multisig_proposal(
+    Path(id): Path<Uuid>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<MultisigProposalResponse>> {
+    let start = Instant::now();
+
+    let proposal = ctx
+        .ton_service
+        .get_multisig_proposal(&service_id, &id)
+        .await
+        .map(MultisigProposalResponse::from)?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "getMultisigProposal");
+    increment_counter!("requests_processed", "method" => "getMultisigProposal");
+
+    Ok(Json(proposal))
+}
+
+pub async fn post_confirm_multisig_proposal(
+    Path(id): Path<Uuid>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<TransactionResponse>> {
+    let start = Instant::now();
+
+    let transaction = ctx
+        .ton_service
+        .confirm_multisig_proposal(&service_id, &id)
+        .await
+        .map(From::from);
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "confirmMultisigProposal");
+    increment_counter!("requests_processed", "method" => "confirmMultisigProposal");
+
+    Ok(Json(TransactionResponse::from(transaction)))
+}
+
+pub async fn post_reject_multisig_proposal(
+    Path(id): Path<Uuid>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<MultisigProposalResponse>> {
+    let start = Instant::now();
+
+    let proposal = ctx
+        .ton_service
+        .reject_multisig_proposal(&service_id, &id)
+        .await
+        .map(MultisigProposalResponse::from)?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "rejectMultisigProposal");
+    increment_counter!("requests_processed", "method" => "rejectMultisigProposal");
+
+    Ok(Json(proposal))
+}
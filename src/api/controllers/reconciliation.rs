@@ -0,0 +1,38 @@
+use axum::extract::Query;
+use axum::{Extension, Json};
+use metrics::{histogram, increment_counter};
+use tokio::time::Instant;
+
+use crate::api::controllers::*;
+use crate::api::requests::*;
+use crate::api::responses::*;
+use crate::api::*;
+
+pub async fn get_balance_discrepancies(
+    Query(req): Query<BalanceDiscrepanciesRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<BalanceDiscrepanciesResponse>> {
+    let start = Instant::now();
+
+    let discrepancies = ctx
+        .ton_service
+        .get_balance_discrepancies(&service_id, req.try_into()?)
+        .await
+        .map(|discrepancies| {
+            let discrepancies: Vec<_> = discrepancies
+                .into_iter()
+                .map(BalanceDiscrepancyResponse::from)
+                .collect();
+            BalanceDiscrepanciesResponse {
+                count: discrepancies.len() as i32,
+                items: discrepancies,
+            }
+        })?;
+
+    let elapsed = start.elapsed();
+    histogram!("execution_time_seconds", elapsed, "method" => "getBalanceDiscrepancies");
+    increment_counter!("requests_processed", "method" => "getBalanceDiscrepancies");
+
+    Ok(Json(discrepancies))
+}
@@ -1,6 +1,8 @@
 use axum::extract::Path;
 use axum::{Extension, Json};
+use bigdecimal::BigDecimal;
 use metrics::{histogram, increment_counter};
+use nekoton_utils::repack_address;
 use tokio::time::Instant;
 use uuid::Uuid;
 
@@ -8,6 +10,7 @@ use crate::api::controllers::*;
 use crate::api::requests::*;
 use crate::api::responses::*;
 use crate::api::*;
+use crate::models::*;
 
 pub async fn post_transactions(
     Json(req): Json<TonTransactionsRequest>,
@@ -16,9 +19,9 @@ pub async fn post_transactions(
 ) -> Result<Json<TonTransactionsResponse>> {
     let transactions = ctx
         .ton_service
-        .search_transaction(&service_id, &req.into())
+        .search_transaction(&service_id, &req.try_into()?)
         .await
-        .map(|transactions| {
+        .map(|(transactions, next_cursor)| {
             let transactions: Vec<_> = transactions
                 .into_iter()
                 .map(TransactionDataResponse::from)
@@ -26,6 +29,7 @@ pub async fn post_transactions(
             TransactionsResponse {
                 count: transactions.len() as i32,
                 items: transactions,
+                next_cursor: next_cursor.map(|cursor| cursor.to_string()),
             }
         });
 
@@ -36,12 +40,13 @@ pub async fn post_transactions_create(
     Json(req): Json<TonTransactionSendRequest>,
     Extension(ctx): Extension<Arc<ApiContext>>,
     IdExtractor(service_id): IdExtractor,
+    KeyIdExtractor(key_id): KeyIdExtractor,
 ) -> Result<Json<TransactionResponse>> {
     let start = Instant::now();
 
     let transaction = ctx
         .ton_service
-        .create_send_transaction(&service_id, req.into())
+        .create_send_transaction(&service_id, &key_id, req.into())
         .await
         .map(From::from);
 
@@ -52,6 +57,19 @@ pub async fn post_transactions_create(
     Ok(Json(TransactionResponse::from(transaction)))
 }
 
+pub async fn post_transactions_estimate(
+    Json(req): Json<TonTransactionSendRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<EstimateFeesResponse>> {
+    let fees = ctx
+        .ton_service
+        .estimate_transaction_fees(&service_id, req.into())
+        .await;
+
+    Ok(Json(EstimateFeesResponse::from(fees)))
+}
+
 pub async fn post_transactions_confirm(
     Json(req): Json<TonTransactionConfirmRequest>,
     Extension(ctx): Extension<Arc<ApiContext>>,
@@ -105,11 +123,22 @@ pub async fn get_transactions_id(
     Extension(ctx): Extension<Arc<ApiContext>>,
     IdExtractor(service_id): IdExtractor,
 ) -> Result<Json<TransactionResponse>> {
-    let transaction = ctx
+    let transaction = match ctx
         .ton_service
         .get_transaction_by_id(&service_id, &id)
         .await
-        .map(From::from);
+    {
+        // Outputs of a payout batch are reported along with its first transaction
+        Ok(transaction) => ctx
+            .ton_service
+            .get_payout_batch(&service_id, &id)
+            .await
+            .map(|batch| TransactionDataResponse {
+                batch: batch.map(From::from),
+                ..TransactionDataResponse::from(transaction)
+            }),
+        Err(e) => Err(e),
+    };
 
     Ok(Json(TransactionResponse::from(transaction)))
 }
@@ -123,7 +152,10 @@ pub async fn get_tokens_transactions_id(
         .ton_service
         .get_tokens_transaction_by_id(&service_id, &internal_id)
         .await
-        .map(From::from);
+        .map(|transaction| {
+            let metadata = ctx.ton_service.token_metadata(&transaction.root_address);
+            TokenTransactionDataResponse::from(transaction).with_metadata(metadata)
+        });
 
     Ok(Json(TokenTransactionResponse::from(transaction)))
 }
@@ -137,7 +169,10 @@ pub async fn get_tokens_transactions_mh(
         .ton_service
         .get_tokens_transaction_by_mh(&service_id, &message_hash)
         .await
-        .map(From::from);
+        .map(|transaction| {
+            let metadata = ctx.ton_service.token_metadata(&transaction.root_address);
+            TokenTransactionDataResponse::from(transaction).with_metadata(metadata)
+        });
 
     Ok(Json(TokenTransactionResponse::from(transaction)))
 }
@@ -146,14 +181,16 @@ pub async fn post_tokens_transactions_create(
     Json(req): Json<TonTokenTransactionSendRequest>,
     Extension(ctx): Extension<Arc<ApiContext>>,
     IdExtractor(service_id): IdExtractor,
+    KeyIdExtractor(key_id): KeyIdExtractor,
 ) -> Result<Json<TransactionResponse>> {
     let start = Instant::now();
 
+    let token = token_amount(&ctx, &req.root_address, req.value.clone());
     let transaction = ctx
         .ton_service
-        .create_send_token_transaction(&service_id, &req.into())
+        .create_send_token_transaction(&service_id, &key_id, &req.into())
         .await
-        .map(From::from);
+        .map(|transaction| TransactionDataResponse::from(transaction).with_token(token));
 
     let elapsed = start.elapsed();
     histogram!("execution_time_seconds", elapsed, "method" => "tokenTransactionCreate");
@@ -162,6 +199,19 @@ pub async fn post_tokens_transactions_create(
     Ok(Json(TransactionResponse::from(transaction)))
 }
 
+pub async fn post_tokens_transactions_estimate(
+    Json(req): Json<TonTokenTransactionSendRequest>,
+    Extension(ctx): Extension<Arc<ApiContext>>,
+    IdExtractor(service_id): IdExtractor,
+) -> Result<Json<EstimateFeesResponse>> {
+    let fees = ctx
+        .ton_service
+        .estimate_token_transaction_fees(&service_id, &req.into())
+        .await;
+
+    Ok(Json(EstimateFeesResponse::from(fees)))
+}
+
 pub async fn post_tokens_transactions_burn(
     Json(req): Json<TonTokenTransactionBurnRequest>,
     Extension(ctx): Extension<Arc<ApiContext>>,
@@ -169,11 +219,12 @@ pub async fn post_tokens_transactions_burn(
 ) -> Result<Json<TransactionResponse>> {
     let start = Instant::now();
 
+    let token = token_amount(&ctx, &req.root_address, req.value.clone());
     let transaction = ctx
         .ton_service
         .create_burn_token_transaction(&service_id, &req.into())
         .await
-        .map(From::from);
+        .map(|transaction| TransactionDataResponse::from(transaction).with_token(token));
 
     let elapsed = start.elapsed();
     histogram!("execution_time_seconds", elapsed, "method" => "tokenTransactionBurn");
@@ -189,11 +240,12 @@ pub async fn post_tokens_transactions_mint(
 ) -> Result<Json<TransactionResponse>> {
     let start = Instant::now();
 
+    let token = token_amount(&ctx, &req.root_address, req.value.clone());
     let transaction = ctx
         .ton_service
         .create_mint_token_transaction(&service_id, &req.into())
         .await
-        .map(From::from);
+        .map(|transaction| TransactionDataResponse::from(transaction).with_token(token));
 
     let elapsed = start.elapsed();
     histogram!("execution_time_seconds", elapsed, "method" => "tokenTransactionMint");
@@ -201,3 +253,16 @@ pub async fn post_tokens_transactions_mint(
 
     Ok(Json(TransactionResponse::from(transaction)))
 }
+
+fn token_amount(
+    ctx: &ApiContext,
+    root_address: &Address,
+    value: BigDecimal,
+) -> TokenAmountResponse {
+    let root_address = repack_address(&root_address.0)
+        .map(|root_address| root_address.to_string())
+        .unwrap_or_else(|_| root_address.0.clone());
+    let metadata = ctx.ton_service.token_metadata(&root_address);
+
+    TokenAmountResponse::new(root_address, value, metadata)
+}
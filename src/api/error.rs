@@ -8,6 +8,7 @@ use tracing::log;
 
 use crate::api::controllers::ControllersError;
 use crate::client::TonClientError;
+use crate::models::KeyScope;
 use crate::services::TonServiceError;
 
 /// A common error type that can be used throughout the API.
@@ -78,6 +79,18 @@ pub enum Error {
 
     #[error(transparent)]
     Controllers(#[from] ControllersError),
+
+    /// Return `403 Forbidden` when the API key lacks the scope required by the route.
+    #[error("API key has no `{0}` scope")]
+    Forbidden(KeyScope),
+
+    /// Return `409 Conflict` when the idempotency key was used for a different request.
+    #[error("Idempotency key is already used for another request")]
+    IdempotencyKeyMismatch,
+
+    /// Return `409 Conflict` while the request with the same idempotency key is processed.
+    #[error("Request with the idempotency key is in progress")]
+    IdempotencyKeyInProgress,
 }
 
 impl Error {
@@ -94,6 +107,8 @@ impl Error {
             Error::TonService(e) => e.status_code(),
             Error::TonClient(e) => e.status_code(),
             Error::Controllers(e) => e.status_code(),
+            Error::Forbidden(_) => StatusCode::FORBIDDEN,
+            Error::IdempotencyKeyMismatch | Error::IdempotencyKeyInProgress => StatusCode::CONFLICT,
         }
     }
 }
@@ -177,6 +192,14 @@ impl Error {
             Self::Controllers(ref e) => {
                 log::error!("Controllers error: {:?}", e);
             }
+
+            Self::Forbidden(ref scope) => {
+                log::warn!("Forbidden: key has no `{}` scope", scope);
+            }
+
+            Self::IdempotencyKeyMismatch | Self::IdempotencyKeyInProgress => {
+                log::warn!("Idempotency conflict: {}", self);
+            }
         }
 
         self.to_string()
@@ -75,6 +75,31 @@ pub async fn http_service(
         .unwrap();
 }
 
+pub async fn admin_http_service(
+    server_addr: SocketAddr,
+    auth_token: String,
+    auth_service: Arc<AuthService>,
+    ton_service: Arc<TonService>,
+) {
+    let app = router::admin_router(auth_service, ton_service, auth_token)
+        .layer(
+            TraceLayer::new_for_http().on_request(|request: &Request<Body>, _span: &Span| {
+                tracing::info!(
+                    "started admin {} {}",
+                    request.method(),
+                    request.uri().path()
+                )
+            }),
+        )
+        .fallback(controllers::handler_404.into_service());
+
+    axum::Server::bind(&server_addr)
+        .serve(app.into_make_service())
+        .await
+        .context("Failed to start admin HTTP server")
+        .unwrap();
+}
+
 fn install_monitoring(metrics_addr: SocketAddr) -> anyhow::Result<()> {
     metrics_exporter_prometheus::PrometheusBuilder::new()
         .set_buckets_for_metric(
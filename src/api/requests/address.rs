@@ -1,7 +1,9 @@
 use opg::OpgModel;
 use serde::Deserialize;
 
+use crate::api::controllers::ControllersError;
 use crate::models::*;
+use crate::prelude::*;
 
 #[derive(Deserialize, OpgModel)]
 #[serde(rename_all = "camelCase")]
@@ -19,6 +21,7 @@ pub struct CreateAddressRequest {
     pub custodians: Option<i32>,
     pub confirmations: Option<i32>,
     pub custodians_public_keys: Option<Vec<String>>,
+    pub signer_type: Option<SignerType>,
 }
 
 impl From<CreateAddressRequest> for CreateAddress {
@@ -29,6 +32,89 @@ impl From<CreateAddressRequest> for CreateAddress {
             custodians: c.custodians,
             confirmations: c.confirmations,
             custodians_public_keys: c.custodians_public_keys,
+            signer_type: c.signer_type,
         }
     }
 }
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("ImportAddressRequest")]
+pub struct ImportAddressRequest {
+    pub account_type: Option<AccountType>,
+    pub workchain_id: Option<i32>,
+    pub address: Option<Address>,
+    pub public_key: Option<String>,
+    pub private_key: Option<String>,
+    pub custodians: Option<i32>,
+    pub confirmations: Option<i32>,
+    pub custodians_public_keys: Option<Vec<String>>,
+}
+
+impl From<ImportAddressRequest> for ImportAddress {
+    fn from(c: ImportAddressRequest) -> Self {
+        ImportAddress {
+            account_type: c.account_type,
+            workchain_id: c.workchain_id,
+            address: c.address,
+            public_key: c.public_key,
+            private_key: c.private_key,
+            custodians: c.custodians,
+            confirmations: c.confirmations,
+            custodians_public_keys: c.custodians_public_keys,
+        }
+    }
+}
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("BalanceAtRequest")]
+pub struct BalanceAtRequest {
+    pub root_address: Option<Address>,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
+    pub timestamp: i64,
+}
+
+impl TryFrom<BalanceAtRequest> for BalanceAt {
+    type Error = ControllersError;
+
+    fn try_from(c: BalanceAtRequest) -> Result<Self, Self::Error> {
+        Ok(BalanceAt {
+            root_address: c.root_address,
+            timestamp: super::parse_timestamp_ms(Some(c.timestamp))?.unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("BalanceHistoryRequest")]
+pub struct BalanceHistoryRequest {
+    pub root_address: Option<Address>,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64", optional)]
+    pub from: Option<i64>,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64", optional)]
+    pub to: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+impl TryFrom<BalanceHistoryRequest> for BalanceHistorySearch {
+    type Error = ControllersError;
+
+    fn try_from(c: BalanceHistoryRequest) -> Result<Self, Self::Error> {
+        let limit = c.limit.unwrap_or(MAX_LIMIT_SEARCH);
+        if !(1..=MAX_LIMIT_BALANCE_HISTORY).contains(&limit) {
+            return Err(ControllersError::WrongInput(format!(
+                "Limit must be from 1 to {}",
+                MAX_LIMIT_BALANCE_HISTORY
+            )));
+        }
+
+        Ok(BalanceHistorySearch {
+            root_address: c.root_address,
+            from: super::parse_timestamp_ms(c.from)?,
+            to: super::parse_timestamp_ms(c.to)?,
+            limit,
+        })
+    }
+}
@@ -0,0 +1,64 @@
+use opg::OpgModel;
+use serde::Deserialize;
+
+use crate::api::controllers::ControllersError;
+use crate::models::*;
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiServiceRequest {
+    pub id: Option<ServiceId>,
+    pub name: String,
+}
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateApiServiceRequest {
+    pub name: String,
+}
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiServiceKeyRequest {
+    pub key: Option<String>,
+    pub secret: Option<String>,
+    pub whitelist: Option<Vec<String>>,
+    pub scopes: Option<Vec<KeyScope>>,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64", optional)]
+    pub expires_at: Option<i64>,
+}
+
+impl TryFrom<CreateApiServiceKeyRequest> for CreateApiServiceKey {
+    type Error = ControllersError;
+
+    fn try_from(c: CreateApiServiceKeyRequest) -> Result<Self, Self::Error> {
+        Ok(CreateApiServiceKey {
+            key: c.key,
+            secret: c.secret,
+            whitelist: c.whitelist,
+            scopes: c.scopes,
+            expires_at: super::parse_timestamp_ms(c.expires_at)?,
+        })
+    }
+}
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateApiServiceKeyRequest {
+    pub whitelist: Option<Vec<String>>,
+    pub scopes: Option<Vec<KeyScope>>,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64", optional)]
+    pub expires_at: Option<i64>,
+}
+
+impl TryFrom<UpdateApiServiceKeyRequest> for UpdateApiServiceKey {
+    type Error = ControllersError;
+
+    fn try_from(c: UpdateApiServiceKeyRequest) -> Result<Self, Self::Error> {
+        Ok(UpdateApiServiceKey {
+            whitelist: c.whitelist,
+            scopes: c.scopes,
+            expires_at: super::parse_timestamp_ms(c.expires_at)?,
+        })
+    }
+}
@@ -0,0 +1,18 @@
+use opg::OpgModel;
+use serde::Deserialize;
+
+use crate::models::*;
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("TransferApprovalsRequest")]
+pub struct TransferApprovalsRequest {
+    pub status: Option<TonTransactionStatus>,
+}
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("RejectTransferRequest")]
+pub struct RejectTransferRequest {
+    pub reason: Option<String>,
+}
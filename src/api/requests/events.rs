@@ -3,15 +3,24 @@ use opg::OpgModel;
 use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::api::controllers::ControllersError;
 use crate::models::*;
 use crate::prelude::*;
 
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("EventsStreamRequest")]
+pub struct EventsStreamRequest {
+    pub cursor: Option<String>,
+}
+
 #[derive(Deserialize, OpgModel)]
 #[serde(rename_all = "camelCase")]
 #[opg("TonTransactionEventsRequest")]
 pub struct TonTransactionEventsRequest {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    pub cursor: Option<String>,
     pub created_at_ge: Option<i64>,
     pub created_at_le: Option<i64>,
     pub transaction_id: Option<Uuid>,
@@ -23,11 +32,21 @@ pub struct TonTransactionEventsRequest {
     pub event_status: Option<TonEventStatus>,
 }
 
-impl From<TonTransactionEventsRequest> for TransactionsEventsSearch {
-    fn from(c: TonTransactionEventsRequest) -> Self {
-        TransactionsEventsSearch {
+impl TryFrom<TonTransactionEventsRequest> for TransactionsEventsSearch {
+    type Error = ControllersError;
+
+    fn try_from(c: TonTransactionEventsRequest) -> Result<Self, Self::Error> {
+        let cursor = super::parse_search_cursor(c.cursor)?;
+
+        Ok(TransactionsEventsSearch {
             limit: c.limit.unwrap_or(MAX_LIMIT_SEARCH),
-            offset: c.offset.unwrap_or(0),
+            // The cursor is used in place of the offset
+            offset: if cursor.is_some() {
+                0
+            } else {
+                c.offset.unwrap_or(0)
+            },
+            cursor,
             created_at_ge: c.created_at_ge,
             created_at_le: c.created_at_le,
             transaction_id: c.transaction_id,
@@ -37,7 +56,7 @@ impl From<TonTransactionEventsRequest> for TransactionsEventsSearch {
             transaction_direction: c.transaction_direction,
             transaction_status: c.transaction_status,
             event_status: c.event_status,
-        }
+        })
     }
 }
 
@@ -61,6 +80,7 @@ pub struct MarkAllTransactionEventRequest {
 pub struct TonTokenTransactionEventsRequest {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    pub cursor: Option<String>,
     pub created_at_ge: Option<i64>,
     pub created_at_le: Option<i64>,
     pub token_transaction_id: Option<Uuid>,
@@ -74,11 +94,21 @@ pub struct TonTokenTransactionEventsRequest {
     pub event_status: Option<TonEventStatus>,
 }
 
-impl From<TonTokenTransactionEventsRequest> for TokenTransactionsEventsSearch {
-    fn from(c: TonTokenTransactionEventsRequest) -> Self {
-        TokenTransactionsEventsSearch {
+impl TryFrom<TonTokenTransactionEventsRequest> for TokenTransactionsEventsSearch {
+    type Error = ControllersError;
+
+    fn try_from(c: TonTokenTransactionEventsRequest) -> Result<Self, Self::Error> {
+        let cursor = super::parse_search_cursor(c.cursor)?;
+
+        Ok(TokenTransactionsEventsSearch {
             limit: c.limit.unwrap_or(MAX_LIMIT_SEARCH),
-            offset: c.offset.unwrap_or(0),
+            // The cursor is used in place of the offset
+            offset: if cursor.is_some() {
+                0
+            } else {
+                c.offset.unwrap_or(0)
+            },
+            cursor,
             created_at_ge: c.created_at_ge,
             created_at_le: c.created_at_le,
             token_transaction_id: c.token_transaction_id,
@@ -90,7 +120,7 @@ impl From<TonTokenTransactionEventsRequest> for TokenTransactionsEventsSearch {
             transaction_direction: c.transaction_direction,
             transaction_status: c.transaction_status,
             event_status: c.event_status,
-        }
+        })
     }
 }
 
@@ -0,0 +1,35 @@
+use bigdecimal::BigDecimal;
+use opg::OpgModel;
+use serde::Deserialize;
+
+use crate::models::*;
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendingLimitRequest {
+    pub account: Option<String>,
+    pub root_address: Option<String>,
+    #[opg("maxPerTransaction", string, optional)]
+    pub max_per_transaction: Option<BigDecimal>,
+    #[opg("hourlyLimit", string, optional)]
+    pub hourly_limit: Option<BigDecimal>,
+    #[opg("dailyLimit", string, optional)]
+    pub daily_limit: Option<BigDecimal>,
+    #[opg("approvalThreshold", string, optional)]
+    pub approval_threshold: Option<BigDecimal>,
+    pub allowed_recipients: Option<Vec<String>>,
+}
+
+impl From<SpendingLimitRequest> for CreateSpendingLimit {
+    fn from(c: SpendingLimitRequest) -> Self {
+        CreateSpendingLimit {
+            account: c.account,
+            root_address: c.root_address,
+            max_per_transaction: c.max_per_transaction,
+            hourly_limit: c.hourly_limit,
+            daily_limit: c.daily_limit,
+            approval_threshold: c.approval_threshold,
+            allowed_recipients: c.allowed_recipients,
+        }
+    }
+}
@@ -91,4 +91,51 @@ pub struct SendMessageRequest {
 #[serde(rename_all = "camelCase")]
 pub struct SetCallbackRequest {
     pub callback: String,
+    pub notify_type: Option<NotifyType>,
+    pub transaction_direction: Option<TonTransactionDirection>,
+    pub root_address: Option<String>,
+    pub account: Option<String>,
+}
+
+impl From<SetCallbackRequest> for CreateCallback {
+    fn from(c: SetCallbackRequest) -> Self {
+        CreateCallback {
+            callback: c.callback,
+            notify_type: c.notify_type,
+            transaction_direction: c.transaction_direction,
+            root_address: c.root_address,
+            account: c.account,
+        }
+    }
+}
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct AddRootTokenRequest {
+    pub name: String,
+    pub address: Address,
+    pub version: TokenWalletVersionDb,
+}
+
+impl From<AddRootTokenRequest> for CreateRootToken {
+    fn from(c: AddRootTokenRequest) -> Self {
+        CreateRootToken {
+            name: c.name,
+            address: c.address,
+            version: c.version,
+        }
+    }
+}
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRootTokenRequest {
+    pub enabled: bool,
+}
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct SetServiceRootTokensRequest {
+    /// Removes the limit when not specified
+    pub root_addresses: Option<Vec<Address>>,
 }
@@ -1,9 +1,49 @@
 pub use self::address::*;
+pub use self::admin::*;
+pub use self::approvals::*;
 pub use self::events::*;
+pub use self::limits::*;
 pub use self::misc::*;
+pub use self::multisig::*;
+pub use self::reconciliation::*;
 pub use self::transactions::*;
 
 mod address;
+mod admin;
+mod approvals;
 mod events;
+mod limits;
 mod misc;
+mod multisig;
+mod reconciliation;
 mod transactions;
+
+fn parse_search_cursor(
+    cursor: Option<String>,
+) -> Result<Option<crate::models::SearchCursor>, crate::api::controllers::ControllersError> {
+    cursor
+        .map(|cursor| {
+            cursor.parse().map_err(|_| {
+                crate::api::controllers::ControllersError::WrongInput("Invalid cursor".to_string())
+            })
+        })
+        .transpose()
+}
+
+fn parse_timestamp_ms(
+    timestamp: Option<i64>,
+) -> Result<Option<chrono::NaiveDateTime>, crate::api::controllers::ControllersError> {
+    timestamp
+        .map(|timestamp| {
+            chrono::NaiveDateTime::from_timestamp_opt(
+                timestamp.div_euclid(1000),
+                timestamp.rem_euclid(1000) as u32 * 1_000_000,
+            )
+            .ok_or_else(|| {
+                crate::api::controllers::ControllersError::WrongInput(
+                    "Invalid timestamp".to_string(),
+                )
+            })
+        })
+        .transpose()
+}
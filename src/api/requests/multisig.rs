@@ -0,0 +1,12 @@
+use opg::OpgModel;
+use serde::Deserialize;
+
+use crate::models::*;
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("MultisigProposalsRequest")]
+pub struct MultisigProposalsRequest {
+    pub address: Option<Address>,
+    pub status: Option<MultisigProposalStatus>,
+}
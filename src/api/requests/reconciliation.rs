@@ -0,0 +1,42 @@
+use opg::OpgModel;
+use serde::Deserialize;
+
+use crate::api::controllers::ControllersError;
+use crate::models::*;
+use crate::prelude::*;
+
+#[derive(Deserialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("BalanceDiscrepanciesRequest")]
+pub struct BalanceDiscrepanciesRequest {
+    pub status: Option<DiscrepancyStatus>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl TryFrom<BalanceDiscrepanciesRequest> for BalanceDiscrepanciesSearch {
+    type Error = ControllersError;
+
+    fn try_from(c: BalanceDiscrepanciesRequest) -> Result<Self, Self::Error> {
+        let limit = c.limit.unwrap_or(MAX_LIMIT_SEARCH);
+        if !(1..=MAX_LIMIT_SEARCH).contains(&limit) {
+            return Err(ControllersError::WrongInput(format!(
+                "Limit must be from 1 to {}",
+                MAX_LIMIT_SEARCH
+            )));
+        }
+
+        let offset = c.offset.unwrap_or(0);
+        if offset < 0 {
+            return Err(ControllersError::WrongInput(
+                "Offset must not be negative".to_string(),
+            ));
+        }
+
+        Ok(BalanceDiscrepanciesSearch {
+            status: c.status,
+            limit,
+            offset,
+        })
+    }
+}
@@ -6,6 +6,7 @@ use opg::OpgModel;
 use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::api::controllers::ControllersError;
 use crate::models::*;
 use crate::prelude::*;
 
@@ -19,6 +20,7 @@ pub struct TonTransactionSendRequest {
     pub bounce: Option<bool>,
     #[opg("base64 encoded payload", string, optional)]
     pub payload: Option<String>,
+    pub retry_on_expire: Option<bool>,
 }
 
 impl From<TonTransactionSendRequest> for TransactionSend {
@@ -29,6 +31,7 @@ impl From<TonTransactionSendRequest> for TransactionSend {
             bounce: c.bounce,
             outputs: c.outputs.into_iter().map(From::from).collect(),
             payload: c.payload,
+            retry_on_expire: c.retry_on_expire.unwrap_or(false),
         }
     }
 }
@@ -87,13 +90,36 @@ pub struct TonTransactionsRequest {
     pub ordering: Option<TransactionsSearchOrdering>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    pub cursor: Option<String>,
 }
 
-impl From<TonTransactionsRequest> for TransactionsSearch {
-    fn from(c: TonTransactionsRequest) -> Self {
-        TransactionsSearch {
+impl TryFrom<TonTransactionsRequest> for TransactionsSearch {
+    type Error = ControllersError;
+
+    fn try_from(c: TonTransactionsRequest) -> Result<Self, Self::Error> {
+        let cursor = super::parse_search_cursor(c.cursor)?;
+
+        if cursor.is_some()
+            && !matches!(
+                c.ordering,
+                None | Some(TransactionsSearchOrdering::CreatedAtAsc)
+                    | Some(TransactionsSearchOrdering::CreatedAtDesc)
+            )
+        {
+            return Err(ControllersError::WrongInput(
+                "Cursor can only be used with created at ordering".to_string(),
+            ));
+        }
+
+        Ok(TransactionsSearch {
             limit: c.limit.unwrap_or(MAX_LIMIT_SEARCH),
-            offset: c.offset.unwrap_or(0),
+            // The cursor is used in place of the offset
+            offset: if cursor.is_some() {
+                0
+            } else {
+                c.offset.unwrap_or(0)
+            },
+            cursor,
             id: c.id,
             message_hash: c.message_hash,
             transaction_hash: c.transaction_hash,
@@ -103,7 +129,7 @@ impl From<TonTransactionsRequest> for TransactionsSearch {
             created_at_min: c.created_at_min,
             created_at_max: c.created_at_max,
             ordering: c.ordering,
-        }
+        })
     }
 }
 
@@ -124,6 +150,7 @@ pub struct TonTokenTransactionSendRequest {
     pub fee: Option<BigDecimal>,
     #[opg("base64 encoded payload", string, optional)]
     pub payload: Option<String>,
+    pub retry_on_expire: Option<bool>,
 }
 
 impl From<TonTokenTransactionSendRequest> for TokenTransactionSend {
@@ -140,6 +167,7 @@ impl From<TonTokenTransactionSendRequest> for TokenTransactionSend {
                 .fee
                 .unwrap_or_else(|| BigDecimal::from_u64(TOKEN_FEE).trust_me()),
             payload: c.payload,
+            retry_on_expire: c.retry_on_expire.unwrap_or(false),
         }
     }
 }
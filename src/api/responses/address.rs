@@ -177,6 +177,8 @@ pub struct AddressInfoDataResponse {
     pub custodians: Option<i32>,
     pub confirmations: Option<i32>,
     pub custodians_public_keys: Option<Vec<String>>,
+    pub signer_type: SignerType,
+    pub watch_only: bool,
     #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
     pub created_at: i64,
     #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
@@ -201,6 +203,8 @@ impl AddressInfoDataResponse {
             custodians_public_keys: a
                 .custodians_public_keys
                 .and_then(|k| serde_json::from_value(k).unwrap_or_default()),
+            signer_type: a.signer_type,
+            watch_only: a.watch_only,
             balance: a.balance,
             created_at: a.created_at.timestamp_millis(),
             updated_at: a.updated_at.timestamp_millis(),
@@ -250,10 +254,18 @@ pub struct TokenBalanceDataResponse {
     pub created_at: i64,
     #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
     pub updated_at: i64,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    #[opg("balance in tokens", string, optional)]
+    pub human_balance: Option<BigDecimal>,
 }
 
 impl TokenBalanceDataResponse {
-    pub fn new(a: TokenBalanceFromDb, b: NetworkTokenAddressData) -> Self {
+    pub fn new(
+        a: TokenBalanceFromDb,
+        b: NetworkTokenAddressData,
+        metadata: Option<TokenMetadata>,
+    ) -> Self {
         let account =
             MsgAddressInt::from_str(&format!("{}:{}", a.account_workchain_id, a.account_hex))
                 .trust_me();
@@ -272,6 +284,161 @@ impl TokenBalanceDataResponse {
             root_address: a.root_address,
             created_at: a.created_at.timestamp_millis(),
             updated_at: a.updated_at.timestamp_millis(),
+            human_balance: metadata.as_ref().map(|m| m.human_amount(&a.balance)),
+            symbol: metadata.as_ref().map(|m| m.symbol.clone()),
+            decimals: metadata.map(|m| m.decimals),
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("RescanTasksResponse")]
+pub struct RescanTasksResponse {
+    pub status: TonStatus,
+    pub data: Option<Vec<RescanTaskDataResponse>>,
+    pub error_message: Option<String>,
+}
+
+impl From<Result<Vec<RescanTaskDataResponse>, Error>> for RescanTasksResponse {
+    fn from(r: Result<Vec<RescanTaskDataResponse>, Error>) -> Self {
+        match r {
+            Ok(data) => Self {
+                status: TonStatus::Ok,
+                error_message: None,
+                data: Some(data),
+            },
+            Err(e) => Self {
+                status: TonStatus::Error,
+                error_message: Some(e.get_error()),
+                data: None,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("RescanTaskDataResponse")]
+pub struct RescanTaskDataResponse {
+    pub id: Uuid,
+    pub status: TaskStatus,
+    pub token_wallet: Option<String>,
+    pub transactions_found: i64,
+    pub transactions_inserted: i64,
+    pub next_transaction_lt: Option<String>,
+    /// Older transactions are not available in the node storage
+    pub pruned_before_lt: Option<String>,
+    pub error: Option<String>,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
+    pub created_at: i64,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
+    pub updated_at: i64,
+}
+
+impl From<TaskDb> for RescanTaskDataResponse {
+    fn from(t: TaskDb) -> Self {
+        let data: RescanTaskData = serde_json::from_value(t.data).unwrap_or_default();
+
+        Self {
+            id: t.id,
+            status: t.status,
+            token_wallet: data.token_wallet,
+            transactions_found: data.transactions_found,
+            transactions_inserted: data.transactions_inserted,
+            next_transaction_lt: data.cursor.map(|c| c.transaction_lt.to_string()),
+            pruned_before_lt: data.pruned_before_lt.map(|lt| lt.to_string()),
+            error: t.error,
+            created_at: t.created_at.timestamp_millis(),
+            updated_at: t.updated_at.timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("BalanceSnapshotResponse")]
+pub struct BalanceSnapshotResponse {
+    pub status: TonStatus,
+    pub data: Option<BalanceSnapshotDataResponse>,
+    pub error_message: Option<String>,
+}
+
+impl From<Result<Option<BalanceSnapshotDataResponse>, Error>> for BalanceSnapshotResponse {
+    fn from(r: Result<Option<BalanceSnapshotDataResponse>, Error>) -> Self {
+        match r {
+            Ok(data) => Self {
+                status: TonStatus::Ok,
+                data,
+                error_message: None,
+            },
+            Err(e) => Self {
+                status: TonStatus::Error,
+                data: None,
+                error_message: Some(e.get_error()),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("BalanceHistoryResponse")]
+pub struct BalanceHistoryResponse {
+    pub status: TonStatus,
+    pub data: Option<Vec<BalanceSnapshotDataResponse>>,
+    pub error_message: Option<String>,
+}
+
+impl From<Result<Vec<BalanceSnapshotDataResponse>, Error>> for BalanceHistoryResponse {
+    fn from(r: Result<Vec<BalanceSnapshotDataResponse>, Error>) -> Self {
+        match r {
+            Ok(data) => Self {
+                status: TonStatus::Ok,
+                data: Some(data),
+                error_message: None,
+            },
+            Err(e) => Self {
+                status: TonStatus::Error,
+                data: None,
+                error_message: Some(e.get_error()),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("BalanceSnapshotDataResponse")]
+pub struct BalanceSnapshotDataResponse {
+    pub address: Account,
+    pub root_address: Option<String>,
+    #[opg("balance", string)]
+    pub balance: BigDecimal,
+    pub last_transaction_lt: String,
+    pub account_status: AccountStatus,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
+    pub block_time: i64,
+}
+
+impl From<BalanceSnapshotDb> for BalanceSnapshotDataResponse {
+    fn from(s: BalanceSnapshotDb) -> Self {
+        let account =
+            MsgAddressInt::from_str(&format!("{}:{}", s.account_workchain_id, s.account_hex))
+                .trust_me();
+        let base64url = Address(pack_std_smc_addr(true, &account, true).trust_me());
+
+        Self {
+            address: Account {
+                workchain_id: s.account_workchain_id,
+                hex: Address(s.account_hex),
+                base64url,
+            },
+            root_address: s.root_address,
+            balance: s.balance,
+            last_transaction_lt: s.last_transaction_lt.to_string(),
+            account_status: s.account_status,
+            block_time: s.block_time.timestamp_millis(),
         }
     }
 }
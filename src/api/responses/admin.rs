@@ -0,0 +1,90 @@
+use opg::OpgModel;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::*;
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("ApiServiceResponse")]
+pub struct ApiServiceResponse {
+    pub id: ServiceId,
+    pub name: String,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
+    pub created_at: i64,
+}
+
+impl From<ApiServiceDb> for ApiServiceResponse {
+    fn from(s: ApiServiceDb) -> Self {
+        Self {
+            id: ServiceId::new(s.id),
+            name: s.name,
+            created_at: s.created_at.timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("ApiServicesResponse")]
+pub struct ApiServicesResponse {
+    pub count: i32,
+    pub items: Vec<ApiServiceResponse>,
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("ApiServiceKeyResponse")]
+pub struct ApiServiceKeyResponse {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub key: String,
+    /// Returned only when the key is created or its secret is rotated
+    pub secret: Option<String>,
+    pub whitelist: Option<Vec<String>>,
+    pub scopes: Option<Vec<KeyScope>>,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64", optional)]
+    pub expires_at: Option<i64>,
+    #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
+    pub created_at: i64,
+}
+
+impl ApiServiceKeyResponse {
+    pub fn with_secret(k: ApiServiceKeyDb) -> Self {
+        let secret = k.secret.clone();
+        Self {
+            secret: Some(secret),
+            ..Self::from(k)
+        }
+    }
+}
+
+impl From<ApiServiceKeyDb> for ApiServiceKeyResponse {
+    fn from(k: ApiServiceKeyDb) -> Self {
+        Self {
+            id: k.id,
+            service_id: k.service_id,
+            key: k.key,
+            secret: None,
+            whitelist: k
+                .whitelist
+                .and_then(|whitelist| serde_json::from_value(whitelist).ok()),
+            scopes: k.scopes.map(|scopes| {
+                scopes
+                    .iter()
+                    .filter_map(|scope| scope.parse().ok())
+                    .collect()
+            }),
+            expires_at: k.expires_at.map(|expires_at| expires_at.timestamp_millis()),
+            created_at: k.created_at.timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("ApiServiceKeysResponse")]
+pub struct ApiServiceKeysResponse {
+    pub count: i32,
+    pub items: Vec<ApiServiceKeyResponse>,
+}
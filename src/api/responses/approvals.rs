@@ -0,0 +1,64 @@
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use nekoton_utils::pack_std_smc_addr;
+use opg::OpgModel;
+use serde::Serialize;
+use ton_block::MsgAddressInt;
+use uuid::Uuid;
+
+use crate::models::*;
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferApprovalResponse {
+    pub id: Uuid,
+    pub notify_type: NotifyType,
+    pub account: Account,
+    pub root_address: Option<String>,
+    #[opg("value", string)]
+    pub value: BigDecimal,
+    #[opg(string, format = "any")]
+    pub input: serde_json::Value,
+    pub status: TonTransactionStatus,
+    pub requested_by: Uuid,
+    pub decided_by: Option<Uuid>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<TransferApprovalDb> for TransferApprovalResponse {
+    fn from(a: TransferApprovalDb) -> Self {
+        let account =
+            MsgAddressInt::from_str(&format!("{}:{}", a.account_workchain_id, a.account_hex))
+                .unwrap();
+        let base64url = Address(pack_std_smc_addr(true, &account, true).unwrap());
+
+        Self {
+            id: a.id,
+            notify_type: a.notify_type,
+            account: Account {
+                workchain_id: a.account_workchain_id,
+                hex: Address(a.account_hex),
+                base64url,
+            },
+            root_address: a.root_address,
+            value: a.value,
+            input: a.input,
+            status: a.status,
+            requested_by: a.requested_by,
+            decided_by: a.decided_by,
+            error: a.error,
+            created_at: a.created_at.timestamp_millis(),
+            updated_at: a.updated_at.timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferApprovalsResponse {
+    pub count: i32,
+    pub items: Vec<TransferApprovalResponse>,
+}
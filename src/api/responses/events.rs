@@ -10,6 +10,7 @@ use crate::models::*;
 pub struct EventsResponse {
     pub count: i32,
     pub items: Vec<AccountTransactionEvent>,
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Serialize, OpgModel)]
@@ -108,6 +109,7 @@ impl From<Result<TokenEventsResponse, Error>> for TonTokenEventsResponse {
 pub struct TokenEventsResponse {
     pub count: i32,
     pub items: Vec<AccountTransactionEvent>,
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Serialize, OpgModel)]
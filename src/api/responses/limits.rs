@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use nekoton_utils::pack_std_smc_addr;
+use opg::OpgModel;
+use serde::Serialize;
+use ton_block::MsgAddressInt;
+use uuid::Uuid;
+
+use crate::models::*;
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendingLimitResponse {
+    pub id: Uuid,
+    pub account: Option<Account>,
+    pub root_address: Option<String>,
+    #[opg("maxPerTransaction", string, optional)]
+    pub max_per_transaction: Option<BigDecimal>,
+    #[opg("hourlyLimit", string, optional)]
+    pub hourly_limit: Option<BigDecimal>,
+    #[opg("dailyLimit", string, optional)]
+    pub daily_limit: Option<BigDecimal>,
+    #[opg("approvalThreshold", string, optional)]
+    pub approval_threshold: Option<BigDecimal>,
+    pub allowed_recipients: Option<Vec<String>>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<SpendingLimitDb> for SpendingLimitResponse {
+    fn from(l: SpendingLimitDb) -> Self {
+        let account = if let (Some(account_workchain_id), Some(account_hex)) =
+            (l.account_workchain_id, l.account_hex)
+        {
+            let account =
+                MsgAddressInt::from_str(&format!("{}:{}", account_workchain_id, account_hex))
+                    .unwrap();
+            let base64url = Address(pack_std_smc_addr(true, &account, true).unwrap());
+            Some(Account {
+                workchain_id: account_workchain_id,
+                hex: Address(account_hex),
+                base64url,
+            })
+        } else {
+            None
+        };
+
+        Self {
+            id: l.id,
+            account,
+            root_address: l.root_address,
+            max_per_transaction: l.max_per_transaction,
+            hourly_limit: l.hourly_limit,
+            daily_limit: l.daily_limit,
+            approval_threshold: l.approval_threshold,
+            allowed_recipients: l.allowed_recipients,
+            created_at: l.created_at.timestamp_millis(),
+            updated_at: l.updated_at.timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendingLimitsResponse {
+    pub count: i32,
+    pub items: Vec<SpendingLimitResponse>,
+}
@@ -1,7 +1,13 @@
-use crate::models::WhitelistedTokenFromDb;
+use std::str::FromStr;
+
+use crate::models::*;
+use bigdecimal::BigDecimal;
 use nekoton_contracts::tip3_any::TokenWalletVersion;
+use nekoton_utils::pack_std_smc_addr;
 use opg::OpgModel;
 use serde::Serialize;
+use ton_block::MsgAddressInt;
+use uuid::Uuid;
 
 #[derive(Serialize, OpgModel)]
 #[serde(rename_all = "camelCase")]
@@ -34,12 +40,69 @@ pub struct SetCallbackResponse {
     pub callback: String,
 }
 
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct CallbackResponse {
+    pub id: Uuid,
+    pub callback: String,
+    pub notify_type: Option<NotifyType>,
+    pub transaction_direction: Option<TonTransactionDirection>,
+    pub root_address: Option<String>,
+    pub account: Option<Account>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl TryFrom<ApiServiceCallbackDb> for CallbackResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(c: ApiServiceCallbackDb) -> Result<Self, Self::Error> {
+        let account = if let (Some(account_workchain_id), Some(account_hex)) =
+            (c.account_workchain_id, c.account_hex)
+        {
+            let account =
+                MsgAddressInt::from_str(&format!("{}:{}", account_workchain_id, account_hex))?;
+            let base64url = Address(pack_std_smc_addr(true, &account, true)?);
+            Some(Account {
+                workchain_id: account_workchain_id,
+                hex: Address(account_hex),
+                base64url,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            id: c.id,
+            callback: c.callback,
+            notify_type: c.notify_type,
+            transaction_direction: c.transaction_direction,
+            root_address: c.root_address,
+            account,
+            created_at: c.created_at.timestamp_millis(),
+            updated_at: c.updated_at.timestamp_millis(),
+        })
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct CallbacksResponse {
+    pub count: i32,
+    pub items: Vec<CallbackResponse>,
+}
+
 #[derive(Serialize, OpgModel)]
 #[serde(rename_all = "camelCase")]
 pub struct WhitelistedTokenResponse {
     pub name: String,
     pub address: String,
     pub version: String,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    #[opg("totalSupply", string, optional)]
+    pub total_supply: Option<BigDecimal>,
+    pub enabled: bool,
 }
 
 impl From<WhitelistedTokenFromDb> for WhitelistedTokenResponse {
@@ -48,6 +111,10 @@ impl From<WhitelistedTokenFromDb> for WhitelistedTokenResponse {
             name: t.name,
             address: t.address,
             version: TokenWalletVersion::from(t.version).to_string(),
+            symbol: t.symbol,
+            decimals: t.decimals,
+            total_supply: t.total_supply,
+            enabled: t.enabled,
         }
     }
 }
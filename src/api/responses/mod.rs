@@ -1,13 +1,23 @@
 pub use self::address::*;
+pub use self::admin::*;
+pub use self::approvals::*;
 pub use self::blockchain::*;
 pub use self::events::*;
+pub use self::limits::*;
 pub use self::misc::*;
+pub use self::multisig::*;
+pub use self::reconciliation::*;
 pub use self::ton_metrics::*;
 pub use self::transactions::*;
 
 mod address;
+mod admin;
+mod approvals;
 mod blockchain;
 mod events;
+mod limits;
 mod misc;
+mod multisig;
+mod reconciliation;
 mod ton_metrics;
 mod transactions;
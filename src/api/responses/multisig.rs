@@ -0,0 +1,67 @@
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use nekoton_utils::pack_std_smc_addr;
+use opg::OpgModel;
+use serde::Serialize;
+use ton_block::MsgAddressInt;
+use uuid::Uuid;
+
+use crate::models::*;
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigProposalResponse {
+    pub id: Uuid,
+    pub account: Account,
+    pub transaction_id: i64,
+    pub creator: String,
+    pub destination: String,
+    #[opg("value", string)]
+    pub value: BigDecimal,
+    pub bounce: bool,
+    pub signs_required: i32,
+    pub signs_received: i32,
+    pub confirmations: Vec<String>,
+    pub expire_at: i64,
+    pub status: MultisigProposalStatus,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<MultisigProposalDb> for MultisigProposalResponse {
+    fn from(p: MultisigProposalDb) -> Self {
+        let account =
+            MsgAddressInt::from_str(&format!("{}:{}", p.account_workchain_id, p.account_hex))
+                .unwrap();
+        let base64url = Address(pack_std_smc_addr(true, &account, true).unwrap());
+
+        Self {
+            id: p.id,
+            account: Account {
+                workchain_id: p.account_workchain_id,
+                hex: Address(p.account_hex),
+                base64url,
+            },
+            transaction_id: p.transaction_id,
+            creator: p.creator,
+            destination: p.destination,
+            value: p.value,
+            bounce: p.bounce,
+            signs_required: p.signs_required,
+            signs_received: p.signs_received,
+            confirmations: p.confirmations,
+            expire_at: p.expire_at.timestamp_millis(),
+            status: p.status,
+            created_at: p.created_at.timestamp_millis(),
+            updated_at: p.updated_at.timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigProposalsResponse {
+    pub count: i32,
+    pub items: Vec<MultisigProposalResponse>,
+}
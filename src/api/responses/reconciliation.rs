@@ -0,0 +1,62 @@
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use nekoton_utils::pack_std_smc_addr;
+use opg::OpgModel;
+use serde::Serialize;
+use ton_block::MsgAddressInt;
+use uuid::Uuid;
+
+use crate::models::*;
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceDiscrepancyResponse {
+    pub id: Uuid,
+    pub account: Account,
+    pub root_address: Option<String>,
+    #[opg("ledgerBalance", string)]
+    pub ledger_balance: BigDecimal,
+    #[opg("networkBalance", string)]
+    pub network_balance: BigDecimal,
+    /// Network balance minus ledger balance
+    #[opg("difference", string)]
+    pub difference: BigDecimal,
+    pub status: DiscrepancyStatus,
+    pub rescan_requested: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl From<BalanceDiscrepancyDb> for BalanceDiscrepancyResponse {
+    fn from(d: BalanceDiscrepancyDb) -> Self {
+        let account =
+            MsgAddressInt::from_str(&format!("{}:{}", d.account_workchain_id, d.account_hex))
+                .unwrap();
+        let base64url = Address(pack_std_smc_addr(true, &account, true).unwrap());
+
+        Self {
+            id: d.id,
+            account: Account {
+                workchain_id: d.account_workchain_id,
+                hex: Address(d.account_hex),
+                base64url,
+            },
+            root_address: d.root_address,
+            difference: &d.network_balance - &d.ledger_balance,
+            ledger_balance: d.ledger_balance,
+            network_balance: d.network_balance,
+            status: d.status,
+            rescan_requested: d.rescan_requested,
+            created_at: d.created_at.timestamp_millis(),
+            updated_at: d.updated_at.timestamp_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceDiscrepanciesResponse {
+    pub count: i32,
+    pub items: Vec<BalanceDiscrepancyResponse>,
+}
@@ -70,6 +70,53 @@ pub struct TransactionDataResponse {
     pub created_at: i64,
     #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
     pub updated_at: i64,
+    /// Payout batch sent with the id of the transaction
+    pub batch: Option<PayoutBatchResponse>,
+    /// Tokens transferred, minted or burned by the transaction
+    pub token: Option<TokenAmountResponse>,
+}
+
+impl TransactionDataResponse {
+    pub fn with_token(self, token: TokenAmountResponse) -> Self {
+        Self {
+            token: Some(token),
+            ..self
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("TokenAmountResponse")]
+pub struct TokenAmountResponse {
+    pub root_address: String,
+    #[opg("value", string)]
+    pub value: BigDecimal,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    #[opg("value in tokens", string, optional)]
+    pub human_value: Option<BigDecimal>,
+}
+
+impl TokenAmountResponse {
+    pub fn new(root_address: String, value: BigDecimal, metadata: Option<TokenMetadata>) -> Self {
+        match metadata {
+            Some(metadata) => Self {
+                human_value: Some(metadata.human_amount(&value)),
+                symbol: Some(metadata.symbol),
+                decimals: Some(metadata.decimals),
+                root_address,
+                value,
+            },
+            None => Self {
+                root_address,
+                value,
+                symbol: None,
+                decimals: None,
+                human_value: None,
+            },
+        }
+    }
 }
 
 impl From<TransactionDb> for TransactionDataResponse {
@@ -93,24 +140,7 @@ impl From<TransactionDb> for TransactionDataResponse {
         let original_outputs = if let Some(outputs) = c.original_outputs {
             serde_json::from_value(outputs.clone())
                 .map(|original_outputs: Vec<TransactionSendOutput>| {
-                    original_outputs
-                        .into_iter()
-                        .map(|output| {
-                            let output_address =
-                                nekoton_utils::repack_address(&output.recipient_address.0)
-                                    .unwrap_or_default();
-                            let output_base64url =
-                                Address(pack_std_smc_addr(true, &output_address, true).unwrap());
-                            TransactionOutput {
-                                value: output.value,
-                                recipient: Account {
-                                    workchain_id: output_address.workchain_id(),
-                                    hex: Address(output_address.address().to_hex_string()),
-                                    base64url: output_base64url,
-                                },
-                            }
-                        })
-                        .collect()
+                    original_outputs.into_iter().map(From::from).collect()
                 })
                 .or_else(|_| serde_json::from_value(outputs))
                 .ok()
@@ -150,6 +180,8 @@ impl From<TransactionDb> for TransactionDataResponse {
             updated_at: c.updated_at.timestamp_millis(),
             error: c.error,
             multisig_transaction_id: c.multisig_transaction_id,
+            batch: None,
+            token: None,
         }
     }
 }
@@ -175,6 +207,66 @@ pub struct TransactionOutput {
     pub recipient: Account,
 }
 
+impl From<TransactionSendOutput> for TransactionOutput {
+    fn from(c: TransactionSendOutput) -> Self {
+        let output_address =
+            nekoton_utils::repack_address(&c.recipient_address.0).unwrap_or_default();
+        let output_base64url = Address(pack_std_smc_addr(true, &output_address, true).unwrap());
+
+        TransactionOutput {
+            value: c.value,
+            recipient: Account {
+                workchain_id: output_address.workchain_id(),
+                hex: Address(output_address.address().to_hex_string()),
+                base64url: output_base64url,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("PayoutBatchResponse")]
+pub struct PayoutBatchResponse {
+    pub status: PayoutBatchStatus,
+    pub outputs: Vec<PayoutOutputResponse>,
+}
+
+impl From<PayoutBatch> for PayoutBatchResponse {
+    fn from(c: PayoutBatch) -> Self {
+        PayoutBatchResponse {
+            status: c.batch.status,
+            outputs: c.outputs.into_iter().map(From::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("PayoutOutputResponse")]
+pub struct PayoutOutputResponse {
+    pub position: i32,
+    #[opg("transactionId", string)]
+    pub transaction_id: Uuid,
+    pub output: Option<TransactionOutput>,
+    pub status: PayoutOutputStatus,
+    pub error: Option<String>,
+}
+
+impl From<PayoutOutputDb> for PayoutOutputResponse {
+    fn from(c: PayoutOutputDb) -> Self {
+        PayoutOutputResponse {
+            position: c.position,
+            transaction_id: c.transaction_id,
+            output: serde_json::from_value::<TransactionSendOutput>(c.output)
+                .map(From::from)
+                .ok(),
+            status: c.status,
+            error: c.error,
+        }
+    }
+}
+
 #[derive(Serialize, OpgModel)]
 #[serde(rename_all = "camelCase")]
 #[opg("TonTransactionsResponse")]
@@ -207,6 +299,7 @@ impl From<Result<TransactionsResponse, Error>> for TonTransactionsResponse {
 pub struct TransactionsResponse {
     pub count: i32,
     pub items: Vec<TransactionDataResponse>,
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Serialize, OpgModel)]
@@ -256,6 +349,24 @@ pub struct TokenTransactionDataResponse {
     #[opg("UTC timestamp in milliseconds", integer, format = "int64")]
     pub updated_at: i64,
     pub payload: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    #[opg("value in tokens", string, optional)]
+    pub human_value: Option<BigDecimal>,
+}
+
+impl TokenTransactionDataResponse {
+    pub fn with_metadata(self, metadata: Option<TokenMetadata>) -> Self {
+        match metadata {
+            Some(metadata) => Self {
+                human_value: Some(metadata.human_amount(&self.value)),
+                symbol: Some(metadata.symbol),
+                decimals: Some(metadata.decimals),
+                ..self
+            },
+            None => self,
+        }
+    }
 }
 
 impl From<TokenTransactionFromDb> for TokenTransactionDataResponse {
@@ -285,6 +396,67 @@ impl From<TokenTransactionFromDb> for TokenTransactionDataResponse {
             created_at: c.created_at.timestamp_millis(),
             updated_at: c.updated_at.timestamp_millis(),
             payload,
+            symbol: None,
+            decimals: None,
+            human_value: None,
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("EstimateFeesResponse")]
+pub struct EstimateFeesResponse {
+    pub status: TonStatus,
+    pub data: Option<EstimateFeesDataResponse>,
+    pub error_message: Option<String>,
+}
+
+impl From<Result<EstimatedFees, Error>> for EstimateFeesResponse {
+    fn from(r: Result<EstimatedFees, Error>) -> Self {
+        match r {
+            Ok(data) => Self {
+                status: TonStatus::Ok,
+                error_message: None,
+                data: Some(data.into()),
+            },
+            Err(e) => Self {
+                status: TonStatus::Error,
+                error_message: Some(e.get_error()),
+                data: None,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("EstimateFeesDataResponse")]
+pub struct EstimateFeesDataResponse {
+    pub gas_used: u64,
+    #[opg("gasFees", string)]
+    pub gas_fees: BigDecimal,
+    #[opg("fwdFees", string)]
+    pub fwd_fees: BigDecimal,
+    #[opg("storageFees", string)]
+    pub storage_fees: BigDecimal,
+    #[opg("totalFees", string)]
+    pub total_fees: BigDecimal,
+    #[opg("balance", string)]
+    pub balance: BigDecimal,
+    pub sufficient_balance: bool,
+}
+
+impl From<EstimatedFees> for EstimateFeesDataResponse {
+    fn from(c: EstimatedFees) -> Self {
+        EstimateFeesDataResponse {
+            gas_used: c.gas_used,
+            gas_fees: BigDecimal::from(c.gas_fees),
+            fwd_fees: BigDecimal::from(c.fwd_fees),
+            storage_fees: BigDecimal::from(c.storage_fees),
+            total_fees: BigDecimal::from(c.total_fees),
+            balance: BigDecimal::from(c.balance),
+            sufficient_balance: c.sufficient_balance,
         }
     }
 }
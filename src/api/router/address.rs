@@ -3,12 +3,48 @@ use axum::{
     Router,
 };
 
-use crate::api::controllers;
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
 
 pub fn router() -> Router {
     Router::new()
-        .route("/check", post(controllers::post_address_check))
-        .route("/create", post(controllers::post_address_create))
-        .route("/:address", get(controllers::get_address_balance))
-        .route("/:address/info", get(controllers::get_address_info))
+        .route(
+            "/check",
+            post(controllers::post_address_check).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/create",
+            post(controllers::post_address_create)
+                .route_layer(RequireScope(KeyScope::CreateAddress)),
+        )
+        .route(
+            "/import",
+            post(controllers::post_address_import)
+                .route_layer(RequireScope(KeyScope::CreateAddress)),
+        )
+        .route(
+            "/:address",
+            get(controllers::get_address_balance).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/:address/info",
+            get(controllers::get_address_info).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/:address/balance",
+            get(controllers::get_address_balance_at).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/:address/balance/history",
+            get(controllers::get_address_balance_history).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/:address/rescan",
+            get(controllers::get_address_rescan).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/:address/rescan",
+            post(controllers::post_address_rescan)
+                .route_layer(RequireScope(KeyScope::CreateAddress)),
+        )
 }
@@ -0,0 +1,45 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::api::controllers;
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/services",
+            get(controllers::get_admin_services).post(controllers::post_admin_services),
+        )
+        .route(
+            "/services/:id",
+            get(controllers::get_admin_service).post(controllers::post_admin_service),
+        )
+        .route(
+            "/services/:id/delete",
+            post(controllers::post_admin_service_delete),
+        )
+        .route(
+            "/services/:id/keys",
+            get(controllers::get_admin_service_keys).post(controllers::post_admin_service_keys),
+        )
+        .route("/keys/:id", post(controllers::post_admin_key))
+        .route("/keys/:id/rotate", post(controllers::post_admin_key_rotate))
+        .route("/keys/:id/delete", post(controllers::post_admin_key_delete))
+        .route(
+            "/tokens/whitelist",
+            post(controllers::post_admin_root_token),
+        )
+        .route(
+            "/tokens/whitelist/service/:id",
+            post(controllers::post_admin_service_root_tokens),
+        )
+        .route(
+            "/tokens/whitelist/:address",
+            post(controllers::post_admin_root_token_update),
+        )
+        .route(
+            "/tokens/whitelist/:address/delete",
+            post(controllers::post_admin_root_token_delete),
+        )
+}
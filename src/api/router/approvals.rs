@@ -0,0 +1,27 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(controllers::get_transfer_approvals).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/:id",
+            get(controllers::get_transfer_approval).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/:id/approve",
+            post(controllers::post_approve_transfer).route_layer(RequireScope(KeyScope::Approve)),
+        )
+        .route(
+            "/:id/reject",
+            post(controllers::post_reject_transfer).route_layer(RequireScope(KeyScope::Approve)),
+        )
+}
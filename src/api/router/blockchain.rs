@@ -1,7 +1,11 @@
 use axum::{routing::get, Router};
 
-use crate::api::controllers;
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
 
 pub fn router() -> Router {
-    Router::new().route("/", get(controllers::get_blockchain_info))
+    Router::new().route(
+        "/",
+        get(controllers::get_blockchain_info).route_layer(RequireScope(KeyScope::Read)),
+    )
 }
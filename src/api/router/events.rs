@@ -3,12 +3,29 @@ use axum::{
     Router,
 };
 
-use crate::api::controllers;
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
 
 pub fn router() -> Router {
     Router::new()
-        .route("/", post(controllers::post_events))
-        .route("/mark", post(controllers::post_events_mark))
-        .route("/mark/all", post(controllers::post_events_mark_all))
-        .route("/id/:id", get(controllers::get_events_id))
+        .route(
+            "/",
+            post(controllers::post_events).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/mark",
+            post(controllers::post_events_mark).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/mark/all",
+            post(controllers::post_events_mark_all).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/id/:id",
+            get(controllers::get_events_id).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/stream",
+            get(controllers::get_events_stream).route_layer(RequireScope(KeyScope::Read)),
+        )
 }
@@ -0,0 +1,30 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(controllers::get_spending_limits).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/",
+            post(controllers::post_create_spending_limit)
+                .route_layer(RequireScope(KeyScope::Admin)),
+        )
+        .route(
+            "/:id",
+            post(controllers::post_update_spending_limit)
+                .route_layer(RequireScope(KeyScope::Admin)),
+        )
+        .route(
+            "/:id/delete",
+            post(controllers::post_delete_spending_limit)
+                .route_layer(RequireScope(KeyScope::Admin)),
+        )
+}
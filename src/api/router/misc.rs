@@ -1,22 +1,52 @@
-use axum::{routing::post, Router};
+use axum::{
+    routing::{get, post},
+    Router,
+};
 
-use crate::api::controllers;
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
 
 pub fn router() -> Router {
     Router::new()
-        .route("/callback", post(controllers::post_set_callback))
-        .route("/read-contract", post(controllers::post_read_contract))
-        .route("/encode-into-cell", post(controllers::post_encode_tvm_cell))
+        .route(
+            "/callback",
+            get(controllers::get_callbacks).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/callback",
+            post(controllers::post_set_callback).route_layer(RequireScope(KeyScope::Admin)),
+        )
+        .route(
+            "/callback/id/:id",
+            get(controllers::get_callback_id).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/callback/id/:id",
+            post(controllers::post_update_callback).route_layer(RequireScope(KeyScope::Admin)),
+        )
+        .route(
+            "/callback/id/:id/delete",
+            post(controllers::post_delete_callback).route_layer(RequireScope(KeyScope::Admin)),
+        )
+        .route(
+            "/read-contract",
+            post(controllers::post_read_contract).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/encode-into-cell",
+            post(controllers::post_encode_tvm_cell).route_layer(RequireScope(KeyScope::Read)),
+        )
         .route(
             "/prepare-message",
-            post(controllers::post_prepare_generic_message),
+            post(controllers::post_prepare_generic_message)
+                .route_layer(RequireScope(KeyScope::Read)),
         )
         .route(
             "/send-signed-message",
-            post(controllers::post_send_signed_message),
+            post(controllers::post_send_signed_message).route_layer(RequireScope(KeyScope::Send)),
         )
         .route(
             "/send-message",
-            post(controllers::post_send_generic_message),
+            post(controllers::post_send_generic_message).route_layer(RequireScope(KeyScope::Send)),
         )
 }
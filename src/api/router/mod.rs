@@ -13,9 +13,14 @@ use crate::api::*;
 use crate::services::*;
 
 mod address;
+mod admin;
+mod approvals;
 mod blockchain;
 mod events;
+mod limits;
 mod misc;
+mod multisig;
+mod reconciliation;
 mod tokens;
 mod ton_metrics;
 mod transactions;
@@ -63,6 +68,26 @@ pub fn router(
         .layer(in_flight_requests_layer)
 }
 
+pub fn admin_router(
+    auth_service: Arc<AuthService>,
+    ton_service: Arc<TonService>,
+    auth_token: String,
+) -> Router {
+    admin_api_router(auth_token)
+        .layer(Extension(auth_service))
+        .layer(Extension(ton_service))
+}
+
+fn admin_api_router(auth_token: String) -> Router {
+    let auth_token = Arc::new(auth_token);
+
+    Router::new()
+        .nest(API_PREFIX, admin::router())
+        .layer(axum::middleware::from_fn(move |req, next| {
+            controllers::verify_admin_auth(req, next, auth_token.clone())
+        }))
+}
+
 fn api_router(
     auth_service: Arc<AuthService>,
     ton_service: Arc<TonService>,
@@ -75,7 +100,15 @@ fn api_router(
         .nest("/tokens", tokens::router())
         .nest("/misc", misc::router())
         .nest("/transactions", transactions::router())
+        .nest("/limits", limits::router())
+        .nest("/approvals", approvals::router())
+        .nest("/multisig", multisig::router())
+        .nest("/reconciliation", reconciliation::router())
         .nest("/metrics", ton_metrics::router())
+        .layer(axum::middleware::from_fn({
+            let ton_service = ton_service.clone();
+            move |req, next| controllers::verify_idempotency(req, next, ton_service.clone())
+        }))
         .layer(axum::middleware::from_fn(move |req, next| {
             controllers::verify_auth(req, next, auth_service.clone())
         }))
@@ -84,3 +117,53 @@ fn api_router(
             memory_storage,
         })))
 }
+
+#[cfg(test)]
+mod tests {
+    use http::{Request, StatusCode};
+    use hyper::Body;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn signed_request(uri: &str) -> Request<Body> {
+        Request::post(uri)
+            .header("api-key", "key")
+            .header("timestamp", "1700000000000")
+            .header("sign", "sign")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from("{}"))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn service_keys_cannot_manage_whitelist() {
+        let address = "0:1111111111111111111111111111111111111111111111111111111111111111";
+
+        for uri in [
+            "/whitelist".to_string(),
+            format!("/whitelist/service/{}", uuid::Uuid::new_v4()),
+            format!("/whitelist/{}", address),
+            format!("/whitelist/{}/delete", address),
+        ] {
+            let response = tokens::router()
+                .oneshot(signed_request(&uri))
+                .await
+                .unwrap();
+            assert!(
+                matches!(
+                    response.status(),
+                    StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED
+                ),
+                "{} is available to service keys",
+                uri
+            );
+
+            let response = admin_api_router("token".to_string())
+                .oneshot(signed_request(&format!("{}/tokens{}", API_PREFIX, uri)))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+    }
+}
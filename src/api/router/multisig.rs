@@ -0,0 +1,29 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/proposals",
+            get(controllers::get_multisig_proposals).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/proposals/:id",
+            get(controllers::get_multisig_proposal).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/proposals/:id/confirm",
+            post(controllers::post_confirm_multisig_proposal)
+                .route_layer(RequireScope(KeyScope::Send)),
+        )
+        .route(
+            "/proposals/:id/reject",
+            post(controllers::post_reject_multisig_proposal)
+                .route_layer(RequireScope(KeyScope::Send)),
+        )
+}
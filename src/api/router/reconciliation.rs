@@ -0,0 +1,11 @@
+use axum::{routing::get, Router};
+
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
+
+pub fn router() -> Router {
+    Router::new().route(
+        "/discrepancies",
+        get(controllers::get_balance_discrepancies).route_layer(RequireScope(KeyScope::Read)),
+    )
+}
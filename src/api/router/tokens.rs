@@ -3,35 +3,53 @@ use axum::{
     Router,
 };
 
-use crate::api::controllers;
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
 
 pub fn router() -> Router {
     Router::new()
         .route(
             "/address/:address",
-            get(controllers::get_token_address_balance),
+            get(controllers::get_token_address_balance).route_layer(RequireScope(KeyScope::Read)),
         )
         .route(
             "/transactions/id/:internal_id",
-            get(controllers::get_tokens_transactions_id),
+            get(controllers::get_tokens_transactions_id).route_layer(RequireScope(KeyScope::Read)),
         )
         .route(
             "/transactions/mh/:message_hash",
-            get(controllers::get_tokens_transactions_mh),
+            get(controllers::get_tokens_transactions_mh).route_layer(RequireScope(KeyScope::Read)),
         )
         .route(
             "/transactions/create",
-            post(controllers::post_tokens_transactions_create),
+            post(controllers::post_tokens_transactions_create)
+                .route_layer(RequireScope(KeyScope::Send)),
+        )
+        .route(
+            "/transactions/estimate",
+            post(controllers::post_tokens_transactions_estimate)
+                .route_layer(RequireScope(KeyScope::Read)),
         )
         .route(
             "/transactions/burn",
-            post(controllers::post_tokens_transactions_burn),
+            post(controllers::post_tokens_transactions_burn)
+                .route_layer(RequireScope(KeyScope::Send)),
         )
         .route(
             "/transactions/mint",
-            post(controllers::post_tokens_transactions_mint),
+            post(controllers::post_tokens_transactions_mint)
+                .route_layer(RequireScope(KeyScope::TokensMint)),
+        )
+        .route(
+            "/events",
+            post(controllers::post_tokens_events).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/events/mark",
+            post(controllers::post_tokens_events_mark).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/whitelist",
+            get(controllers::get_token_whitelist).route_layer(RequireScope(KeyScope::Read)),
         )
-        .route("/events", post(controllers::post_tokens_events))
-        .route("/events/mark", post(controllers::post_tokens_events_mark))
-        .route("/whitelist", get(controllers::get_token_whitelist))
 }
@@ -1,7 +1,11 @@
 use axum::{routing::get, Router};
 
-use crate::api::controllers;
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
 
 pub fn router() -> Router {
-    Router::new().route("/", get(controllers::get_ton_metrics))
+    Router::new().route(
+        "/",
+        get(controllers::get_ton_metrics).route_layer(RequireScope(KeyScope::Read)),
+    )
 }
@@ -3,14 +3,37 @@ use axum::{
     Router,
 };
 
-use crate::api::controllers;
+use crate::api::controllers::{self, RequireScope};
+use crate::models::KeyScope;
 
 pub fn router() -> Router {
     Router::new()
-        .route("/", post(controllers::post_transactions))
-        .route("/create", post(controllers::post_transactions_create))
-        .route("/confirm", post(controllers::post_transactions_confirm))
-        .route("/id/:id", get(controllers::get_transactions_id))
-        .route("/h/:hash", get(controllers::get_transactions_h))
-        .route("/mh/:message_hash", get(controllers::get_transactions_mh))
+        .route(
+            "/",
+            post(controllers::post_transactions).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/create",
+            post(controllers::post_transactions_create).route_layer(RequireScope(KeyScope::Send)),
+        )
+        .route(
+            "/estimate",
+            post(controllers::post_transactions_estimate).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/confirm",
+            post(controllers::post_transactions_confirm).route_layer(RequireScope(KeyScope::Send)),
+        )
+        .route(
+            "/id/:id",
+            get(controllers::get_transactions_id).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/h/:hash",
+            get(controllers::get_transactions_h).route_layer(RequireScope(KeyScope::Read)),
+        )
+        .route(
+            "/mh/:message_hash",
+            get(controllers::get_transactions_mh).route_layer(RequireScope(KeyScope::Read)),
+        )
 }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use chrono::Utc;
 use http::Method;
@@ -5,6 +7,7 @@ use nekoton_utils::TrustMe;
 use reqwest::Url;
 
 use crate::models::*;
+use crate::prelude::*;
 
 #[derive(Clone)]
 pub struct CallbackClient {
@@ -14,7 +17,10 @@ pub struct CallbackClient {
 impl CallbackClient {
     pub fn new() -> Self {
         Self {
-            client: reqwest::ClientBuilder::new().build().trust_me(),
+            client: reqwest::ClientBuilder::new()
+                .timeout(Duration::from_secs(WEBHOOK_REQUEST_TIMEOUT))
+                .build()
+                .trust_me(),
         }
     }
 }
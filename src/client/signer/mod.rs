@@ -0,0 +1,340 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer as _};
+use nekoton_utils::TrustMe;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::settings::RemoteSignerConfig;
+
+pub use self::stub::*;
+
+mod stub;
+
+/// Signs messages on behalf of an address
+#[async_trait::async_trait]
+pub trait Signer: Send + Sync {
+    fn public_key(&self) -> &PublicKey;
+
+    async fn sign(&self, data: &[u8]) -> Result<[u8; ed25519_dalek::SIGNATURE_LENGTH]>;
+}
+
+/// Signs with the private key stored encrypted in the database
+pub struct LocalSigner {
+    key_pair: Keypair,
+}
+
+impl LocalSigner {
+    pub fn new(public_key: PublicKey, private_key: &[u8]) -> Result<Self> {
+        Ok(Self {
+            key_pair: Keypair {
+                secret: SecretKey::from_bytes(private_key)?,
+                public: public_key,
+            },
+        })
+    }
+
+    pub fn generate_key_pair() -> Result<Keypair> {
+        let generated_key = nekoton::crypto::generate_key(nekoton::crypto::MnemonicType::Labs(0));
+        nekoton::crypto::derive_from_phrase(
+            &generated_key.words.join(" "),
+            generated_key.account_type,
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for LocalSigner {
+    fn public_key(&self) -> &PublicKey {
+        &self.key_pair.public
+    }
+
+    async fn sign(&self, data: &[u8]) -> Result<[u8; ed25519_dalek::SIGNATURE_LENGTH]> {
+        Ok(self.key_pair.sign(data).to_bytes())
+    }
+}
+
+/// Signs with the key held by the external signing daemon
+pub struct RemoteSigner {
+    client: Arc<RemoteSignerClient>,
+    key_id: Uuid,
+    public_key: PublicKey,
+}
+
+impl RemoteSigner {
+    pub fn new(client: Arc<RemoteSignerClient>, key_id: Uuid, public_key: PublicKey) -> Self {
+        Self {
+            client,
+            key_id,
+            public_key,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    async fn sign(&self, data: &[u8]) -> Result<[u8; ed25519_dalek::SIGNATURE_LENGTH]> {
+        let signature = self
+            .client
+            .sign(&self.key_id, &self.public_key, data)
+            .await?;
+
+        // Never trust the daemon blindly
+        let signature = ed25519_dalek::Signature::from_bytes(&signature)?;
+        self.public_key
+            .verify_strict(data, &signature)
+            .context("Invalid signature from remote signer")?;
+
+        Ok(signature.to_bytes())
+    }
+}
+
+/// Produces an empty signature, used to build messages for the local execution only
+pub struct EstimationSigner {
+    public_key: PublicKey,
+}
+
+impl EstimationSigner {
+    pub fn new(public_key: PublicKey) -> Self {
+        Self { public_key }
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for EstimationSigner {
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    async fn sign(&self, _: &[u8]) -> Result<[u8; ed25519_dalek::SIGNATURE_LENGTH]> {
+        Ok([0; ed25519_dalek::SIGNATURE_LENGTH])
+    }
+}
+
+/// Client of the external signing daemon
+pub struct RemoteSignerClient {
+    client: reqwest::Client,
+    url: Url,
+    auth_token: Option<String>,
+}
+
+impl RemoteSignerClient {
+    pub fn new(config: &RemoteSignerConfig) -> Result<Self> {
+        let mut url = Url::parse(&config.url).context("Invalid remote signer url")?;
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+
+        Ok(Self {
+            client: reqwest::ClientBuilder::new()
+                .timeout(Duration::from_secs(config.request_timeout_sec))
+                .build()
+                .trust_me(),
+            url,
+            auth_token: config.auth_token.clone(),
+        })
+    }
+
+    /// Generates a new key pair identified by `key_id` and returns its public key
+    pub async fn generate_key(&self, key_id: &Uuid) -> Result<PublicKey> {
+        let res: GenerateKeyResponse = self
+            .post("keys", &GenerateKeyRequest { key_id: *key_id })
+            .await?;
+
+        Ok(PublicKey::from_bytes(&hex::decode(res.public_key)?)?)
+    }
+
+    pub async fn sign(
+        &self,
+        key_id: &Uuid,
+        public_key: &PublicKey,
+        data: &[u8],
+    ) -> Result<[u8; ed25519_dalek::SIGNATURE_LENGTH]> {
+        let res: SignResponse = self
+            .post(
+                "sign",
+                &SignRequest {
+                    key_id: *key_id,
+                    public_key: hex::encode(public_key.as_bytes()),
+                    data: base64::encode(data),
+                },
+            )
+            .await?;
+
+        hex::decode(res.signature)?
+            .as_slice()
+            .try_into()
+            .context("Invalid signature length")
+    }
+
+    async fn post<T, R>(&self, path: &str, payload: &T) -> Result<R>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        let mut request = self.client.post(self.url.join(path)?).json(payload);
+        if let Some(auth_token) = &self.auth_token {
+            request = request.bearer_auth(auth_token);
+        }
+
+        let res = request.send().await?;
+        if !res.status().is_success() {
+            anyhow::bail!("Remote signer responded with status {}", res.status());
+        }
+
+        Ok(res.json().await?)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateKeyRequest {
+    key_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GenerateKeyResponse {
+    public_key: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignRequest {
+    key_id: Uuid,
+    public_key: String,
+    data: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignResponse {
+    signature: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, TcpListener};
+
+    use axum::routing::post;
+    use axum::{Json, Router};
+
+    use super::*;
+
+    const AUTH_TOKEN: &str = "secret";
+
+    fn client(url: String, auth_token: Option<&str>) -> Arc<RemoteSignerClient> {
+        Arc::new(
+            RemoteSignerClient::new(&RemoteSignerConfig {
+                url,
+                auth_token: auth_token.map(ToOwned::to_owned),
+                request_timeout_sec: 5,
+            })
+            .unwrap(),
+        )
+    }
+
+    fn spawn_stub() -> String {
+        StubSignerDaemon::new(Some(AUTH_TOKEN.to_owned()))
+            .spawn()
+            .unwrap()
+    }
+
+    /// Serves a daemon which responds to sign requests with the specified signature
+    fn spawn_faulty(signature: String) -> String {
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        let app = Router::new().route(
+            "/sign",
+            post(move || {
+                let signature = signature.clone();
+                async move { Json(SignResponse { signature }) }
+            }),
+        );
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+
+        url
+    }
+
+    #[tokio::test]
+    async fn remote_signer_signs() {
+        let client = client(spawn_stub(), Some(AUTH_TOKEN));
+
+        let key_id = Uuid::new_v4();
+        let public_key = client.generate_key(&key_id).await.unwrap();
+
+        let signer = RemoteSigner::new(client, key_id, public_key);
+        let data = b"message hash";
+        let signature = signer.sign(data).await.unwrap();
+
+        let signature = ed25519_dalek::Signature::from_bytes(&signature).unwrap();
+        assert!(public_key.verify_strict(data, &signature).is_ok());
+    }
+
+    #[tokio::test]
+    async fn remote_signer_rejects_unauthorized() {
+        let url = spawn_stub();
+
+        for auth_token in [None, Some("wrong")] {
+            let err = client(url.clone(), auth_token)
+                .generate_key(&Uuid::new_v4())
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("401"), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn remote_signer_fails_for_unknown_key() {
+        let client = client(spawn_stub(), Some(AUTH_TOKEN));
+
+        let key_id = Uuid::new_v4();
+        let public_key = client.generate_key(&key_id).await.unwrap();
+
+        // Key ids can't be reused
+        let err = client.generate_key(&key_id).await.unwrap_err();
+        assert!(err.to_string().contains("409"), "{err}");
+
+        let signer = RemoteSigner::new(client.clone(), Uuid::new_v4(), public_key);
+        let err = signer.sign(b"data").await.unwrap_err();
+        assert!(err.to_string().contains("404"), "{err}");
+
+        let other_public_key = client.generate_key(&Uuid::new_v4()).await.unwrap();
+        let signer = RemoteSigner::new(client, key_id, other_public_key);
+        let err = signer.sign(b"data").await.unwrap_err();
+        assert!(err.to_string().contains("400"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn remote_signer_verifies_signature() {
+        let public_key = LocalSigner::generate_key_pair().unwrap().public;
+
+        let signer = RemoteSigner::new(
+            client(spawn_faulty("00".repeat(64)), None),
+            Uuid::new_v4(),
+            public_key,
+        );
+        let err = signer.sign(b"data").await.unwrap_err();
+        assert_eq!(err.to_string(), "Invalid signature from remote signer");
+
+        let signer = RemoteSigner::new(
+            client(spawn_faulty("00".to_owned()), None),
+            Uuid::new_v4(),
+            public_key,
+        );
+        let err = signer.sign(b"data").await.unwrap_err();
+        assert_eq!(err.to_string(), "Invalid signature length");
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::extract::Extension;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use ed25519_dalek::{Keypair, Signer as _};
+use parking_lot::Mutex;
+use uuid::Uuid;
+
+use super::{GenerateKeyRequest, GenerateKeyResponse, LocalSigner, SignRequest, SignResponse};
+
+/// In-memory implementation of the signing daemon protocol.
+/// Keys are lost on restart, so it must only be used for local development and tests
+pub struct StubSignerDaemon {
+    keys: Mutex<HashMap<Uuid, Keypair>>,
+    auth_token: Option<String>,
+}
+
+impl StubSignerDaemon {
+    pub fn new(auth_token: Option<String>) -> Arc<Self> {
+        Arc::new(Self {
+            keys: Default::default(),
+            auth_token,
+        })
+    }
+
+    /// Serves the daemon on the listener until the server fails
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<()> {
+        let app = Router::new()
+            .route("/keys", post(generate_key))
+            .route("/sign", post(sign))
+            .layer(Extension(self));
+
+        axum::Server::from_tcp(listener)?
+            .serve(app.into_make_service())
+            .await
+            .context("Failed to start stub signer")
+    }
+
+    /// Binds the daemon to a random local port and returns its url
+    pub fn spawn(self: Arc<Self>) -> Result<String> {
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))?;
+        let url = format!("http://{}/", listener.local_addr()?);
+
+        tokio::spawn(async move {
+            if let Err(e) = self.serve(listener).await {
+                log::error!("Stub signer failed: {e:?}");
+            }
+        });
+
+        Ok(url)
+    }
+
+    fn authorize(&self, headers: &HeaderMap) -> Result<(), StatusCode> {
+        let auth_token = match &self.auth_token {
+            Some(auth_token) => auth_token,
+            None => return Ok(()),
+        };
+
+        let authorized = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|token| token == auth_token)
+            .unwrap_or_default();
+
+        if authorized {
+            Ok(())
+        } else {
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+}
+
+async fn generate_key(
+    Extension(daemon): Extension<Arc<StubSignerDaemon>>,
+    headers: HeaderMap,
+    Json(req): Json<GenerateKeyRequest>,
+) -> Result<Json<GenerateKeyResponse>, StatusCode> {
+    daemon.authorize(&headers)?;
+
+    let mut keys = daemon.keys.lock();
+    if keys.contains_key(&req.key_id) {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let key_pair =
+        LocalSigner::generate_key_pair().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let public_key = hex::encode(key_pair.public.as_bytes());
+    keys.insert(req.key_id, key_pair);
+
+    Ok(Json(GenerateKeyResponse { public_key }))
+}
+
+async fn sign(
+    Extension(daemon): Extension<Arc<StubSignerDaemon>>,
+    headers: HeaderMap,
+    Json(req): Json<SignRequest>,
+) -> Result<Json<SignResponse>, StatusCode> {
+    daemon.authorize(&headers)?;
+
+    let data = base64::decode(&req.data).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let keys = daemon.keys.lock();
+    let key_pair = keys.get(&req.key_id).ok_or(StatusCode::NOT_FOUND)?;
+    if hex::encode(key_pair.public.as_bytes()) != req.public_key {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(Json(SignResponse {
+        signature: hex::encode(key_pair.sign(&data).to_bytes()),
+    }))
+}
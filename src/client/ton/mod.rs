@@ -1,10 +1,11 @@
+use std::borrow::Cow;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use bigdecimal::{BigDecimal, ToPrimitive};
-use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use ed25519_dalek::PublicKey;
 use http::StatusCode;
-use nekoton::core::models::Expiration;
+use nekoton::core::models::{Expiration, MultisigPendingTransaction};
 use nekoton::core::ton_wallet::multisig::DeployParams;
 use nekoton::core::ton_wallet::{MultisigType, TransferAction};
 use nekoton::core::InternalMessage;
@@ -19,6 +20,7 @@ use ton_types::{deserialize_tree_of_cells, SliceData, UInt256};
 use uuid::Uuid;
 
 use crate::api::*;
+use crate::client::Signer;
 use crate::models::*;
 use crate::prelude::*;
 use crate::services::*;
@@ -55,52 +57,20 @@ impl TonClient {
             .collect::<Vec<MsgAddressInt>>();
 
         // Subscribe to ton accounts
-        let owner_accounts = owner_addresses
-            .iter()
-            .map(|item| UInt256::from_be_bytes(&item.address().get_bytestring(0)))
-            .collect::<Vec<UInt256>>();
-
-        self.ton_core.add_ton_account_subscription(owner_accounts);
+        self.ton_core.add_ton_account_subscription(owner_addresses);
 
         Ok(())
     }
 
-    pub async fn create_address(&self, payload: CreateAddress) -> Result<CreatedAddress, Error> {
-        let generated_key = nekoton::crypto::generate_key(nekoton::crypto::MnemonicType::Labs(0));
-
-        let Keypair { public, secret } = nekoton::crypto::derive_from_phrase(
-            &generated_key.words.join(" "),
-            generated_key.account_type,
-        )?;
-
+    pub async fn create_address(
+        &self,
+        payload: CreateAddress,
+        public: PublicKey,
+    ) -> Result<CreatedAddress, Error> {
         let workchain_id = payload.workchain_id.unwrap_or_default();
         let account_type = payload.account_type.unwrap_or_default();
 
-        let address = match account_type {
-            AccountType::HighloadWallet => {
-                nekoton::core::ton_wallet::highload_wallet_v2::compute_contract_address(
-                    &public,
-                    workchain_id as i8,
-                )
-            }
-            AccountType::Wallet => nekoton::core::ton_wallet::wallet_v3::compute_contract_address(
-                &public,
-                workchain_id as i8,
-            ),
-            AccountType::SafeMultisig => {
-                nekoton::core::ton_wallet::multisig::compute_contract_address(
-                    &public,
-                    MultisigType::SafeMultisigWallet,
-                    workchain_id as i8,
-                )
-            }
-            AccountType::EverWallet => {
-                nekoton::core::ton_wallet::ever_wallet::compute_contract_address(
-                    &public,
-                    workchain_id as i8,
-                )
-            }
-        };
+        let address = compute_address(account_type, &public, workchain_id);
 
         let (custodians, confirmations) = match account_type {
             AccountType::SafeMultisig => (
@@ -150,15 +120,101 @@ impl TonClient {
         };
 
         // Subscribe to accounts
-        let account = UInt256::from_be_bytes(&hex::decode(address.address().to_hex_string())?);
-        self.ton_core.add_ton_account_subscription([account]);
+        self.ton_core
+            .add_ton_account_subscription([address.clone()]);
 
         Ok(CreatedAddress {
             workchain_id: address.workchain_id(),
             hex: address.address().to_hex_string(),
             base64url: nekoton_utils::pack_std_smc_addr(true, &address, true)?,
             public_key: public.to_bytes().to_vec(),
-            private_key: secret.to_bytes().to_vec(),
+            account_type,
+            custodians,
+            confirmations,
+            custodians_public_keys,
+        })
+    }
+
+    pub async fn import_address(
+        &self,
+        payload: ImportAddress,
+        public: Option<PublicKey>,
+    ) -> Result<ImportedAddress, Error> {
+        let account_type = payload.account_type.unwrap_or_default();
+
+        let address = match (payload.address, public) {
+            (Some(address), public) => {
+                let address = nekoton_utils::repack_address(&address.0)?;
+                if let Some(public) = &public {
+                    if compute_address(account_type, public, address.workchain_id()) != address {
+                        return Err(TonServiceError::WrongInput(
+                            "Address doesn't match public key".to_string(),
+                        )
+                        .into());
+                    }
+                }
+                address
+            }
+            (None, Some(public)) => compute_address(
+                account_type,
+                &public,
+                payload.workchain_id.unwrap_or_default(),
+            ),
+            (None, None) => {
+                return Err(TonServiceError::WrongInput(
+                    "Either address or public key must be specified".to_string(),
+                )
+                .into())
+            }
+        };
+
+        let (custodians, confirmations, custodians_public_keys) = match account_type {
+            AccountType::SafeMultisig => {
+                let mut custodians_public_keys = Vec::new();
+                for key in payload.custodians_public_keys.unwrap_or_default() {
+                    let key = hex::decode(&key)
+                        .ok()
+                        .and_then(|key| PublicKey::from_bytes(&key).ok())
+                        .ok_or_else(|| {
+                            TonServiceError::WrongInput("Invalid custodian".to_string())
+                        })?;
+                    custodians_public_keys.push(hex::encode(key.to_bytes()));
+                }
+                if let Some(public) = &public {
+                    let public = hex::encode(public.to_bytes());
+                    if !custodians_public_keys.contains(&public) {
+                        custodians_public_keys.push(public);
+                    }
+                }
+
+                let custodians = payload
+                    .custodians
+                    .unwrap_or(custodians_public_keys.len().max(1) as i32);
+                let confirmations = payload.confirmations.unwrap_or(1);
+                if confirmations > custodians {
+                    return Err(TonServiceError::WrongInput(
+                        "Invalid number of confirmations".to_string(),
+                    )
+                    .into());
+                }
+
+                (
+                    Some(custodians),
+                    Some(confirmations),
+                    Some(custodians_public_keys),
+                )
+            }
+            AccountType::HighloadWallet | AccountType::Wallet | AccountType::EverWallet => {
+                (None, None, None)
+            }
+        };
+
+        // The account is subscribed to by the caller once the address is stored
+        Ok(ImportedAddress {
+            workchain_id: address.workchain_id(),
+            hex: address.address().to_hex_string(),
+            base64url: nekoton_utils::pack_std_smc_addr(true, &address, true)?,
+            public_key: public.map(|public| public.to_bytes().to_vec()),
             account_type,
             custodians,
             confirmations,
@@ -170,8 +226,7 @@ impl TonClient {
         &self,
         owner: &MsgAddressInt,
     ) -> Result<NetworkAddressData, Error> {
-        let account = UInt256::from_be_bytes(&owner.address().get_bytestring(0));
-        let contract = match self.ton_core.get_contract_state(&account) {
+        let contract = match self.ton_core.get_contract_state(owner) {
             Ok(contract) => contract,
             Err(_) => return Ok(NetworkAddressData::uninit(owner)),
         };
@@ -197,10 +252,9 @@ impl TonClient {
     pub async fn prepare_deploy(
         &self,
         address: &AddressDb,
-        public_key: &[u8],
-        private_key: &[u8],
+        signer: &dyn Signer,
     ) -> Result<Option<(SentTransaction, SignedMessage)>, Error> {
-        let public_key = PublicKey::from_bytes(public_key)?;
+        let public_key = *signer.public_key();
 
         let unsigned_message = match address.account_type {
             AccountType::SafeMultisig => {
@@ -237,17 +291,8 @@ impl TonClient {
             }
         };
 
-        let key_pair = Keypair {
-            secret: SecretKey::from_bytes(private_key)?,
-            public: public_key,
-        };
-
-        let data_to_sign = ton_abi::extend_signature_with_id(
-            unsigned_message.hash(),
-            self.ton_core.signature_id(),
-        );
-        let signature = key_pair.sign(&data_to_sign);
-        let signed_message = unsigned_message.sign(&signature.to_bytes())?;
+        let signed_message =
+            sign_message(&self.ton_core, unsigned_message.as_ref(), signer).await?;
 
         let sent_transaction = SentTransaction {
             id: Uuid::new_v4(),
@@ -266,17 +311,21 @@ impl TonClient {
     pub async fn prepare_transaction(
         &self,
         transaction: TransactionSend,
-        public_key: &[u8],
-        private_key: &[u8],
+        signer: &dyn Signer,
         account_type: &AccountType,
         custodians: &Option<i32>,
     ) -> Result<(SentTransaction, SignedMessage), Error> {
+        let max_outputs = max_outputs_per_message(account_type);
+        if transaction.outputs.len() > max_outputs {
+            return Err(TonClientError::TooManyOutputs(max_outputs).into());
+        }
+
         let original_value = transaction.outputs.iter().map(|o| o.value.clone()).sum();
         let original_outputs = serde_json::to_value(transaction.outputs.clone())?;
 
         let bounce = transaction.bounce.unwrap_or_default();
 
-        let public_key = PublicKey::from_bytes(public_key)?;
+        let public_key = *signer.public_key();
         let address = nekoton_utils::repack_address(&transaction.from_address.0)?;
 
         let expiration = Expiration::Timeout(DEFAULT_EXPIRATION_TIMEOUT);
@@ -294,8 +343,7 @@ impl TonClient {
 
         let transfer_action = match account_type {
             AccountType::HighloadWallet => {
-                let account = UInt256::from_be_bytes(&address.address().get_bytestring(0));
-                let current_state = self.ton_core.get_contract_state(&account)?.account;
+                let current_state = self.ton_core.get_contract_state(&address)?.account;
 
                 let mut gifts: Vec<nekoton::core::ton_wallet::Gift> = vec![];
                 for item in transaction.outputs {
@@ -326,30 +374,31 @@ impl TonClient {
                 )?
             }
             AccountType::Wallet => {
-                let account = UInt256::from_be_bytes(&address.address().get_bytestring(0));
-                let current_state = self.ton_core.get_contract_state(&account)?.account;
+                let current_state = self.ton_core.get_contract_state(&address)?.account;
 
-                let recipient = transaction
-                    .outputs
-                    .first()
-                    .ok_or(TonClientError::RecipientNotFound)?;
+                if transaction.outputs.is_empty() {
+                    return Err(TonClientError::RecipientNotFound.into());
+                }
 
-                let destination = nekoton_utils::repack_address(&recipient.recipient_address.0)?;
-                let amount = recipient
-                    .value
-                    .to_u64()
-                    .ok_or(TonClientError::ParseBigDecimal)?;
-                let flags = recipient.output_type.clone().unwrap_or_default();
-                let body = payload_cell.map(SliceData::load_cell).transpose()?;
+                let mut gifts: Vec<nekoton::core::ton_wallet::Gift> = vec![];
+                for item in transaction.outputs {
+                    let flags = item.output_type.unwrap_or_default();
+                    let destination = nekoton_utils::repack_address(&item.recipient_address.0)?;
+                    let amount = item.value.to_u64().ok_or(TonClientError::ParseBigDecimal)?;
+                    let body = payload_cell
+                        .as_ref()
+                        .map(|c| SliceData::load_cell(c.clone()))
+                        .transpose()?;
 
-                let gifts = vec![nekoton::core::ton_wallet::Gift {
-                    flags: flags.into(),
-                    bounce,
-                    destination,
-                    amount,
-                    body,
-                    state_init: None,
-                }];
+                    gifts.push(nekoton::core::ton_wallet::Gift {
+                        flags: flags.into(),
+                        bounce,
+                        destination,
+                        amount,
+                        body,
+                        state_init: None,
+                    });
+                }
 
                 let seqno_offset = nekoton::core::ton_wallet::wallet_v3::estimate_seqno_offset(
                     &SimpleClock,
@@ -406,8 +455,7 @@ impl TonClient {
                 )?
             }
             AccountType::EverWallet => {
-                let account = UInt256::from_be_bytes(&address.address().get_bytestring(0));
-                let current_state = self.ton_core.get_contract_state(&account)?.account;
+                let current_state = self.ton_core.get_contract_state(&address)?.account;
 
                 let mut gifts: Vec<nekoton::core::ton_wallet::Gift> = vec![];
                 for item in transaction.outputs {
@@ -447,17 +495,8 @@ impl TonClient {
             }
         };
 
-        let key_pair = Keypair {
-            secret: SecretKey::from_bytes(private_key)?,
-            public: public_key,
-        };
-
-        let data_to_sign = ton_abi::extend_signature_with_id(
-            unsigned_message.hash(),
-            self.ton_core.signature_id(),
-        );
-        let signature = key_pair.sign(&data_to_sign);
-        let signed_message = unsigned_message.sign(&signature.to_bytes())?;
+        let signed_message =
+            sign_message(&self.ton_core, unsigned_message.as_ref(), signer).await?;
 
         let sent_transaction = SentTransaction {
             id: transaction.id,
@@ -476,10 +515,9 @@ impl TonClient {
     pub async fn prepare_confirm_transaction(
         &self,
         transaction: TransactionConfirm,
-        public_key: &[u8],
-        private_key: &[u8],
+        signer: &dyn Signer,
     ) -> Result<(SentTransaction, SignedMessage), Error> {
-        let public_key = PublicKey::from_bytes(public_key)?;
+        let public_key = *signer.public_key();
         let address = nekoton_utils::repack_address(&transaction.address.0)?;
 
         let account_workchain_id = address.workchain_id();
@@ -494,17 +532,8 @@ impl TonClient {
             Expiration::Timeout(DEFAULT_EXPIRATION_TIMEOUT),
         )?;
 
-        let key_pair = Keypair {
-            secret: SecretKey::from_bytes(private_key)?,
-            public: public_key,
-        };
-
-        let data_to_sign = ton_abi::extend_signature_with_id(
-            unsigned_message.hash(),
-            self.ton_core.signature_id(),
-        );
-        let signature = key_pair.sign(&data_to_sign);
-        let signed_message = unsigned_message.sign(&signature.to_bytes())?;
+        let signed_message =
+            sign_message(&self.ton_core, unsigned_message.as_ref(), signer).await?;
 
         let sent_transaction = SentTransaction {
             id: transaction.id,
@@ -520,17 +549,45 @@ impl TonClient {
         Ok((sent_transaction, signed_message))
     }
 
+    /// Transactions of the SafeMultisig wallet waiting for confirmations
+    pub async fn get_multisig_pending_transactions(
+        &self,
+        address: &MsgAddressInt,
+    ) -> Result<Vec<MultisigPendingTransaction>, Error> {
+        let contract = self.ton_core.get_contract_state(address)?;
+
+        if !matches!(
+            contract.account.storage.state,
+            ton_block::AccountState::AccountActive { .. }
+        ) {
+            return Ok(Vec::new());
+        }
+
+        let custodians = nekoton::core::ton_wallet::multisig::get_custodians(
+            MultisigType::SafeMultisigWallet,
+            &SimpleClock,
+            Cow::Borrowed(&contract.account),
+        )?;
+
+        let transactions = nekoton::core::ton_wallet::multisig::get_pending_transactions(
+            MultisigType::SafeMultisigWallet,
+            &SimpleClock,
+            Cow::Borrowed(&contract.account),
+            &custodians,
+        )?;
+
+        Ok(transactions)
+    }
+
     pub async fn get_token_address_info(
         &self,
         owner: &MsgAddressInt,
         root_address: &MsgAddressInt,
     ) -> Result<NetworkTokenAddressData, Error> {
-        let root_account = UInt256::from_be_bytes(&root_address.address().get_bytestring(0));
-        let root_contract = self.ton_core.get_contract_state(&root_account)?;
+        let root_contract = self.ton_core.get_contract_state(root_address)?;
 
         let token_address = get_token_wallet_address(&root_contract, owner)?;
-        let token_account = UInt256::from_be_bytes(&token_address.address().get_bytestring(0));
-        let token_contract = match self.ton_core.get_contract_state(&token_account) {
+        let token_contract = match self.ton_core.get_contract_state(&token_address) {
             Ok(contract) => contract,
             Err(_) => {
                 return Ok(NetworkTokenAddressData::uninit(
@@ -559,11 +616,52 @@ impl TonClient {
         })
     }
 
+    pub fn get_root_token_metadata(
+        &self,
+        root_address: &MsgAddressInt,
+    ) -> Result<TokenMetadata, Error> {
+        let root_contract = self.ton_core.get_contract_state(root_address)?;
+
+        let details = get_root_token_details(&root_contract)?;
+
+        Ok(TokenMetadata {
+            symbol: details.symbol,
+            decimals: details.decimals as i32,
+            total_supply: BigDecimal::new(details.total_supply.into(), 0),
+        })
+    }
+
+    /// Checks that the account is a deployed root token contract of the version
+    pub fn check_root_token(
+        &self,
+        root_address: &MsgAddressInt,
+        version: TokenWalletVersionDb,
+    ) -> Result<TokenMetadata, Error> {
+        let details = self
+            .ton_core
+            .get_contract_state(root_address)
+            .and_then(|root_contract| get_root_token_details(&root_contract))
+            .map_err(|_| TonClientError::InvalidRootToken(root_address.to_string()))?;
+
+        if TokenWalletVersionDb::from(details.version) != version {
+            return Err(TonClientError::RootTokenVersionMismatch(format!(
+                "{:?}",
+                TokenWalletVersionDb::from(details.version)
+            ))
+            .into());
+        }
+
+        Ok(TokenMetadata {
+            symbol: details.symbol,
+            decimals: details.decimals as i32,
+            total_supply: BigDecimal::new(details.total_supply.into(), 0),
+        })
+    }
+
     pub async fn prepare_token_transaction(
         &self,
         input: &TokenTransactionSend,
-        public_key: &[u8],
-        private_key: &[u8],
+        signer: &dyn Signer,
         account_type: &AccountType,
         custodians: &Option<i32>,
     ) -> Result<(SentTransaction, SignedMessage), Error> {
@@ -621,12 +719,12 @@ impl TonClient {
             &self.ton_core,
             input.id,
             owner,
-            public_key,
-            private_key,
+            signer,
             account_type,
             custodians,
             internal_message,
-        )?;
+        )
+        .await?;
 
         Ok(res)
     }
@@ -634,8 +732,7 @@ impl TonClient {
     pub async fn prepare_token_burn(
         &self,
         input: &TokenTransactionBurn,
-        public_key: &[u8],
-        private_key: &[u8],
+        signer: &dyn Signer,
         account_type: &AccountType,
         custodians: &Option<i32>,
     ) -> Result<(SentTransaction, SignedMessage), Error> {
@@ -680,12 +777,12 @@ impl TonClient {
             &self.ton_core,
             input.id,
             owner,
-            public_key,
-            private_key,
+            signer,
             account_type,
             custodians,
             internal_message,
-        )?;
+        )
+        .await?;
 
         Ok(res)
     }
@@ -693,8 +790,7 @@ impl TonClient {
     pub async fn prepare_token_mint(
         &self,
         input: &TokenTransactionMint,
-        public_key: &[u8],
-        private_key: &[u8],
+        signer: &dyn Signer,
         account_type: &AccountType,
         custodians: &Option<i32>,
     ) -> Result<(SentTransaction, SignedMessage), Error> {
@@ -702,8 +798,7 @@ impl TonClient {
         let root_token = nekoton_utils::repack_address(&input.root_address.0)?;
         let recipient = nekoton_utils::repack_address(&input.recipient_address.0)?;
 
-        let root_account = UInt256::from_be_bytes(&root_token.address().get_bytestring(0));
-        let root_contract = self.ton_core.get_contract_state(&root_account)?;
+        let root_contract = self.ton_core.get_contract_state(&root_token)?;
 
         let version = get_root_token_version(&root_contract)?;
 
@@ -742,19 +837,19 @@ impl TonClient {
             &self.ton_core,
             input.id,
             owner,
-            public_key,
-            private_key,
+            signer,
             account_type,
             custodians,
             internal_message,
-        )?;
+        )
+        .await?;
 
         Ok(res)
     }
 
     pub async fn send_transaction(
         &self,
-        account: UInt256,
+        account: MsgAddressInt,
         signed_message: SignedMessage,
     ) -> Result<MessageStatus, Error> {
         let status = self
@@ -765,9 +860,26 @@ impl TonClient {
         Ok(status)
     }
 
+    /// Waits for the message restored into the pending messages queue,
+    /// broadcasting it again if the `message` is specified
+    pub async fn wait_pending_message(
+        &self,
+        rx: oneshot::Receiver<MessageStatus>,
+        message_hash: UInt256,
+        message: Option<&ton_block::Message>,
+        expire_at: u32,
+    ) -> Result<MessageStatus, Error> {
+        let status = self
+            .ton_core
+            .wait_pending_message(rx, message_hash, message, expire_at)
+            .await?;
+
+        Ok(status)
+    }
+
     pub fn add_pending_message(
         &self,
-        account: UInt256,
+        account: MsgAddressInt,
         message_hash: UInt256,
         expire_at: u32,
     ) -> Result<oneshot::Receiver<MessageStatus>, Error> {
@@ -778,6 +890,14 @@ impl TonClient {
         Ok(status)
     }
 
+    pub fn current_utime(&self) -> u32 {
+        self.ton_core.current_utime()
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.ton_core.context.ton_subscriber.metrics().ready
+    }
+
     pub async fn get_metrics(&self) -> Result<Metrics, Error> {
         let gen_utime = self.ton_core.current_utime();
         Ok(Metrics { gen_utime })
@@ -810,14 +930,14 @@ impl TonClient {
 
     pub async fn run_local(
         &self,
-        contract_address: UInt256,
+        contract_address: &MsgAddressInt,
         function: ton_abi::Function,
         input: &[ton_abi::Token],
         responsible: bool,
     ) -> anyhow::Result<Option<nekoton_abi::ExecutionOutput>> {
         use nekoton_abi::FunctionExt;
 
-        let state = match self.ton_core.get_contract_state(&contract_address) {
+        let state = match self.ton_core.get_contract_state(contract_address) {
             Ok(a) => a,
             Err(e) => {
                 log::error!("Failed to get contract state: {e:?}");
@@ -837,8 +957,7 @@ impl TonClient {
     pub async fn prepare_signed_generic_message(
         &self,
         sender_addr: &str,
-        public_key: &[u8],
-        private_key: &[u8],
+        signer: &dyn Signer,
         target_addr: &str,
         execution_flag: u8,
         value: BigDecimal,
@@ -851,7 +970,7 @@ impl TonClient {
         let unsigned_message = self
             .prepare_generic_message(
                 sender_addr,
-                public_key,
+                signer.public_key().as_bytes(),
                 target_addr,
                 execution_flag,
                 value,
@@ -863,19 +982,8 @@ impl TonClient {
             )
             .await?;
 
-        let public_key = PublicKey::from_bytes(public_key).unwrap_or_default();
-
-        let key_pair = Keypair {
-            secret: SecretKey::from_bytes(private_key)?,
-            public: public_key,
-        };
-
-        let data_to_sign = ton_abi::extend_signature_with_id(
-            unsigned_message.hash(),
-            self.ton_core.signature_id(),
-        );
-        let signature = key_pair.sign(&data_to_sign);
-        let signed_message = unsigned_message.sign(&signature.to_bytes())?;
+        let signed_message =
+            sign_message(&self.ton_core, unsigned_message.as_ref(), signer).await?;
 
         Ok(signed_message)
     }
@@ -909,8 +1017,7 @@ impl TonClient {
         let amount = value.to_u64().ok_or(TonClientError::ParseBigDecimal)?;
         let transfer_action = match account_type {
             AccountType::Wallet => {
-                let account = UInt256::from_be_bytes(&address.address().get_bytestring(0));
-                let current_state = self.ton_core.get_contract_state(&account)?.account;
+                let current_state = self.ton_core.get_contract_state(&address)?.account;
 
                 let gifts = vec![nekoton::core::ton_wallet::Gift {
                     flags: execution_flag,
@@ -962,8 +1069,7 @@ impl TonClient {
                 )?
             }
             AccountType::HighloadWallet => {
-                let account = UInt256::from_be_bytes(&address.address().get_bytestring(0));
-                let current_state = self.ton_core.get_contract_state(&account)?.account;
+                let current_state = self.ton_core.get_contract_state(&address)?.account;
 
                 let gift = nekoton::core::ton_wallet::Gift {
                     flags: execution_flag,
@@ -983,8 +1089,7 @@ impl TonClient {
                 )?
             }
             AccountType::EverWallet => {
-                let account = UInt256::from_be_bytes(&address.address().get_bytestring(0));
-                let current_state = self.ton_core.get_contract_state(&account)?.account;
+                let current_state = self.ton_core.get_contract_state(&address)?.account;
 
                 let gift = nekoton::core::ton_wallet::Gift {
                     flags: execution_flag,
@@ -1015,8 +1120,42 @@ impl TonClient {
         Ok(unsigned_message)
     }
 
-    pub fn add_ton_account_subscription(&self, account: UInt256) {
-        self.ton_core.add_ton_account_subscription([account])
+    pub fn estimate_fees(&self, signed_message: &SignedMessage) -> Result<EstimatedFees, Error> {
+        self.ton_core
+            .estimate_fees(&signed_message.message)
+            .map_err(From::from)
+    }
+
+    pub fn add_ton_account_subscription(&self, address: MsgAddressInt) {
+        self.ton_core.add_ton_account_subscription([address])
+    }
+
+    pub fn update_root_token(&self, root_address: MsgAddressInt, enabled: bool) {
+        self.ton_core.update_root_token(root_address, enabled)
+    }
+
+    pub async fn load_ton_transactions_history(
+        &self,
+        address: &MsgAddressInt,
+        cursor: Option<TransactionsHistoryCursor>,
+        limit: usize,
+    ) -> Result<TransactionsHistoryBatch<CaughtTonTransaction>, Error> {
+        self.ton_core
+            .load_ton_transactions_history(address, cursor, limit)
+            .await
+            .map_err(From::from)
+    }
+
+    pub async fn load_token_transactions_history(
+        &self,
+        address: &MsgAddressInt,
+        cursor: Option<TransactionsHistoryCursor>,
+        limit: usize,
+    ) -> Result<TransactionsHistoryBatch<CreateTokenTransaction>, Error> {
+        self.ton_core
+            .load_token_transactions_history(address, cursor, limit)
+            .await
+            .map_err(From::from)
     }
 }
 
@@ -1032,6 +1171,12 @@ pub enum TonClientError {
     ParseBigDecimal,
     #[error("Parse BigUint error")]
     ParseBigUint,
+    #[error("Too many outputs, the wallet can send at most {0} in a single message")]
+    TooManyOutputs(usize),
+    #[error("Account `{0}` is not a root token contract")]
+    InvalidRootToken(String),
+    #[error("Root token contract version is `{0}`")]
+    RootTokenVersionMismatch(String),
 }
 
 impl TonClientError {
@@ -1039,6 +1184,9 @@ impl TonClientError {
         match self {
             TonClientError::ParseBigUint
             | TonClientError::RecipientNotFound
+            | TonClientError::TooManyOutputs(_)
+            | TonClientError::InvalidRootToken(_)
+            | TonClientError::RootTokenVersionMismatch(_)
             | TonClientError::AccountNotDeployed(_) => StatusCode::BAD_REQUEST,
             TonClientError::CustodiansNotFound | TonClientError::ParseBigDecimal => {
                 StatusCode::INTERNAL_SERVER_ERROR
@@ -1047,12 +1195,11 @@ impl TonClientError {
     }
 }
 
-fn build_token_transaction(
+async fn build_token_transaction(
     ton_core: &Arc<TonCore>,
     id: Uuid,
     owner: MsgAddressInt,
-    public_key: &[u8],
-    private_key: &[u8],
+    signer: &dyn Signer,
     account_type: &AccountType,
     custodians: &Option<i32>,
     internal_message: InternalMessage,
@@ -1066,12 +1213,11 @@ fn build_token_transaction(
 
     let expiration = Expiration::Timeout(DEFAULT_EXPIRATION_TIMEOUT);
 
-    let public_key = PublicKey::from_bytes(public_key).unwrap_or_default();
+    let public_key = *signer.public_key();
 
     let transfer_action = match account_type {
         AccountType::HighloadWallet => {
-            let account = UInt256::from_be_bytes(&owner.address().get_bytestring(0));
-            let current_state = ton_core.get_contract_state(&account)?.account;
+            let current_state = ton_core.get_contract_state(&owner)?.account;
 
             let gift = nekoton::core::ton_wallet::Gift {
                 flags: flags.into(),
@@ -1091,8 +1237,7 @@ fn build_token_transaction(
             )?
         }
         AccountType::Wallet => {
-            let account = UInt256::from_be_bytes(&owner.address().get_bytestring(0));
-            let current_state = ton_core.get_contract_state(&account)?.account;
+            let current_state = ton_core.get_contract_state(&owner)?.account;
 
             let gifts = vec![nekoton::core::ton_wallet::Gift {
                 flags: flags.into(),
@@ -1144,8 +1289,7 @@ fn build_token_transaction(
             )?
         }
         AccountType::EverWallet => {
-            let account = UInt256::from_be_bytes(&owner.address().get_bytestring(0));
-            let current_state = ton_core.get_contract_state(&account)?.account;
+            let current_state = ton_core.get_contract_state(&owner)?.account;
 
             let gift = nekoton::core::ton_wallet::Gift {
                 flags: flags.into(),
@@ -1174,15 +1318,7 @@ fn build_token_transaction(
         }
     };
 
-    let key_pair = Keypair {
-        secret: SecretKey::from_bytes(private_key)?,
-        public: public_key,
-    };
-
-    let data_to_sign =
-        ton_abi::extend_signature_with_id(unsigned_message.hash(), ton_core.signature_id());
-    let signature = key_pair.sign(&data_to_sign);
-    let signed_message = unsigned_message.sign(&signature.to_bytes())?;
+    let signed_message = sign_message(ton_core, unsigned_message.as_ref(), signer).await?;
 
     let sent_transaction = SentTransaction {
         id,
@@ -1201,3 +1337,54 @@ fn build_token_transaction(
 const EVER_CHAIN_ID: i32 = 42;
 const VENOM_CHAIN_ID: i32 = 1;
 const TON_CHAIN_ID: i32 = -239;
+
+async fn sign_message(
+    ton_core: &TonCore,
+    unsigned_message: &dyn UnsignedMessage,
+    signer: &dyn Signer,
+) -> anyhow::Result<SignedMessage> {
+    let data_to_sign =
+        ton_abi::extend_signature_with_id(unsigned_message.hash(), ton_core.signature_id());
+    let signature = signer.sign(&data_to_sign).await?;
+
+    unsigned_message.sign(&signature)
+}
+
+/// Max number of outputs the wallet contract can send in a single external message
+pub fn max_outputs_per_message(account_type: &AccountType) -> usize {
+    match account_type {
+        AccountType::HighloadWallet => HIGHLOAD_WALLET_MAX_OUTPUTS,
+        AccountType::Wallet | AccountType::EverWallet => WALLET_MAX_OUTPUTS,
+        AccountType::SafeMultisig => 1,
+    }
+}
+
+fn compute_address(
+    account_type: AccountType,
+    public: &PublicKey,
+    workchain_id: i32,
+) -> MsgAddressInt {
+    match account_type {
+        AccountType::HighloadWallet => {
+            nekoton::core::ton_wallet::highload_wallet_v2::compute_contract_address(
+                public,
+                workchain_id as i8,
+            )
+        }
+        AccountType::Wallet => nekoton::core::ton_wallet::wallet_v3::compute_contract_address(
+            public,
+            workchain_id as i8,
+        ),
+        AccountType::SafeMultisig => nekoton::core::ton_wallet::multisig::compute_contract_address(
+            public,
+            MultisigType::SafeMultisigWallet,
+            workchain_id as i8,
+        ),
+        AccountType::EverWallet => {
+            nekoton::core::ton_wallet::ever_wallet::compute_contract_address(
+                public,
+                workchain_id as i8,
+            )
+        }
+    }
+}
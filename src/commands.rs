@@ -1,11 +1,15 @@
+use std::net::{SocketAddr, TcpListener};
 use std::str::FromStr;
 
 use anyhow::{Context, Result};
 use argon2::password_hash::{rand_core::OsRng, SaltString};
 use sqlx::postgres::PgPoolOptions;
 
+use crate::client::StubSignerDaemon;
 use crate::models::*;
+use crate::settings::*;
 use crate::sqlx_client::*;
+use crate::utils::Keyring;
 
 const DB_POOL_SIZE: u32 = 1;
 
@@ -29,6 +33,11 @@ pub async fn add_root_token(
             name: token_name,
             address: token_address,
             version: TokenWalletVersionDb::from_str(&token_version)?,
+            symbol: None,
+            decimals: None,
+            total_supply: None,
+            metadata_updated_at: None,
+            enabled: true,
         })
         .await?;
 
@@ -62,7 +71,7 @@ pub async fn create_api_service(
     println!("Api service {:?} created successfully!", api_service);
 
     let api_service_key = sqlx_client
-        .create_api_service_key(id, &service_key, &service_secret)
+        .create_api_service_key(id, &service_key, &service_secret, None, None, None)
         .await?;
     println!(
         "Api service key {:?} created successfully!",
@@ -72,9 +81,151 @@ pub async fn create_api_service(
     Ok(())
 }
 
+/// Runs the in-memory signing daemon for local development
+pub async fn run_stub_signer(addr: SocketAddr, auth_token: Option<String>) -> Result<()> {
+    let listener = TcpListener::bind(addr).context("Failed to bind stub signer")?;
+    println!("Stub signer is listening on {}", listener.local_addr()?);
+
+    StubSignerDaemon::new(auth_token).serve(listener).await
+}
+
 pub async fn generate_salt() -> Result<()> {
     let salt = SaltString::generate(&mut OsRng);
     println!("Salt: {}", salt);
 
     Ok(())
 }
+
+/// Re-encrypts private keys of all addresses with the specified (or active) key.
+/// Addresses already encrypted with it are skipped, so it can be safely restarted.
+pub async fn reencrypt_private_keys(
+    config: AppConfig,
+    key_id: Option<String>,
+    batch_size: i64,
+) -> Result<()> {
+    let keyring = config.keyring()?;
+    let keyring = match key_id {
+        Some(key_id) => keyring.with_active_key(&key_id)?,
+        None => keyring,
+    };
+
+    let key_id = keyring
+        .active_key_id()
+        .context("Private keys can only be re-encrypted with a versioned key")?
+        .to_owned();
+
+    let pool = PgPoolOptions::new()
+        .max_connections(DB_POOL_SIZE)
+        .connect(&config.database_url)
+        .await
+        .expect("fail pg pool");
+
+    let sqlx_client = SqlxClient::new(pool);
+
+    let mut after = None;
+    let mut reencrypted = 0;
+    let mut skipped = 0;
+
+    loop {
+        let addresses = sqlx_client.get_addresses_batch(after, batch_size).await?;
+
+        for address in &addresses {
+            let old_private_key = match &address.private_key {
+                Some(private_key) => private_key,
+                None => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+
+            let new_private_key = reencrypt_private_key(&keyring, old_private_key, &address.id)
+                .with_context(|| format!("Failed to re-encrypt private key of `{}`", address.id))?;
+            let new_private_key = match new_private_key {
+                Some(new_private_key) => new_private_key,
+                None => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+
+            if sqlx_client
+                .update_address_private_key(address.id, old_private_key, &new_private_key)
+                .await?
+            {
+                reencrypted += 1;
+            } else {
+                skipped += 1;
+            }
+        }
+
+        match addresses.last() {
+            Some(address) => after = Some(address.id),
+            None => break,
+        }
+
+        println!(
+            "Re-encrypted {} private keys with key `{}`, skipped {}, last address id {}",
+            reencrypted,
+            key_id,
+            skipped,
+            after.unwrap_or_default()
+        );
+
+        if (addresses.len() as i64) < batch_size {
+            break;
+        }
+    }
+
+    println!("Private keys have been re-encrypted with key `{}`!", key_id);
+
+    Ok(())
+}
+
+/// Private key encrypted with the active key of the keyring,
+/// `None` if it's already encrypted with it
+fn reencrypt_private_key(
+    keyring: &Keyring,
+    private_key: &str,
+    id: &uuid::Uuid,
+) -> Result<Option<String>> {
+    if keyring.is_active(private_key) {
+        return Ok(None);
+    }
+
+    let decrypted = keyring.decrypt(private_key, id)?;
+    keyring.encrypt(&decrypted, id).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_keys_are_reencrypted_with_active_key() {
+        let keyring = Keyring::new(
+            Some(vec![1; 32]),
+            vec![
+                ("v1".to_owned(), vec![2; 32]),
+                ("v2".to_owned(), vec![3; 32]),
+            ],
+            Some("v1".to_owned()),
+        )
+        .unwrap();
+        let id = uuid::Uuid::new_v4();
+
+        let legacy = crate::utils::encrypt_private_key(b"private key", [1; 32], &id).unwrap();
+        let v1 = reencrypt_private_key(&keyring, &legacy, &id)
+            .unwrap()
+            .unwrap();
+        assert!(v1.starts_with("v1:"));
+        assert_eq!(reencrypt_private_key(&keyring, &v1, &id).unwrap(), None);
+
+        let keyring = keyring.with_active_key("v2").unwrap();
+        let v2 = reencrypt_private_key(&keyring, &v1, &id).unwrap().unwrap();
+        assert!(v2.starts_with("v2:"));
+        assert_eq!(keyring.decrypt(&v2, &id).unwrap(), b"private key");
+
+        // Keys encrypted with an unknown key are not rewritten
+        assert!(reencrypt_private_key(&keyring, "v3:AAAA", &id).is_err());
+    }
+}
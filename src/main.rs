@@ -25,6 +25,11 @@ async fn run(app: App) -> Result<()> {
         Subcommand::RootToken(run) => run.execute().await,
         Subcommand::ApiService(run) => run.execute().await,
         Subcommand::Salt(run) => run.execute().await,
+        Subcommand::StubSigner(run) => run.execute().await,
+        Subcommand::ReencryptKeys(run) => {
+            let config: AppConfig = broxus_util::read_config(&run.config)?;
+            run.execute(config).await
+        }
     }
 }
 
@@ -42,6 +47,8 @@ enum Subcommand {
     RootToken(CmdRootToken),
     ApiService(CmdApiService),
     Salt(CmdSalt),
+    StubSigner(CmdStubSigner),
+    ReencryptKeys(CmdReencryptKeys),
 }
 
 #[derive(Debug, FromArgs)]
@@ -133,6 +140,46 @@ impl CmdSalt {
     }
 }
 
+#[derive(Debug, FromArgs)]
+/// Run an in-memory signing daemon for local development
+#[argh(subcommand, name = "stub_signer")]
+struct CmdStubSigner {
+    /// listen address ('127.0.0.1:8090' by default)
+    #[argh(option, short = 'a', default = "String::from(\"127.0.0.1:8090\")")]
+    addr: String,
+    /// bearer token expected from the clients
+    #[argh(option, short = 't')]
+    auth_token: Option<String>,
+}
+
+impl CmdStubSigner {
+    async fn execute(self) -> Result<()> {
+        let addr = self.addr.parse().context("Invalid listen address")?;
+        run_stub_signer(addr, self.auth_token).await
+    }
+}
+
+#[derive(Debug, FromArgs)]
+/// Re-encrypt private keys of all addresses with a new key
+#[argh(subcommand, name = "reencrypt_keys")]
+struct CmdReencryptKeys {
+    /// path to config file ('config.yaml' by default)
+    #[argh(option, short = 'c', default = "String::from(\"config.yaml\")")]
+    config: String,
+    /// key id (active key by default)
+    #[argh(option, short = 'k')]
+    key: Option<String>,
+    /// addresses processed at once (100 by default)
+    #[argh(option, short = 'b', default = "100")]
+    batch_size: i64,
+}
+
+impl CmdReencryptKeys {
+    async fn execute(self, config: AppConfig) -> Result<()> {
+        reencrypt_private_keys(config, self.key, self.batch_size).await
+    }
+}
+
 struct TonWalletApi {
     engine: tokio::sync::Mutex<Option<Arc<Engine>>>,
 }
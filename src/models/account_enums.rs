@@ -21,6 +21,17 @@ pub enum AccountType {
     EverWallet,
 }
 
+#[derive(
+    Debug, Default, Deserialize, Serialize, Clone, opg::OpgModel, Eq, PartialEq, sqlx::Type, Copy,
+)]
+#[opg("SignerType")]
+#[sqlx(type_name = "twa_signer_type", rename_all = "PascalCase")]
+pub enum SignerType {
+    #[default]
+    Local,
+    Remote,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, sqlx::Type, Eq, PartialEq)]
 #[opg("AccountStatus")]
 #[sqlx(type_name = "twa_account_status", rename_all = "PascalCase")]
@@ -104,6 +115,20 @@ pub enum TonTransactionStatus {
     Done,
     PartiallyDone,
     Error,
+    PendingApproval,
+    Rejected,
+    PendingConfirmation,
+}
+
+impl TonTransactionStatus {
+    /// States of proposed transfers: waiting for or refused an approval,
+    /// or multisig transactions waiting for confirmations. They have no transaction yet
+    pub fn is_proposal(&self) -> bool {
+        matches!(
+            self,
+            Self::PendingApproval | Self::Rejected | Self::PendingConfirmation
+        )
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, PartialEq, Eq, sqlx::Type)]
@@ -134,6 +159,79 @@ pub enum TonEventStatus {
     Error,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, PartialEq, Eq, sqlx::Type, Copy)]
+#[opg("NotifyType")]
+#[sqlx(type_name = "twa_notify_type", rename_all = "PascalCase")]
+pub enum NotifyType {
+    Transaction,
+    TokenTransaction,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, PartialEq, Eq, sqlx::Type, Copy)]
+#[opg("WebhookDeliveryStatus")]
+#[sqlx(type_name = "twa_webhook_delivery_status", rename_all = "PascalCase")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, PartialEq, Eq, sqlx::Type, Copy)]
+#[opg("TaskStatus")]
+#[sqlx(type_name = "twa_task_status", rename_all = "PascalCase")]
+pub enum TaskStatus {
+    Pending,
+    InProgress,
+    Done,
+    Error,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, PartialEq, Eq, sqlx::Type, Copy)]
+#[opg("MultisigProposalStatus")]
+#[sqlx(type_name = "twa_multisig_proposal_status", rename_all = "PascalCase")]
+pub enum MultisigProposalStatus {
+    Pending,
+    Rejected,
+    Executed,
+    Expired,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, PartialEq, Eq, sqlx::Type, Copy)]
+#[opg("DiscrepancyStatus")]
+#[sqlx(type_name = "twa_discrepancy_status", rename_all = "PascalCase")]
+pub enum DiscrepancyStatus {
+    Open,
+    Resolved,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, PartialEq, Eq, sqlx::Type, Copy)]
+#[opg("PayoutBatchStatus")]
+#[sqlx(type_name = "twa_payout_batch_status", rename_all = "PascalCase")]
+pub enum PayoutBatchStatus {
+    InProgress,
+    Done,
+    PartiallyFailed,
+    Failed,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, PartialEq, Eq, sqlx::Type, Copy)]
+#[opg("PayoutOutputStatus")]
+#[sqlx(type_name = "twa_payout_output_status", rename_all = "PascalCase")]
+pub enum PayoutOutputStatus {
+    Pending,
+    Sent,
+    Done,
+    Error,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, PartialEq, Eq, sqlx::Type, Copy)]
+#[opg("TaskKind")]
+#[sqlx(type_name = "twa_task_kind", rename_all = "PascalCase")]
+pub enum TaskKind {
+    Rescan,
+    DelayedTransfer,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, opg::OpgModel, PartialEq, Eq, sqlx::Type)]
 #[opg("TonTransactionDirection")]
 #[sqlx(type_name = "twa_transaction_direction", rename_all = "PascalCase")]
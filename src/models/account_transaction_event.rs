@@ -22,12 +22,40 @@ pub struct AccountTransactionEvent {
     #[opg("balanceChange", string, optional)]
     pub balance_change: Option<BigDecimal>,
     pub root_address: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    #[opg("balance change in tokens", string, optional)]
+    pub human_balance_change: Option<BigDecimal>,
     pub transaction_direction: TonTransactionDirection,
     pub transaction_status: TonTransactionStatus,
     pub event_status: TonEventStatus,
     pub multisig_transaction_id: Option<i64>,
     pub created_at: i64,
     pub updated_at: i64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+impl AccountTransactionEvent {
+    pub fn with_deliveries(mut self, deliveries: Vec<WebhookDeliveryDb>) -> Self {
+        self.deliveries = deliveries.into_iter().map(From::from).collect();
+        self
+    }
+
+    pub fn with_metadata(self, metadata: Option<TokenMetadata>) -> Self {
+        match metadata {
+            Some(metadata) => Self {
+                human_balance_change: self
+                    .balance_change
+                    .as_ref()
+                    .map(|value| metadata.human_amount(value)),
+                symbol: Some(metadata.symbol),
+                decimals: Some(metadata.decimals),
+                ..self
+            },
+            None => self,
+        }
+    }
 }
 
 impl From<TokenTransactionEventDb> for AccountTransactionEvent {
@@ -67,12 +95,16 @@ impl From<TokenTransactionEventDb> for AccountTransactionEvent {
             sender,
             balance_change: Some(t.value),
             root_address: Some(t.root_address),
+            symbol: None,
+            decimals: None,
+            human_balance_change: None,
             transaction_direction: t.transaction_direction,
             transaction_status: t.transaction_status.into(),
             event_status: t.event_status,
             multisig_transaction_id: None,
             created_at: t.created_at.timestamp_millis(),
             updated_at: t.updated_at.timestamp_millis(),
+            deliveries: Vec::new(),
         }
     }
 }
@@ -114,12 +146,16 @@ impl From<TransactionEventDb> for AccountTransactionEvent {
             sender,
             balance_change: t.balance_change,
             root_address: None,
+            symbol: None,
+            decimals: None,
+            human_balance_change: None,
             transaction_direction: t.transaction_direction,
             transaction_status: t.transaction_status,
             event_status: t.event_status,
             multisig_transaction_id: t.multisig_transaction_id,
             created_at: t.created_at.timestamp_millis(),
             updated_at: t.updated_at.timestamp_millis(),
+            deliveries: Vec::new(),
         }
     }
 }
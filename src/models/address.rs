@@ -29,6 +29,19 @@ pub struct CreateAddress {
     pub custodians: Option<i32>,
     pub confirmations: Option<i32>,
     pub custodians_public_keys: Option<Vec<String>>,
+    pub signer_type: Option<SignerType>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct ImportAddress {
+    pub account_type: Option<AccountType>,
+    pub workchain_id: Option<i32>,
+    pub address: Option<Address>,
+    pub public_key: Option<String>,
+    pub private_key: Option<String>,
+    pub custodians: Option<i32>,
+    pub confirmations: Option<i32>,
+    pub custodians_public_keys: Option<Vec<String>>,
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
@@ -37,7 +50,18 @@ pub struct CreatedAddress {
     pub hex: String,
     pub base64url: String,
     pub public_key: Vec<u8>,
-    pub private_key: Vec<u8>,
+    pub account_type: AccountType,
+    pub custodians: Option<i32>,
+    pub confirmations: Option<i32>,
+    pub custodians_public_keys: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct ImportedAddress {
+    pub workchain_id: i32,
+    pub hex: String,
+    pub base64url: String,
+    pub public_key: Option<Vec<u8>>,
     pub account_type: AccountType,
     pub custodians: Option<i32>,
     pub confirmations: Option<i32>,
@@ -51,8 +75,10 @@ pub struct CreateAddressInDb {
     pub workchain_id: i32,
     pub hex: String,
     pub base64url: String,
-    pub public_key: String,
-    pub private_key: String,
+    pub public_key: Option<String>,
+    pub private_key: Option<String>,
+    pub signer_type: SignerType,
+    pub watch_only: bool,
     pub account_type: AccountType,
     pub custodians: Option<i32>,
     pub confirmations: Option<i32>,
@@ -64,8 +90,33 @@ impl CreateAddressInDb {
         c: CreatedAddress,
         id: uuid::Uuid,
         service_id: ServiceId,
-        public_key: String,
-        private_key: String,
+        private_key: Option<String>,
+        signer_type: SignerType,
+    ) -> Self {
+        Self {
+            id,
+            service_id,
+            workchain_id: c.workchain_id,
+            hex: c.hex,
+            base64url: c.base64url,
+            public_key: Some(hex::encode(&c.public_key)),
+            private_key,
+            signer_type,
+            watch_only: false,
+            account_type: c.account_type,
+            custodians: c.custodians,
+            confirmations: c.confirmations,
+            custodians_public_keys: c
+                .custodians_public_keys
+                .map(|c| serde_json::to_value(c).unwrap_or_default()),
+        }
+    }
+
+    pub fn imported(
+        c: ImportedAddress,
+        id: uuid::Uuid,
+        service_id: ServiceId,
+        private_key: Option<String>,
     ) -> Self {
         Self {
             id,
@@ -73,8 +124,10 @@ impl CreateAddressInDb {
             workchain_id: c.workchain_id,
             hex: c.hex,
             base64url: c.base64url,
-            public_key,
+            public_key: c.public_key.map(hex::encode),
+            watch_only: private_key.is_none(),
             private_key,
+            signer_type: SignerType::Local,
             account_type: c.account_type,
             custodians: c.custodians,
             confirmations: c.confirmations,
@@ -0,0 +1,29 @@
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+use crate::models::*;
+
+/// Ledger balance of the account compared with its balance in the network.
+/// `root_address` is set for token balances of the owner
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct BalanceCheck {
+    pub service_id: ServiceId,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub root_address: Option<String>,
+    pub ledger_balance: BigDecimal,
+    pub network_balance: BigDecimal,
+}
+
+impl BalanceCheck {
+    pub fn is_consistent(&self) -> bool {
+        self.ledger_balance == self.network_balance
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BalanceDiscrepanciesSearch {
+    pub status: Option<DiscrepancyStatus>,
+    pub limit: i64,
+    pub offset: i64,
+}
@@ -0,0 +1,32 @@
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::models::*;
+
+/// Account state after a block with its transactions.
+/// `root_address` is set for token balances of the owner
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CreateBalanceSnapshot {
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub root_address: Option<String>,
+    pub balance: BigDecimal,
+    pub last_transaction_lt: BigDecimal,
+    pub account_status: AccountStatus,
+    pub block_time: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct BalanceAt {
+    pub root_address: Option<Address>,
+    pub timestamp: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct BalanceHistorySearch {
+    pub root_address: Option<Address>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    pub limit: i64,
+}
@@ -0,0 +1,11 @@
+/// Fees of the message executed locally against the current account state
+#[derive(Debug, Clone)]
+pub struct EstimatedFees {
+    pub gas_used: u64,
+    pub gas_fees: u64,
+    pub fwd_fees: u64,
+    pub storage_fees: u64,
+    pub total_fees: u64,
+    pub balance: u64,
+    pub sufficient_balance: bool,
+}
@@ -1,5 +1,6 @@
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
 use uuid::Uuid;
 
 use crate::models::*;
@@ -11,5 +12,95 @@ pub struct Key {
     pub key: String,
     pub secret: String,
     pub whitelist: Option<serde_json::Value>,
+    pub scopes: Option<Vec<String>>,
+    pub expires_at: Option<NaiveDateTime>,
     pub created_at: NaiveDateTime,
 }
+
+impl Key {
+    pub fn scopes(&self) -> KeyScopes {
+        KeyScopes(self.scopes.as_ref().map(|scopes| {
+            scopes
+                .iter()
+                .filter_map(|scope| scope.parse().ok())
+                .collect()
+        }))
+    }
+}
+
+/// Permission granted to an API key
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, opg::OpgModel, Eq, PartialEq, EnumString, Display,
+)]
+#[opg("KeyScope")]
+pub enum KeyScope {
+    /// Get balances, transactions and events, estimate fees
+    Read,
+    /// Create and import addresses, rescan their history
+    CreateAddress,
+    /// Send and confirm transactions, transfer and burn tokens
+    Send,
+    /// Mint tokens
+    TokensMint,
+    /// Approve or reject transfers requested with other keys
+    Approve,
+    /// Manage callbacks and limits
+    Admin,
+}
+
+impl KeyScope {
+    /// Scopes of the keys created without them
+    pub const DEFAULT: [KeyScope; 4] = [
+        KeyScope::Read,
+        KeyScope::CreateAddress,
+        KeyScope::Send,
+        KeyScope::TokensMint,
+    ];
+}
+
+/// Scopes of the authenticated key. A key without scopes has the default ones
+#[derive(Debug, Clone, Default)]
+pub struct KeyScopes(Option<Vec<KeyScope>>);
+
+impl KeyScopes {
+    pub fn contains(&self, scope: KeyScope) -> bool {
+        match &self.0 {
+            Some(scopes) => scopes.contains(&scope),
+            None => KeyScope::DEFAULT.contains(&scope),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApiServiceKey {
+    pub key: Option<String>,
+    pub secret: Option<String>,
+    pub whitelist: Option<Vec<String>>,
+    pub scopes: Option<Vec<KeyScope>>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateApiServiceKey {
+    pub whitelist: Option<Vec<String>>,
+    pub scopes: Option<Vec<KeyScope>>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_without_scopes_are_not_admins() {
+        let scopes = KeyScopes::default();
+        assert!(scopes.contains(KeyScope::Read));
+        assert!(scopes.contains(KeyScope::Send));
+        assert!(!scopes.contains(KeyScope::Approve));
+        assert!(!scopes.contains(KeyScope::Admin));
+
+        let scopes = KeyScopes(Some(vec![KeyScope::Admin]));
+        assert!(scopes.contains(KeyScope::Admin));
+        assert!(!scopes.contains(KeyScope::Read));
+    }
+}
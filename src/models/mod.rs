@@ -1,31 +1,55 @@
 pub use self::account_enums::*;
 pub use self::account_transaction_event::*;
 pub use self::address::*;
+pub use self::balance_discrepancies::*;
+pub use self::balance_history::*;
 pub use self::blockchain::*;
+pub use self::fees::*;
 pub use self::key::*;
 pub use self::last_key_blocks::*;
 pub use self::metrics::*;
+pub use self::multisig_proposals::*;
 pub use self::owners_cache::*;
+pub use self::payouts::*;
+pub use self::search_cursor::*;
 pub use self::service_id::*;
+pub use self::spending_limits::*;
 pub use self::sqlx::*;
+pub use self::tasks::*;
 pub use self::token_balance::*;
+pub use self::token_metadata::*;
 pub use self::token_transaction_events::*;
 pub use self::token_transactions::*;
+pub use self::token_whitelist::*;
 pub use self::transaction_events::*;
 pub use self::transactions::*;
+pub use self::transfer_approvals::*;
+pub use self::webhook_deliveries::*;
 
 mod account_enums;
 mod account_transaction_event;
 mod address;
+mod balance_discrepancies;
+mod balance_history;
 mod blockchain;
+mod fees;
 mod key;
 mod last_key_blocks;
 mod metrics;
+mod multisig_proposals;
 mod owners_cache;
+mod payouts;
+mod search_cursor;
 mod service_id;
+mod spending_limits;
 mod sqlx;
+mod tasks;
 mod token_balance;
+mod token_metadata;
 mod token_transaction_events;
 mod token_transactions;
+mod token_whitelist;
 mod transaction_events;
 mod transactions;
+mod transfer_approvals;
+mod webhook_deliveries;
@@ -0,0 +1,62 @@
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use nekoton_utils::{pack_std_smc_addr, repack_address};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::*;
+
+/// Pending transaction of the SafeMultisig wallet waiting for confirmations
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CreateMultisigProposal {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub transaction_id: i64,
+    pub creator: String,
+    pub destination: String,
+    pub value: BigDecimal,
+    pub bounce: bool,
+    pub signs_required: i32,
+    pub signs_received: i32,
+    pub confirmations: Vec<String>,
+    pub expire_at: NaiveDateTime,
+}
+
+impl MultisigProposalDb {
+    /// Event of the new proposal. It's only delivered, not stored
+    pub fn event(&self) -> anyhow::Result<AccountTransactionEvent> {
+        let account = repack_address(&format!(
+            "{}:{}",
+            self.account_workchain_id, self.account_hex
+        ))?;
+        let base64url = Address(pack_std_smc_addr(true, &account, true)?);
+
+        Ok(AccountTransactionEvent {
+            id: Uuid::new_v4(),
+            transaction_id: self.id,
+            transaction_hash: None,
+            message_hash: String::new(),
+            owner_message_hash: None,
+            account: Account {
+                workchain_id: self.account_workchain_id,
+                hex: Address(self.account_hex.clone()),
+                base64url,
+            },
+            sender: None,
+            balance_change: None,
+            root_address: None,
+            symbol: None,
+            decimals: None,
+            human_balance_change: None,
+            transaction_direction: TonTransactionDirection::Send,
+            transaction_status: TonTransactionStatus::PendingConfirmation,
+            event_status: TonEventStatus::New,
+            multisig_transaction_id: Some(self.transaction_id),
+            created_at: self.created_at.timestamp_millis(),
+            updated_at: self.updated_at.timestamp_millis(),
+            deliveries: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::*;
+
+/// Transfer with more outputs than the wallet can send in a single message.
+/// Outputs are split into chunks, each of them is sent as a separate transaction
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CreatePayoutBatch {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub bounce: bool,
+    pub payload: Option<String>,
+    pub outputs: Vec<CreatePayoutOutput>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CreatePayoutOutput {
+    pub position: i32,
+    pub chunk: i32,
+    /// Id of the transaction which sends the chunk
+    pub transaction_id: Uuid,
+    pub output: serde_json::Value,
+}
+
+#[derive(Clone, Debug)]
+pub struct PayoutBatch {
+    pub batch: PayoutBatchDb,
+    pub outputs: Vec<PayoutOutputDb>,
+}
@@ -0,0 +1,142 @@
+use std::str::FromStr;
+
+use anyhow::Context;
+use chrono::{DateTime, NaiveDateTime};
+use uuid::Uuid;
+
+/// Opaque keyset pagination cursor over `(created_at, id)`
+#[derive(Debug, Clone, Copy, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SearchCursor {
+    pub created_at: NaiveDateTime,
+    pub id: Uuid,
+}
+
+impl SearchCursor {
+    pub fn new(created_at: NaiveDateTime, id: Uuid) -> Self {
+        Self { created_at, id }
+    }
+
+    /// Returns the cursor of the next page if the current one is full
+    pub fn next<T, F>(items: &[T], limit: i64, f: F) -> Option<Self>
+    where
+        F: Fn(&T) -> Self,
+    {
+        if items.len() as i64 >= limit {
+            items.last().map(f)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for SearchCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cursor = format!(
+            "{}_{}",
+            self.created_at.and_utc().timestamp_micros(),
+            self.id
+        );
+        f.write_str(&base64::encode_config(cursor, base64::URL_SAFE_NO_PAD))
+    }
+}
+
+impl FromStr for SearchCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cursor = String::from_utf8(base64::decode_config(s, base64::URL_SAFE_NO_PAD)?)?;
+        let (created_at, id) = cursor.split_once('_').context("Invalid cursor")?;
+
+        Ok(Self {
+            created_at: DateTime::from_timestamp_micros(created_at.parse()?)
+                .context("Invalid cursor timestamp")?
+                .naive_utc(),
+            id: Uuid::parse_str(id)?,
+        })
+    }
+}
+
+/// Position of an event in the events stream, its sequence number
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct EventCursor(pub i64);
+
+impl std::fmt::Display for EventCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&base64::encode_config(
+            self.0.to_string(),
+            base64::URL_SAFE_NO_PAD,
+        ))
+    }
+}
+
+impl FromStr for EventCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cursor = String::from_utf8(base64::decode_config(s, base64::URL_SAFE_NO_PAD)?)?;
+        Ok(Self(cursor.parse()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_cursor_roundtrip() {
+        let created_at = DateTime::from_timestamp_micros(1_700_000_000_123_456)
+            .unwrap()
+            .naive_utc();
+        let cursor = SearchCursor::new(created_at, Uuid::new_v4());
+        let encoded = cursor.to_string();
+        assert!(!encoded.contains(['+', '/', '=']));
+        assert_eq!(SearchCursor::from_str(&encoded).unwrap(), cursor);
+    }
+
+    #[test]
+    fn invalid_search_cursor() {
+        assert!(SearchCursor::from_str("").is_err());
+        assert!(SearchCursor::from_str("not base64!").is_err());
+        for cursor in [
+            "1700000000",
+            "abc_00000000-0000-0000-0000-000000000000",
+            "1_abc",
+        ] {
+            let encoded = base64::encode_config(cursor, base64::URL_SAFE_NO_PAD);
+            assert!(SearchCursor::from_str(&encoded).is_err());
+        }
+    }
+
+    #[test]
+    fn search_cursor_next_page() {
+        let created_at = DateTime::from_timestamp(1_700_000_000, 0)
+            .unwrap()
+            .naive_utc();
+        let items = [
+            SearchCursor::new(created_at, Uuid::new_v4()),
+            SearchCursor::new(created_at, Uuid::new_v4()),
+        ];
+
+        assert_eq!(SearchCursor::next(&items, 2, |c| *c), Some(items[1]));
+        assert_eq!(SearchCursor::next(&items, 3, |c| *c), None);
+        assert_eq!(SearchCursor::next::<SearchCursor, _>(&[], 0, |c| *c), None);
+    }
+
+    #[test]
+    fn event_cursor_roundtrip() {
+        for seq in [0, 1, 42, i64::MAX] {
+            let cursor = EventCursor(seq);
+            let encoded = cursor.to_string();
+            assert!(!encoded.contains(['+', '/', '=']));
+            assert_eq!(EventCursor::from_str(&encoded).unwrap(), cursor);
+        }
+    }
+
+    #[test]
+    fn invalid_event_cursor() {
+        assert!(EventCursor::from_str("").is_err());
+        assert!(EventCursor::from_str("not base64!").is_err());
+        let encoded = base64::encode_config("abc", base64::URL_SAFE_NO_PAD);
+        assert!(EventCursor::from_str(&encoded).is_err());
+    }
+}
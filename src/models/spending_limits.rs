@@ -0,0 +1,16 @@
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+/// Limits outgoing transfers of the service. The rule applies to all addresses of the service
+/// unless `account` is specified, to native transfers unless `root_address` is specified
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CreateSpendingLimit {
+    pub account: Option<String>,
+    pub root_address: Option<String>,
+    pub max_per_transaction: Option<BigDecimal>,
+    pub hourly_limit: Option<BigDecimal>,
+    pub daily_limit: Option<BigDecimal>,
+    /// Transfers above the threshold wait for an approval with another key
+    pub approval_threshold: Option<BigDecimal>,
+    pub allowed_recipients: Option<Vec<String>>,
+}
@@ -19,6 +19,8 @@ pub struct ApiServiceKeyDb {
     pub key: String,
     pub secret: String,
     pub whitelist: Option<serde_json::Value>,
+    pub scopes: Option<Vec<String>>,
+    pub expires_at: Option<NaiveDateTime>,
     pub created_at: NaiveDateTime,
 }
 
@@ -27,16 +29,28 @@ pub struct ApiServiceCallbackDb {
     pub id: Uuid,
     pub service_id: ServiceId,
     pub callback: String,
+    pub notify_type: Option<NotifyType>,
+    pub transaction_direction: Option<TonTransactionDirection>,
+    pub root_address: Option<String>,
+    pub account_workchain_id: Option<i32>,
+    pub account_hex: Option<String>,
     pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
 }
 
 impl ApiServiceCallbackDb {
-    pub fn new(id: Uuid, service_id: ServiceId, callback: String) -> Self {
+    pub fn new(id: Uuid, service_id: ServiceId, callback: String, filter: CallbackFilter) -> Self {
         Self {
             id,
             service_id,
             callback,
+            notify_type: filter.notify_type,
+            transaction_direction: filter.transaction_direction,
+            root_address: filter.root_address,
+            account_workchain_id: filter.account_workchain_id,
+            account_hex: filter.account_hex,
             created_at: Utc::now().naive_utc(),
+            updated_at: Utc::now().naive_utc(),
         }
     }
 }
@@ -48,8 +62,10 @@ pub struct AddressDb {
     pub workchain_id: i32,
     pub hex: String,
     pub base64url: String,
-    pub public_key: String,
-    pub private_key: String,
+    pub public_key: Option<String>,
+    pub private_key: Option<String>,
+    pub signer_type: SignerType,
+    pub watch_only: bool,
     pub account_type: AccountType,
     pub custodians: Option<i32>,
     pub confirmations: Option<i32>,
@@ -184,4 +200,205 @@ pub struct WhitelistedTokenFromDb {
     pub name: String,
     pub address: String,
     pub version: TokenWalletVersionDb,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    pub total_supply: Option<BigDecimal>,
+    pub metadata_updated_at: Option<NaiveDateTime>,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct TaskDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub status: TaskStatus,
+    pub kind: TaskKind,
+    pub data: serde_json::Value,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct PayoutBatchDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub bounce: bool,
+    pub payload: Option<String>,
+    pub status: PayoutBatchStatus,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct PayoutOutputDb {
+    pub batch_id: Uuid,
+    pub position: i32,
+    pub chunk: i32,
+    pub transaction_id: Uuid,
+    pub output: serde_json::Value,
+    pub status: PayoutOutputStatus,
+    pub error: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct EventStreamDb {
+    pub seq: i64,
+    pub service_id: ServiceId,
+    pub event_id: Uuid,
+    pub notify_type: NotifyType,
+    pub payload: serde_json::Value,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct WebhookDeliveryDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub callback_id: Option<Uuid>,
+    pub event_id: Uuid,
+    pub notify_type: NotifyType,
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: NaiveDateTime,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct SpendingLimitDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub account_workchain_id: Option<i32>,
+    pub account_hex: Option<String>,
+    pub root_address: Option<String>,
+    pub max_per_transaction: Option<BigDecimal>,
+    pub hourly_limit: Option<BigDecimal>,
+    pub daily_limit: Option<BigDecimal>,
+    pub approval_threshold: Option<BigDecimal>,
+    pub allowed_recipients: Option<Vec<String>>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Outgoing transfer counted by the spending limits
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct SpendingRecordDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub root_address: Option<String>,
+    pub value: BigDecimal,
+    pub transaction_id: Option<Uuid>,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct IdempotencyKeyDb {
+    pub service_id: ServiceId,
+    pub key: String,
+    pub request_hash: String,
+    pub response_status: Option<i32>,
+    pub response_body: Option<Vec<u8>>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct TransferApprovalDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub notify_type: NotifyType,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub root_address: Option<String>,
+    pub value: BigDecimal,
+    pub input: serde_json::Value,
+    pub status: TonTransactionStatus,
+    pub requested_by: Uuid,
+    pub decided_by: Option<Uuid>,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct MultisigProposalDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub transaction_id: i64,
+    pub creator: String,
+    pub destination: String,
+    pub value: BigDecimal,
+    pub bounce: bool,
+    pub signs_required: i32,
+    pub signs_received: i32,
+    pub confirmations: Vec<String>,
+    pub expire_at: NaiveDateTime,
+    pub status: MultisigProposalStatus,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct BalanceSnapshotDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub root_address: Option<String>,
+    pub balance: BigDecimal,
+    pub last_transaction_lt: BigDecimal,
+    pub account_status: AccountStatus,
+    pub block_time: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct BalanceDiscrepancyDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub root_address: Option<String>,
+    pub ledger_balance: BigDecimal,
+    pub network_balance: BigDecimal,
+    pub status: DiscrepancyStatus,
+    pub rescan_requested: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Sent message waiting to be included into a block
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct PendingMessageDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub message_hash: String,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub message_boc: Option<Vec<u8>>,
+    pub expire_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Sent transaction which can be signed again after its message expires
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct ResendTransactionDb {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub message_hash: String,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub resend_input: Option<serde_json::Value>,
+    pub send_attempts: i32,
+    pub expire_at: Option<NaiveDateTime>,
 }
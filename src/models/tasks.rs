@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::*;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CreateTask {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub kind: TaskKind,
+    pub data: serde_json::Value,
+}
+
+/// Progress of the historical transactions rescan of a single account.
+/// Token wallets of the address are rescanned by separate tasks.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RescanTaskData {
+    /// Token wallet of the address, rescan of the address itself if empty
+    pub token_wallet: Option<String>,
+    /// Next transaction to load, rescan starts from the last account transaction if empty
+    pub cursor: Option<TransactionsHistoryCursor>,
+    pub transactions_found: i64,
+    pub transactions_inserted: i64,
+    /// Set when the rescan stopped at a transaction whose block is pruned from the node storage,
+    /// older transactions are not available
+    pub pruned_before_lt: Option<u64>,
+}
+
+/// Part of the account transactions history loaded from the node storage
+#[derive(Clone, Debug)]
+pub struct TransactionsHistoryBatch<T> {
+    pub transactions: Vec<T>,
+    /// Next transaction to load, empty when there is nothing more to load
+    pub cursor: Option<TransactionsHistoryCursor>,
+    /// Lt of the transaction which could not be loaded because its block is pruned
+    pub pruned_before_lt: Option<u64>,
+}
+
+impl<T> TransactionsHistoryBatch<T> {
+    pub fn empty() -> Self {
+        Self {
+            transactions: Vec::new(),
+            cursor: None,
+            pruned_before_lt: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionsHistoryCursor {
+    pub transaction_lt: u64,
+    pub transaction_hash: String,
+    /// Shard block to search the transaction from
+    pub block_id: String,
+}
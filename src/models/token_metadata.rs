@@ -0,0 +1,77 @@
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+
+use crate::models::*;
+
+/// Root token contract details used to format token amounts
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct TokenMetadata {
+    pub symbol: String,
+    pub decimals: i32,
+    pub total_supply: BigDecimal,
+}
+
+impl TokenMetadata {
+    pub fn from_whitelist(token: &WhitelistedTokenFromDb) -> Option<Self> {
+        Some(Self {
+            symbol: token.symbol.clone()?,
+            decimals: token.decimals?,
+            total_supply: token.total_supply.clone()?,
+        })
+    }
+
+    /// Converts the amount in minimal units to the amount in tokens
+    pub fn human_amount(&self, amount: &BigDecimal) -> BigDecimal {
+        let (value, scale) = amount.as_bigint_and_exponent();
+        BigDecimal::new(value, scale + self.decimals as i64).normalized()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn make_metadata(decimals: i32) -> TokenMetadata {
+        TokenMetadata {
+            symbol: "TKN".to_owned(),
+            decimals,
+            total_supply: BigDecimal::from(0),
+        }
+    }
+
+    #[test]
+    fn human_amount() {
+        let metadata = make_metadata(9);
+        assert_eq!(
+            metadata.human_amount(&BigDecimal::from(1_500_000_000)),
+            BigDecimal::from_str("1.5").unwrap()
+        );
+        assert_eq!(
+            metadata.human_amount(&BigDecimal::from(1)),
+            BigDecimal::from_str("0.000000001").unwrap()
+        );
+        assert_eq!(
+            metadata.human_amount(&BigDecimal::from(0)),
+            BigDecimal::from(0)
+        );
+        assert_eq!(
+            metadata.human_amount(&BigDecimal::from(-2_000_000_000)),
+            BigDecimal::from(-2)
+        );
+    }
+
+    #[test]
+    fn human_amount_without_decimals() {
+        let metadata = make_metadata(0);
+        assert_eq!(
+            metadata.human_amount(&BigDecimal::from(12345)),
+            BigDecimal::from(12345)
+        );
+        assert_eq!(
+            metadata.human_amount(&BigDecimal::from_str("1.5").unwrap()),
+            BigDecimal::from_str("1.5").unwrap()
+        );
+    }
+}
@@ -46,6 +46,7 @@ impl CreateTokenTransactionEvent {
 pub struct TokenTransactionsEventsSearch {
     pub limit: i64,
     pub offset: i64,
+    pub cursor: Option<SearchCursor>,
     pub created_at_ge: Option<i64>,
     pub created_at_le: Option<i64>,
     pub token_transaction_id: Option<Uuid>,
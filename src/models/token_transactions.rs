@@ -37,6 +37,8 @@ pub struct TokenTransactionSend {
     pub send_gas_to: Option<Address>,
     pub fee: BigDecimal,
     pub payload: Option<String>,
+    #[serde(default)]
+    pub retry_on_expire: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -0,0 +1,8 @@
+use crate::models::*;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct CreateRootToken {
+    pub name: String,
+    pub address: Address,
+    pub version: TokenWalletVersionDb,
+}
@@ -92,6 +92,7 @@ impl CreateReceiveTransactionEvent {
 pub struct TransactionsEventsSearch {
     pub limit: i64,
     pub offset: i64,
+    pub cursor: Option<SearchCursor>,
     pub created_at_ge: Option<i64>,
     pub created_at_le: Option<i64>,
     pub transaction_id: Option<Uuid>,
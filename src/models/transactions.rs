@@ -12,6 +12,16 @@ pub struct TransactionSend {
     pub outputs: Vec<TransactionSendOutput>,
     pub bounce: Option<bool>,
     pub payload: Option<String>,
+    #[serde(default)]
+    pub retry_on_expire: bool,
+}
+
+/// Transfer which is signed again with a new expiration when its message expires
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "type", content = "input")]
+pub enum ResendTransfer {
+    Ton(TransactionSend),
+    Token(TokenTransactionSend),
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
@@ -165,6 +175,7 @@ pub struct TransactionsSearch {
     pub ordering: Option<TransactionsSearchOrdering>,
     pub limit: i64,
     pub offset: i64,
+    pub cursor: Option<SearchCursor>,
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
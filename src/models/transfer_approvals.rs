@@ -0,0 +1,102 @@
+use bigdecimal::BigDecimal;
+use nekoton_utils::{pack_std_smc_addr, repack_address};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::*;
+
+/// Outgoing transfer which is sent only after another key approves it
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CreateTransferApproval {
+    pub id: Uuid,
+    pub service_id: ServiceId,
+    pub notify_type: NotifyType,
+    pub account_workchain_id: i32,
+    pub account_hex: String,
+    pub root_address: Option<String>,
+    pub value: BigDecimal,
+    pub input: serde_json::Value,
+    pub requested_by: Uuid,
+}
+
+impl TransferApprovalDb {
+    /// Transaction as returned to the requester until the transfer is approved
+    pub fn pending_transaction(&self) -> TransactionDb {
+        let (original_value, original_outputs) = match self.notify_type {
+            NotifyType::Transaction => {
+                (Some(self.value.clone()), self.input.get("outputs").cloned())
+            }
+            NotifyType::TokenTransaction => (None, None),
+        };
+
+        TransactionDb {
+            id: self.id,
+            service_id: self.service_id,
+            message_hash: String::new(),
+            transaction_hash: None,
+            transaction_lt: None,
+            transaction_timeout: None,
+            transaction_scan_lt: None,
+            transaction_timestamp: None,
+            sender_workchain_id: None,
+            sender_hex: None,
+            account_workchain_id: self.account_workchain_id,
+            account_hex: self.account_hex.clone(),
+            messages: None,
+            messages_hash: None,
+            data: None,
+            original_value,
+            original_outputs,
+            value: None,
+            fee: None,
+            balance_change: None,
+            direction: TonTransactionDirection::Send,
+            status: self.status.clone(),
+            error: self.error.clone(),
+            aborted: false,
+            bounce: self
+                .input
+                .get("bounce")
+                .and_then(|bounce| bounce.as_bool())
+                .unwrap_or_default(),
+            multisig_transaction_id: None,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Event of the approval state change. It's only delivered, not stored
+    pub fn event(&self) -> anyhow::Result<AccountTransactionEvent> {
+        let account = repack_address(&format!(
+            "{}:{}",
+            self.account_workchain_id, self.account_hex
+        ))?;
+        let base64url = Address(pack_std_smc_addr(true, &account, true)?);
+
+        Ok(AccountTransactionEvent {
+            id: Uuid::new_v4(),
+            transaction_id: self.id,
+            transaction_hash: None,
+            message_hash: String::new(),
+            owner_message_hash: None,
+            account: Account {
+                workchain_id: self.account_workchain_id,
+                hex: Address(self.account_hex.clone()),
+                base64url,
+            },
+            sender: None,
+            balance_change: None,
+            root_address: self.root_address.clone(),
+            symbol: None,
+            decimals: None,
+            human_balance_change: None,
+            transaction_direction: TonTransactionDirection::Send,
+            transaction_status: self.status.clone(),
+            event_status: TonEventStatus::New,
+            multisig_transaction_id: None,
+            created_at: self.created_at.timestamp_millis(),
+            updated_at: self.updated_at.timestamp_millis(),
+            deliveries: Vec::new(),
+        })
+    }
+}
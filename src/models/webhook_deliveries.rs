@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::*;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct CreateCallback {
+    pub callback: String,
+    pub notify_type: Option<NotifyType>,
+    pub transaction_direction: Option<TonTransactionDirection>,
+    pub root_address: Option<String>,
+    pub account: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, Eq, PartialEq)]
+pub struct CallbackFilter {
+    pub notify_type: Option<NotifyType>,
+    pub transaction_direction: Option<TonTransactionDirection>,
+    pub root_address: Option<String>,
+    pub account_workchain_id: Option<i32>,
+    pub account_hex: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, opg::OpgModel)]
+#[serde(rename_all = "camelCase")]
+#[opg("WebhookDelivery")]
+pub struct WebhookDelivery {
+    pub callback_id: Option<Uuid>,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub updated_at: i64,
+}
+
+impl From<WebhookDeliveryDb> for WebhookDelivery {
+    fn from(d: WebhookDeliveryDb) -> Self {
+        let next_attempt_at = match d.status {
+            WebhookDeliveryStatus::Pending => Some(d.next_attempt_at.timestamp_millis()),
+            WebhookDeliveryStatus::Delivered | WebhookDeliveryStatus::Failed => None,
+        };
+
+        Self {
+            callback_id: d.callback_id,
+            status: d.status,
+            attempts: d.attempts,
+            next_attempt_at,
+            last_error: d.last_error,
+            updated_at: d.updated_at.timestamp_millis(),
+        }
+    }
+}
@@ -5,4 +5,40 @@ pub const DEPLOY_TOKEN_VALUE: u64 = 200_000_000; // 0.2 TON
 
 pub const DEFAULT_EXPIRATION_TIMEOUT: u32 = 60; // sec
 
+pub const WEBHOOK_REQUEST_TIMEOUT: u64 = 30; // sec
+pub const WEBHOOK_DELIVERY_LOCK_TIMEOUT: i64 = 120; // sec
+
+pub const EVENTS_STREAM_CAPACITY: usize = 1024;
+pub const EVENTS_STREAM_TTL: i64 = 604800; // sec
+pub const EVENTS_STREAM_CLEANUP_INTERVAL: u64 = 3600; // sec
+pub const EVENTS_LISTENER_RETRY_INTERVAL: u64 = 1; // sec
+
+pub const RESCAN_BATCH_SIZE: usize = 100;
+pub const RESCAN_POLL_INTERVAL: u64 = 10; // sec
+
+pub const WALLET_MAX_OUTPUTS: usize = 4;
+pub const HIGHLOAD_WALLET_MAX_OUTPUTS: usize = 100;
+pub const PAYOUT_POLL_INTERVAL: u64 = 5; // sec
+
+pub const TOKEN_METADATA_REFRESH_INTERVAL: u64 = 600; // sec
+pub const TOKEN_METADATA_SYNC_POLL_INTERVAL: u64 = 10; // sec
+
+pub const PENDING_MESSAGES_SYNC_POLL_INTERVAL: u64 = 5; // sec
+pub const MAX_SEND_ATTEMPTS: i32 = 3;
+pub const EXPIRED_MESSAGE_POLL_INTERVAL: u64 = 1; // sec
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const IDEMPOTENCY_KEY_MAX_LEN: usize = 255;
+pub const IDEMPOTENCY_KEY_TTL: i64 = 86400; // sec
+pub const IDEMPOTENCY_CLEANUP_INTERVAL: u64 = 3600; // sec
+
+pub const TRANSFER_APPROVAL_TTL: i64 = 86400; // sec
+pub const TRANSFER_APPROVAL_SEND_TIMEOUT: i64 = 600; // sec
+pub const TRANSFER_APPROVALS_SWEEP_INTERVAL: u64 = 60; // sec
+
+pub const SAFE_MULTISIG_TRANSACTION_LIFETIME: u64 = 3600; // sec
+pub const MULTISIG_PROPOSALS_POLL_INTERVAL: u64 = 30; // sec
+pub const MULTISIG_PROPOSAL_MISSING_READS: u32 = 3;
+
 pub const MAX_LIMIT_SEARCH: i64 = 100i64;
+pub const MAX_LIMIT_BALANCE_HISTORY: i64 = 1000i64;
@@ -63,6 +63,15 @@ impl Engine {
             self.context.memory_storage.clone(),
         ));
 
+        if let Some(admin_api) = &self.context.config.admin_api {
+            tokio::spawn(admin_http_service(
+                admin_api.server_addr,
+                admin_api.auth_token.clone(),
+                self.context.auth_service.clone(),
+                self.context.ton_service.clone(),
+            ));
+        }
+
         // Done
         Ok(())
     }
@@ -100,6 +109,7 @@ impl EngineContext {
 
         let (ton_transaction_tx, ton_transaction_rx) = mpsc::unbounded_channel();
         let (token_transaction_tx, token_transaction_rx) = mpsc::unbounded_channel();
+        let (balance_snapshot_tx, balance_snapshot_rx) = mpsc::unbounded_channel();
 
         let node_config = config.ton_core.clone();
         let ton_core = TonCore::new(
@@ -109,16 +119,27 @@ impl EngineContext {
             owners_cache,
             ton_transaction_tx,
             token_transaction_tx,
+            balance_snapshot_tx,
         )
         .await?;
 
         let ton_client = Arc::new(TonClient::new(ton_core.clone(), sqlx_client.clone()));
 
+        let remote_signer = config
+            .remote_signer
+            .as_ref()
+            .map(RemoteSignerClient::new)
+            .transpose()?
+            .map(Arc::new);
+
         let ton_service = Arc::new(TonService::new(
             sqlx_client.clone(),
             ton_client.clone(),
             callback_client.clone(),
-            config.key.clone(),
+            config.keyring()?,
+            remote_signer,
+            config.webhook.clone(),
+            config.reconciliation.clone(),
         ));
 
         let auth_service = Arc::new(AuthService::new(sqlx_client.clone()));
@@ -138,13 +159,16 @@ impl EngineContext {
 
         engine_context.start_listening_ton_transaction(ton_transaction_rx);
         engine_context.start_listening_token_transaction(token_transaction_rx);
+        engine_context.start_listening_balance_snapshots(balance_snapshot_rx);
 
         Ok(engine_context)
     }
 
     async fn start(&self) -> Result<()> {
         self.ton_client.start().await?;
-        self.ton_service.start().await?;
+        self.ton_service
+            .start(self.config.rebroadcast_pending_messages)
+            .await?;
         self.ton_core.start().await?;
 
         Ok(())
@@ -277,6 +301,38 @@ impl EngineContext {
         });
     }
 
+    fn start_listening_balance_snapshots(self: &Arc<Self>, mut rx: BalanceSnapshotRx) {
+        let engine_context = Arc::downgrade(self);
+
+        tokio::spawn(async move {
+            while let Some(snapshot) = rx.recv().await {
+                let engine_context = match engine_context.upgrade() {
+                    Some(engine_context) => engine_context,
+                    None => {
+                        log::error!("Engine is already dropped");
+                        return;
+                    }
+                };
+
+                let account_hex = snapshot.account_hex.clone();
+                if let Err(e) = engine_context
+                    .ton_service
+                    .create_balance_snapshot(snapshot)
+                    .await
+                {
+                    log::error!(
+                        "Failed to create balance snapshot of account '{}': {:?}",
+                        account_hex,
+                        e
+                    )
+                }
+            }
+
+            rx.close();
+            while rx.recv().await.is_some() {}
+        });
+    }
+
     fn get_guard(&self, account: String) -> Arc<Mutex<()>> {
         use dashmap::mapref::entry::Entry;
 
@@ -346,6 +402,20 @@ impl std::fmt::Display for LabeledTonSubscriberMetrics<'_> {
         f.begin_metric("ton_subscriber_pending_message_count")
             .value(metrics.pending_message_count)?;
 
+        let broadcast_metrics = &self.0.ton_core.context.broadcast_metrics;
+
+        f.begin_metric("ton_subscriber_message_broadcasts")
+            .value(broadcast_metrics.broadcasts())?;
+
+        f.begin_metric("ton_subscriber_expired_messages")
+            .value(broadcast_metrics.expired())?;
+
+        for (attempts, count) in broadcast_metrics.delivered() {
+            f.begin_metric("ton_subscriber_delivered_messages")
+                .label("broadcasts", attempts)
+                .value(count)?;
+        }
+
         Ok(())
     }
 }
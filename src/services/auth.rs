@@ -4,11 +4,16 @@ use std::sync::Arc;
 use anyhow::Context;
 use chrono::{NaiveDateTime, Utc};
 use parking_lot::Mutex;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
 
 use crate::models::*;
 use crate::sqlx_client::*;
 
 pub const TIMESTAMP_EXPIRED_SEC: i64 = 10;
+const GENERATED_KEY_LEN: usize = 32;
 
 #[derive(Clone)]
 pub struct AuthService {
@@ -32,12 +37,16 @@ impl AuthService {
         path: &str,
         body: &str,
         real_ip: Option<String>,
-    ) -> anyhow::Result<ServiceId> {
+    ) -> anyhow::Result<Key> {
         let key = self
             .get_key(api_key)
             .await
             .map_err(|_| anyhow::Error::msg(format!("Can not find api key {} in db", api_key)))?;
 
+        if matches!(key.expires_at, Some(expires_at) if expires_at <= Utc::now().naive_utc()) {
+            anyhow::bail!(format!("Api key {} has expired", api_key))
+        }
+
         if let Some(whitelist) = key.whitelist {
             let whitelist: Vec<String> = serde_json::from_value(whitelist)
                 .map_err(|_| anyhow::Error::msg("Can not parse ips whitelist"))?;
@@ -73,11 +82,11 @@ impl AuthService {
 
         let expected_signature = base64::decode(signature)?;
 
-        if calculated_signature != expected_signature.as_slice() {
+        if !bool::from(calculated_signature.as_slice().ct_eq(&expected_signature)) {
             anyhow::bail!("Invalid signature");
         }
 
-        Ok(key.service_id)
+        Ok(key)
     }
 
     async fn get_key(&self, api_key: &str) -> anyhow::Result<Key> {
@@ -99,4 +108,117 @@ impl AuthService {
 
         Ok(key)
     }
+
+    pub async fn get_services(&self) -> anyhow::Result<Vec<ApiServiceDb>> {
+        self.sqlx_client.get_api_services().await
+    }
+
+    pub async fn get_service(&self, service_id: &ServiceId) -> anyhow::Result<ApiServiceDb> {
+        self.sqlx_client.get_api_service(*service_id).await
+    }
+
+    pub async fn create_service(
+        &self,
+        service_id: Option<ServiceId>,
+        name: &str,
+    ) -> anyhow::Result<ApiServiceDb> {
+        let service_id = service_id.unwrap_or_else(ServiceId::generate);
+        self.sqlx_client.create_api_service(service_id, name).await
+    }
+
+    pub async fn update_service(
+        &self,
+        service_id: &ServiceId,
+        name: &str,
+    ) -> anyhow::Result<ApiServiceDb> {
+        self.sqlx_client.update_api_service(*service_id, name).await
+    }
+
+    pub async fn delete_service(&self, service_id: &ServiceId) -> anyhow::Result<ApiServiceDb> {
+        let (service, keys) = self.sqlx_client.delete_api_service(*service_id).await?;
+        for key in keys {
+            self.invalidate_key(&key.key);
+        }
+        Ok(service)
+    }
+
+    pub async fn get_service_keys(
+        &self,
+        service_id: &ServiceId,
+    ) -> anyhow::Result<Vec<ApiServiceKeyDb>> {
+        self.sqlx_client.get_api_service_keys(*service_id).await
+    }
+
+    /// Creates a key of the service. The key and the secret are generated when not specified
+    pub async fn create_service_key(
+        &self,
+        service_id: &ServiceId,
+        input: CreateApiServiceKey,
+    ) -> anyhow::Result<ApiServiceKeyDb> {
+        let key = input.key.unwrap_or_else(generate_key);
+        let secret = input.secret.unwrap_or_else(generate_key);
+        let whitelist = input.whitelist.map(serde_json::to_value).transpose()?;
+        let scopes = input.scopes.map(scopes_to_strings);
+
+        self.sqlx_client
+            .create_api_service_key(
+                *service_id,
+                &key,
+                &secret,
+                whitelist,
+                scopes,
+                input.expires_at,
+            )
+            .await
+    }
+
+    pub async fn update_service_key(
+        &self,
+        id: &Uuid,
+        input: UpdateApiServiceKey,
+    ) -> anyhow::Result<ApiServiceKeyDb> {
+        let whitelist = input.whitelist.map(serde_json::to_value).transpose()?;
+        let scopes = input.scopes.map(scopes_to_strings);
+
+        let key = self
+            .sqlx_client
+            .update_api_service_key(*id, whitelist, scopes, input.expires_at)
+            .await?;
+        self.invalidate_key(&key.key);
+
+        Ok(key)
+    }
+
+    pub async fn rotate_service_key_secret(&self, id: &Uuid) -> anyhow::Result<ApiServiceKeyDb> {
+        let key = self
+            .sqlx_client
+            .update_api_service_key_secret(*id, &generate_key())
+            .await?;
+        self.invalidate_key(&key.key);
+
+        Ok(key)
+    }
+
+    pub async fn delete_service_key(&self, id: &Uuid) -> anyhow::Result<ApiServiceKeyDb> {
+        let key = self.sqlx_client.delete_api_service_key(*id).await?;
+        self.invalidate_key(&key.key);
+
+        Ok(key)
+    }
+
+    fn invalidate_key(&self, api_key: &str) {
+        self.keys_hash.lock().remove(api_key);
+    }
+}
+
+fn scopes_to_strings(scopes: Vec<KeyScope>) -> Vec<String> {
+    scopes.iter().map(ToString::to_string).collect()
+}
+
+fn generate_key() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(GENERATED_KEY_LEN)
+        .map(char::from)
+        .collect()
 }
@@ -1,5 +1,6 @@
 use std::time::SystemTime;
 
+use bigdecimal::BigDecimal;
 use nekoton::crypto::UnsignedMessage;
 use nekoton_utils::TrustMe;
 
@@ -7,23 +8,36 @@ use crate::utils::FxDashMap;
 
 #[derive(Default)]
 pub struct StorageHandler {
-    message_collection: FxDashMap<String, Box<dyn UnsignedMessage>>,
+    message_collection: FxDashMap<String, (Box<dyn UnsignedMessage>, PreparedTransfer)>,
 }
 
 impl StorageHandler {
-    pub fn add_message(&self, message: Box<dyn UnsignedMessage>) -> String {
+    pub fn add_message(
+        &self,
+        message: Box<dyn UnsignedMessage>,
+        transfer: PreparedTransfer,
+    ) -> String {
         let key = hex::encode(message.hash());
-        self.message_collection.insert(key.clone(), message);
+        self.message_collection
+            .insert(key.clone(), (message, transfer));
         key
     }
 
-    pub fn get_message(&self, hash: &str) -> Option<Box<dyn UnsignedMessage>> {
+    pub fn get_message(&self, hash: &str) -> Option<(Box<dyn UnsignedMessage>, PreparedTransfer)> {
         let now = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .trust_me()
             .as_secs() as u32;
-        self.message_collection.retain(|_, v| v.expire_at() > now);
+        self.message_collection
+            .retain(|_, (v, _)| v.expire_at() > now);
         let message = self.message_collection.get(hash).map(|x| x.value().clone());
         message
     }
 }
+
+/// Transfer made by the prepared message, checked by the spending limits when it is sent
+#[derive(Clone, Debug)]
+pub struct PreparedTransfer {
+    pub value: BigDecimal,
+    pub recipient: String,
+}
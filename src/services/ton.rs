@@ -1,15 +1,20 @@
-use std::convert::TryInto;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 
 use bigdecimal::BigDecimal;
+use chrono::{NaiveDateTime, Utc};
+use futures::StreamExt;
 use http::StatusCode;
+use metrics::gauge;
 use nekoton::crypto::{SignedMessage, UnsignedMessage};
 use nekoton_utils::{repack_address, unpack_std_smc_addr};
+use rand::Rng;
 use serde_json::Value;
 use ton_abi::contract::ABI_VERSION_2_2;
 use ton_abi::{Param, Token, TokenValue};
-use ton_block::{GetRepresentationHash, MsgAddressInt, Serializable};
+use ton_block::{Deserializable, GetRepresentationHash, MsgAddressInt, Serializable};
 use ton_types::{BuilderData, UInt256};
 use uuid::Uuid;
 
@@ -17,7 +22,10 @@ use crate::api::*;
 use crate::client::*;
 use crate::models::*;
 use crate::prelude::*;
+use crate::services::PreparedTransfer;
+use crate::settings::*;
 use crate::sqlx_client::*;
+use crate::ton_core::CaughtTonTransaction;
 use crate::utils::*;
 
 #[derive(Clone)]
@@ -25,45 +33,154 @@ pub struct TonService {
     sqlx_client: SqlxClient,
     ton_api_client: Arc<TonClient>,
     callback_client: Arc<CallbackClient>,
-    key: Arc<Vec<u8>>,
+    keyring: Arc<Keyring>,
+    remote_signer: Option<Arc<RemoteSignerClient>>,
+    webhook_config: WebhookConfig,
+    reconciliation_config: ReconciliationConfig,
+    webhook_notify: Arc<tokio::sync::Notify>,
+    rescan_notify: Arc<tokio::sync::Notify>,
+    payouts_notify: Arc<tokio::sync::Notify>,
+    /// Serializes the spending checks of each service
+    spending_locks: Arc<parking_lot::Mutex<HashMap<ServiceId, Arc<tokio::sync::Mutex<()>>>>>,
+    token_metadata: Arc<parking_lot::RwLock<HashMap<String, TokenMetadata>>>,
+    /// Roots available to the services, `None` for the services without the limit
+    service_root_tokens: Arc<parking_lot::RwLock<HashMap<ServiceId, Option<HashSet<String>>>>>,
+    /// Consecutive reads of the multisig contracts without the open proposals
+    multisig_proposal_misses: Arc<parking_lot::Mutex<HashMap<Uuid, u32>>>,
+    events_tx: EventsSender,
 }
 
+/// Streamed events in the order of their commits. `None` is sent when some of them could be missed
+type EventsSender = tokio::sync::broadcast::Sender<Option<Arc<EventStreamDb>>>;
+type EventsReceiver = tokio::sync::broadcast::Receiver<Option<Arc<EventStreamDb>>>;
+
 impl TonService {
     pub fn new(
         sqlx_client: SqlxClient,
         ton_api_client: Arc<TonClient>,
         callback_client: Arc<CallbackClient>,
-        key: Vec<u8>,
+        keyring: Keyring,
+        remote_signer: Option<Arc<RemoteSignerClient>>,
+        webhook_config: WebhookConfig,
+        reconciliation_config: ReconciliationConfig,
     ) -> Self {
-        let key = Arc::new(key);
+        let keyring = Arc::new(keyring);
+        let (events_tx, _) = tokio::sync::broadcast::channel(EVENTS_STREAM_CAPACITY);
         Self {
             sqlx_client,
             ton_api_client,
             callback_client,
-            key,
+            keyring,
+            remote_signer,
+            webhook_config,
+            reconciliation_config,
+            webhook_notify: Default::default(),
+            rescan_notify: Default::default(),
+            payouts_notify: Default::default(),
+            spending_locks: Default::default(),
+            token_metadata: Default::default(),
+            service_root_tokens: Default::default(),
+            multisig_proposal_misses: Default::default(),
+            events_tx,
         }
     }
 
-    pub async fn start(self: &Arc<Self>) -> anyhow::Result<()> {
+    /// Starts background tasks. Still valid sent messages are broadcast
+    /// again if `rebroadcast_pending_messages` is set
+    pub async fn start(self: &Arc<Self>, rebroadcast_pending_messages: bool) -> anyhow::Result<()> {
         // Get unprocessed sent transactions
-        let transactions: Vec<TransactionDb> = self
-            .sqlx_client
-            .get_all_transactions_by_status(TonTransactionStatus::New)
-            .await?;
-
-        // Resend transactions
-        for transaction in transactions {
-            let account = UInt256::from_be_bytes(&hex::decode(transaction.account_hex.clone())?);
-            let message_hash =
-                UInt256::from_be_bytes(&hex::decode(transaction.message_hash.clone())?);
-            let expire_at = transaction.created_at.timestamp() as u32 + DEFAULT_EXPIRATION_TIMEOUT;
+        let messages = self.sqlx_client.get_pending_messages().await?;
+
+        // Restore the pending messages queue
+        for message in messages {
+            let account = MsgAddressInt::from_str(&format!(
+                "{}:{}",
+                message.account_workchain_id, message.account_hex
+            ))?;
+            let message_hash = UInt256::from_be_bytes(&hex::decode(&message.message_hash)?);
+            let expire_at = match message.expire_at {
+                Some(expire_at) => expire_at.timestamp() as u32,
+                // Messages sent before the expiration was stored
+                None => message.created_at.timestamp() as u32 + DEFAULT_EXPIRATION_TIMEOUT,
+            };
 
             let rx = self
                 .ton_api_client
                 .add_pending_message(account, message_hash, expire_at)?;
 
             let ton_service = Arc::downgrade(self);
-            self.spawn_background_task("Wait message", wait_message(ton_service, transaction, rx));
+            self.spawn_background_task(
+                "Wait message",
+                wait_message(
+                    ton_service,
+                    message,
+                    rx,
+                    expire_at,
+                    rebroadcast_pending_messages,
+                ),
+            );
+        }
+
+        // Deliver queued notifications
+        let ton_service = Arc::downgrade(self);
+        self.spawn_background_task("Deliver notifications", deliver_notifications(ton_service));
+
+        // Feed the events streams with the committed events
+        let ton_service = Arc::downgrade(self);
+        self.spawn_background_task("Listen events stream", listen_event_stream(ton_service));
+
+        // Process queued and interrupted rescans
+        let ton_service = Arc::downgrade(self);
+        self.spawn_background_task("Rescan transactions", process_rescan_tasks(ton_service));
+
+        // Send the remaining chunks of payout batches
+        let ton_service = Arc::downgrade(self);
+        self.spawn_background_task("Process payouts", process_payout_batches(ton_service));
+
+        // Load known token metadata and keep it up to date
+        let whitelist = self.sqlx_client.get_token_whitelist().await?;
+        self.token_metadata
+            .write()
+            .extend(whitelist.iter().filter_map(|token| {
+                TokenMetadata::from_whitelist(token)
+                    .map(|metadata| (token.address.clone(), metadata))
+            }));
+
+        let ton_service = Arc::downgrade(self);
+        self.spawn_background_task(
+            "Refresh token metadata",
+            refresh_token_metadata(ton_service),
+        );
+
+        // Reject expired transfer approvals and settle the interrupted ones
+        let ton_service = Arc::downgrade(self);
+        self.spawn_background_task(
+            "Sweep transfer approvals",
+            sweep_transfer_approvals(ton_service),
+        );
+
+        // Forget expired idempotency keys
+        let ton_service = Arc::downgrade(self);
+        self.spawn_background_task(
+            "Clean up idempotency keys",
+            cleanup_idempotency_keys(ton_service),
+        );
+
+        // Forget events which can no longer be replayed
+        let ton_service = Arc::downgrade(self);
+        self.spawn_background_task("Clean up events stream", cleanup_event_stream(ton_service));
+
+        // Track pending transactions of multisig wallets
+        let ton_service = Arc::downgrade(self);
+        self.spawn_background_task(
+            "Sync multisig proposals",
+            sync_multisig_proposals(ton_service),
+        );
+
+        // Compare ledger balances with the network ones
+        if self.reconciliation_config.enabled {
+            let ton_service = Arc::downgrade(self);
+            self.spawn_background_task("Reconcile balances", reconcile_balances(ton_service));
         }
 
         Ok(())
@@ -75,11 +192,21 @@ impl TonService {
         input: CreateAddress,
     ) -> Result<AddressDb, Error> {
         let id = Uuid::new_v4();
-        let key = self.key.as_slice().try_into()?;
-        let address = self.ton_api_client.create_address(input).await?;
+        let signer_type = input.signer_type.unwrap_or_default();
+
+        let (public_key, private_key) = match signer_type {
+            SignerType::Local => {
+                let key_pair = LocalSigner::generate_key_pair()?;
+                let private_key = self.keyring.encrypt(key_pair.secret.as_bytes(), &id)?;
+                (key_pair.public, Some(private_key))
+            }
+            SignerType::Remote => (self.remote_signer()?.generate_key(&id).await?, None),
+        };
 
-        let public_key = hex::encode(&address.public_key);
-        let private_key = encrypt_private_key(&address.private_key, key, &id)?;
+        let address = self
+            .ton_api_client
+            .create_address(input, public_key)
+            .await?;
 
         let address = self
             .sqlx_client
@@ -87,14 +214,96 @@ impl TonService {
                 address,
                 id,
                 *service_id,
-                public_key,
+                private_key,
+                signer_type,
+            ))
+            .await?;
+
+        self.start_backfill(&address).await;
+
+        Ok(address)
+    }
+
+    pub async fn import_address(
+        &self,
+        service_id: &ServiceId,
+        input: ImportAddress,
+    ) -> Result<AddressDb, Error> {
+        let id = Uuid::new_v4();
+
+        let public_key = input
+            .public_key
+            .as_deref()
+            .map(|key| {
+                ed25519_dalek::PublicKey::from_bytes(&hex::decode(key)?).map_err(Error::from)
+            })
+            .transpose()?;
+
+        let (public_key, private_key) = match input.private_key.as_deref() {
+            Some(private_key) => {
+                let secret = ed25519_dalek::SecretKey::from_bytes(&hex::decode(private_key)?)?;
+                let public = ed25519_dalek::PublicKey::from(&secret);
+                if matches!(public_key, Some(public_key) if public_key != public) {
+                    return Err(TonServiceError::WrongInput(
+                        "Public key doesn't match private key".to_string(),
+                    )
+                    .into());
+                }
+
+                let private_key = self.keyring.encrypt(secret.as_bytes(), &id)?;
+                (Some(public), Some(private_key))
+            }
+            None => (public_key, None),
+        };
+
+        let address = self
+            .ton_api_client
+            .import_address(input, public_key)
+            .await?;
+
+        if self
+            .sqlx_client
+            .get_address_by_workchain_hex(address.workchain_id, address.hex.clone())
+            .await
+            .is_ok()
+        {
+            return Err(TonServiceError::WrongInput("Address already exists".to_string()).into());
+        }
+
+        let address = self
+            .sqlx_client
+            .create_address(CreateAddressInDb::imported(
+                address,
+                id,
+                *service_id,
                 private_key,
             ))
             .await?;
 
+        // Subscribe only after the address is stored, a duplicate must not be tracked twice
+        self.ton_api_client
+            .add_ton_account_subscription(MsgAddressInt::from_str(&format!(
+                "{}:{}",
+                address.workchain_id, address.hex
+            ))?);
+
+        self.start_backfill(&address).await;
+
         Ok(address)
     }
 
+    /// Loads the transactions which happened before the address was added
+    async fn start_backfill(&self, address: &AddressDb) {
+        if let Err(e) = self.create_rescan_tasks(address).await {
+            log::error!(
+                "Failed to start rescan of `{}:{}`: {:?}",
+                address.workchain_id,
+                address.hex,
+                e
+            );
+        }
+    }
+
     pub async fn check_address(&self, address: Address) -> Result<bool, Error> {
         Ok(MsgAddressInt::from_str(&address.0).is_ok()
             || (unpack_std_smc_addr(&address.0, false).is_ok())
@@ -120,6 +329,243 @@ impl TonService {
         Ok((address, network))
     }
 
+    /// The last known balance of the address at the time, the token balance if `root_address` is set
+    pub async fn get_balance_at(
+        &self,
+        service_id: &ServiceId,
+        address: Address,
+        input: BalanceAt,
+    ) -> Result<Option<BalanceSnapshotDb>, Error> {
+        let account = repack_address(&address.0)?;
+        let root_address = parse_root_address(input.root_address)?;
+
+        let snapshot = self
+            .sqlx_client
+            .get_balance_snapshot_at(
+                *service_id,
+                account.workchain_id(),
+                &account.address().to_hex_string(),
+                root_address.as_deref(),
+                input.timestamp,
+            )
+            .await?;
+
+        Ok(snapshot)
+    }
+
+    pub async fn get_balance_history(
+        &self,
+        service_id: &ServiceId,
+        address: Address,
+        input: BalanceHistorySearch,
+    ) -> Result<Vec<BalanceSnapshotDb>, Error> {
+        let account = repack_address(&address.0)?;
+        let root_address = parse_root_address(input.root_address)?;
+
+        let snapshots = self
+            .sqlx_client
+            .get_balance_history(
+                *service_id,
+                account.workchain_id(),
+                &account.address().to_hex_string(),
+                root_address.as_deref(),
+                input.from,
+                input.to,
+                input.limit,
+            )
+            .await?;
+
+        Ok(snapshots)
+    }
+
+    pub async fn create_balance_snapshot(&self, input: CreateBalanceSnapshot) -> Result<(), Error> {
+        self.sqlx_client.create_balance_snapshot(input).await?;
+        Ok(())
+    }
+
+    pub async fn get_balance_discrepancies(
+        &self,
+        service_id: &ServiceId,
+        input: BalanceDiscrepanciesSearch,
+    ) -> Result<Vec<BalanceDiscrepancyDb>, Error> {
+        let discrepancies = self
+            .sqlx_client
+            .get_balance_discrepancies(*service_id, input.status, input.limit, input.offset)
+            .await?;
+        Ok(discrepancies)
+    }
+
+    /// Compares the ledger balances of the address with the network ones and resolves
+    /// the consistent discrepancies. Mismatched balances are recorded only if `record` is set,
+    /// a rescan of the address is started for new discrepancies if `backfill` is enabled.
+    /// Returns whether all balances are consistent
+    async fn reconcile_address_balances(
+        &self,
+        address: &AddressDb,
+        record: bool,
+    ) -> Result<bool, Error> {
+        let checks = self.check_address_balances(address).await?;
+
+        let mut consistent = true;
+        let mut opened = Vec::new();
+        for check in checks {
+            if check.is_consistent() {
+                self.sqlx_client
+                    .resolve_balance_discrepancy(
+                        check.account_workchain_id,
+                        &check.account_hex,
+                        check.root_address.as_deref(),
+                    )
+                    .await?;
+                continue;
+            }
+
+            consistent = false;
+            if !record {
+                continue;
+            }
+
+            let (discrepancy, created) = self.sqlx_client.upsert_balance_discrepancy(check).await?;
+            if created {
+                log::warn!(
+                    "Balance discrepancy of `{}:{}` (root address: {:?}): ledger {}, network {}",
+                    discrepancy.account_workchain_id,
+                    discrepancy.account_hex,
+                    discrepancy.root_address,
+                    discrepancy.ledger_balance,
+                    discrepancy.network_balance
+                );
+                opened.push(discrepancy.id);
+            }
+        }
+
+        if !opened.is_empty() && self.reconciliation_config.backfill {
+            let account = Address(format!("{}:{}", address.workchain_id, address.hex));
+            match self.start_rescan(&address.service_id, account).await {
+                Ok(_) => {
+                    for id in opened {
+                        self.sqlx_client
+                            .set_discrepancy_rescan_requested(id)
+                            .await?;
+                    }
+                }
+                Err(e) => log::warn!(
+                    "Failed to start rescan of `{}:{}`: {:?}",
+                    address.workchain_id,
+                    address.hex,
+                    e
+                ),
+            }
+        }
+
+        Ok(consistent)
+    }
+
+    /// Reconciles balances of the addresses, `concurrency` of them at a time.
+    /// Returns the addresses with mismatched balances
+    async fn reconcile_addresses_balances(
+        &self,
+        addresses: Vec<AddressDb>,
+        record: bool,
+    ) -> Vec<AddressDb> {
+        futures::stream::iter(addresses)
+            .map(|address| async move {
+                match self.reconcile_address_balances(&address, record).await {
+                    Ok(true) => None,
+                    Ok(false) => Some(address),
+                    Err(e) => {
+                        log::error!(
+                            "Failed to reconcile balances of `{}:{}`: {:?}",
+                            address.workchain_id,
+                            address.hex,
+                            e
+                        );
+                        None
+                    }
+                }
+            })
+            .buffer_unordered(self.reconciliation_config.concurrency.max(1))
+            .filter_map(futures::future::ready)
+            .collect()
+            .await
+    }
+
+    /// Native balance and token balances of every root the owner has transactions or snapshots with.
+    /// Balances which failed to be read from the network are skipped
+    async fn check_address_balances(
+        &self,
+        address: &AddressDb,
+    ) -> Result<Vec<BalanceCheck>, Error> {
+        let account = repack_address(&format!("{}:{}", address.workchain_id, address.hex))?;
+
+        let mut checks = Vec::new();
+
+        let ledger_balance = self
+            .sqlx_client
+            .get_ledger_balance(address.workchain_id, &address.hex)
+            .await?;
+        match self.ton_api_client.get_address_info(&account).await {
+            Ok(network) => checks.push(BalanceCheck {
+                service_id: address.service_id,
+                account_workchain_id: address.workchain_id,
+                account_hex: address.hex.clone(),
+                root_address: None,
+                ledger_balance,
+                network_balance: network.network_balance,
+            }),
+            Err(e) => log::error!(
+                "Failed to get balance of `{}:{}`: {:?}",
+                address.workchain_id,
+                address.hex,
+                e
+            ),
+        }
+
+        let token_balances = self
+            .sqlx_client
+            .get_token_ledger_balances(address.workchain_id, &address.hex)
+            .await?;
+        for (root_address, ledger_balance) in token_balances {
+            let network = match repack_address(&root_address) {
+                Ok(root_account) => {
+                    self.ton_api_client
+                        .get_token_address_info(&account, &root_account)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+
+            match network {
+                Ok(network) => checks.push(BalanceCheck {
+                    service_id: address.service_id,
+                    account_workchain_id: address.workchain_id,
+                    account_hex: address.hex.clone(),
+                    root_address: Some(root_address),
+                    ledger_balance,
+                    network_balance: network.network_balance,
+                }),
+                Err(e) => log::error!(
+                    "Failed to get token balance of `{}:{}` (root address: {}): {:?}",
+                    address.workchain_id,
+                    address.hex,
+                    root_address,
+                    e
+                ),
+            }
+        }
+
+        Ok(checks)
+    }
+
+    async fn update_discrepancies_metrics(&self) -> Result<(), Error> {
+        let (ton, token) = self.sqlx_client.count_open_balance_discrepancies().await?;
+
+        gauge!("balance_discrepancies", ton as f64, "kind" => "ton");
+        gauge!("balance_discrepancies", token as f64, "kind" => "token");
+
+        Ok(())
+    }
+
     pub async fn get_address_info(
         &self,
         service_id: &ServiceId,
@@ -138,10 +584,24 @@ impl TonService {
         Ok(address)
     }
 
+    /// Sends the transfer, or stores it for approval by another key
+    /// if it exceeds the approval threshold of the spending rules
     pub async fn create_send_transaction(
+        self: &Arc<Self>,
+        service_id: &ServiceId,
+        key_id: &Uuid,
+        input: TransactionSend,
+    ) -> Result<TransactionDb, Error> {
+        self.process_send_transaction(service_id, input, Some(key_id))
+            .await
+    }
+
+    /// `requested_by` is `None` for the already approved transfers
+    async fn process_send_transaction(
         self: &Arc<Self>,
         service_id: &ServiceId,
         input: TransactionSend,
+        requested_by: Option<&Uuid>,
     ) -> Result<TransactionDb, Error> {
         let address = repack_address(&input.from_address.0)?;
         let network = self.ton_api_client.get_address_info(&address).await?;
@@ -166,6 +626,19 @@ impl TonService {
             return Err(TonServiceError::InsufficientBalance.into());
         }
 
+        let recipients = input
+            .outputs
+            .iter()
+            .map(|o| {
+                repack_address(&o.recipient_address.0).map_err(|_| {
+                    TonServiceError::WrongInput("Invalid recipient address".to_string())
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let spending = self
+            .check_spending_limits(service_id, &address, None, &balance, &recipients)
+            .await?;
+
         let address_db = self
             .sqlx_client
             .get_address(
@@ -175,31 +648,62 @@ impl TonService {
             )
             .await?;
 
-        let key = self.key.as_slice().try_into()?;
-
-        let public_key = hex::decode(address_db.public_key.clone())?;
-        let private_key = decrypt_private_key(&address_db.private_key, key, &address_db.id)?;
+        let signer = self.signer(&address_db)?;
+
+        if let Some(requested_by) = requested_by.filter(|_| spending.approval_required) {
+            let approval = CreateTransferApproval {
+                id: input.id,
+                service_id: *service_id,
+                notify_type: NotifyType::Transaction,
+                account_workchain_id: address.workchain_id(),
+                account_hex: address.address().to_hex_string(),
+                root_address: None,
+                value: balance,
+                input: serde_json::to_value(&input)?,
+                requested_by: *requested_by,
+            };
+            return self.request_transfer_approval(approval).await;
+        }
 
         if network.account_status == AccountStatus::UnInit {
-            self.deploy_wallet(service_id, &address_db, &public_key, &private_key)
+            self.deploy_wallet(service_id, &address_db, signer.as_ref())
                 .await?;
         }
 
+        if input.outputs.len() > max_outputs_per_message(&address_db.account_type) {
+            let transaction = self
+                .create_payout_batch(service_id, input, &address_db, signer.as_ref())
+                .await?;
+            // The whole batch is counted, even if its next chunks fail
+            self.record_spending(spending, None).await;
+            return Ok(transaction);
+        }
+
+        let resend = input
+            .retry_on_expire
+            .then(|| ResendTransfer::Ton(input.clone()));
+
         let (payload, signed_message) = self
             .ton_api_client
             .prepare_transaction(
                 input,
-                &public_key,
-                &private_key,
+                signer.as_ref(),
                 &address_db.account_type,
                 &address_db.custodians,
             )
             .await?;
 
-        let (transaction, event) = self
+        let (transaction, _) = self
             .sqlx_client
             .create_send_transaction(CreateSendTransaction::new(payload, *service_id))
             .await?;
+        self.record_spending(spending, Some(transaction.id)).await;
+
+        if let Some(resend) = resend {
+            self.sqlx_client
+                .update_transaction_resend_input(transaction.id, serde_json::to_value(resend)?)
+                .await?;
+        }
 
         self.send_transaction(
             transaction.message_hash.clone(),
@@ -211,12 +715,50 @@ impl TonService {
         )
         .await?;
 
-        self.notify(service_id, event.into(), NotifyType::Transaction)
-            .await?;
+        self.notify();
 
         Ok(transaction)
     }
 
+    /// Builds the same message as `create_send_transaction` and executes it locally
+    pub async fn estimate_transaction_fees(
+        &self,
+        service_id: &ServiceId,
+        input: TransactionSend,
+    ) -> Result<EstimatedFees, Error> {
+        let address = repack_address(&input.from_address.0)?;
+
+        for transaction_output in input.outputs.iter() {
+            let (_, scale) = transaction_output.value.as_bigint_and_exponent();
+            if scale != 0 {
+                return Err(TonServiceError::WrongInput("Invalid value".to_string()).into());
+            }
+        }
+
+        let address_db = self
+            .sqlx_client
+            .get_address(
+                *service_id,
+                address.workchain_id(),
+                address.address().to_hex_string(),
+            )
+            .await?;
+
+        let signer = self.estimation_signer(&address_db)?;
+
+        let (_, signed_message) = self
+            .ton_api_client
+            .prepare_transaction(
+                input,
+                &signer,
+                &address_db.account_type,
+                &address_db.custodians,
+            )
+            .await?;
+
+        self.ton_api_client.estimate_fees(&signed_message)
+    }
+
     pub async fn create_confirm_transaction(
         self: &Arc<Self>,
         service_id: &ServiceId,
@@ -237,27 +779,42 @@ impl TonService {
             return Err(TonServiceError::WrongInput("Invalid account type".to_string()).into());
         }
 
-        let key = self.key.as_slice().try_into()?;
-
-        let public_key = hex::decode(address_db.public_key.clone())?;
-        let private_key = decrypt_private_key(&address_db.private_key, key, &address_db.id)?;
+        let signer = self.signer(&address_db)?;
 
         let network = self.ton_api_client.get_address_info(&address).await?;
 
         if network.account_status == AccountStatus::UnInit {
-            self.deploy_wallet(service_id, &address_db, &public_key, &private_key)
+            self.deploy_wallet(service_id, &address_db, signer.as_ref())
                 .await?;
         }
 
+        // The confirmation can execute the transfer, so it is checked by the limits
+        let pending = self
+            .ton_api_client
+            .get_multisig_pending_transactions(&address)
+            .await?
+            .into_iter()
+            .find(|transaction| transaction.id == input.transaction_id)
+            .ok_or_else(|| {
+                TonServiceError::WrongInput("Pending multisig transaction not found".to_string())
+            })?;
+        let value =
+            BigDecimal::from_str(&pending.value.to_string()).map_err(anyhow::Error::from)?;
+        let spending = self
+            .check_spending_limits(service_id, &address, None, &value, &[pending.dest])
+            .await?
+            .without_approval()?;
+
         let (payload, signed_message) = self
             .ton_api_client
-            .prepare_confirm_transaction(input, &public_key, &private_key)
+            .prepare_confirm_transaction(input, signer.as_ref())
             .await?;
 
-        let (transaction, event) = self
+        let (transaction, _) = self
             .sqlx_client
             .create_send_transaction(CreateSendTransaction::new(payload, *service_id))
             .await?;
+        self.record_spending(spending, Some(transaction.id)).await;
 
         self.send_transaction(
             transaction.message_hash.clone(),
@@ -269,45 +826,240 @@ impl TonService {
         )
         .await?;
 
-        self.notify(service_id, event.into(), NotifyType::Transaction)
-            .await?;
+        self.notify();
 
         Ok(transaction)
     }
 
-    pub async fn create_receive_transaction(
-        self: &Arc<Self>,
-        input: CreateReceiveTransaction,
-    ) -> Result<TransactionDb, Error> {
-        let address = self
-            .sqlx_client
-            .get_address_by_workchain_hex(input.account_workchain_id, input.account_hex.clone())
-            .await?;
+    pub async fn get_multisig_proposals(
+        &self,
+        service_id: &ServiceId,
+        address: Option<Address>,
+        status: Option<MultisigProposalStatus>,
+    ) -> Result<Vec<MultisigProposalDb>, Error> {
+        let account = address
+            .map(|address| {
+                repack_address(&address.0)
+                    .map_err(|_| TonServiceError::WrongInput("Invalid address".to_string()))
+            })
+            .transpose()?;
+        let account_hex = account.as_ref().map(|a| a.address().to_hex_string());
 
-        let (transaction, event) = self
+        let proposals = self
             .sqlx_client
-            .create_receive_transaction(input, address.service_id)
+            .get_multisig_proposals(
+                *service_id,
+                account
+                    .as_ref()
+                    .map(|a| a.workchain_id())
+                    .zip(account_hex.as_deref()),
+                status,
+            )
             .await?;
+        Ok(proposals)
+    }
 
-        self.notify(&address.service_id, event.into(), NotifyType::Transaction)
+    pub async fn get_multisig_proposal(
+        &self,
+        service_id: &ServiceId,
+        id: &Uuid,
+    ) -> Result<MultisigProposalDb, Error> {
+        let proposal = self
+            .sqlx_client
+            .get_multisig_proposal(*service_id, *id)
             .await?;
-
-        Ok(transaction)
+        Ok(proposal)
     }
 
-    pub async fn upsert_sent_transaction(
+    /// Confirms the pending multisig transaction with the custodian key of the address
+    pub async fn confirm_multisig_proposal(
         self: &Arc<Self>,
-        message_hash: String,
-        account_workchain_id: i32,
-        account_hex: String,
-        input: UpdateSendTransaction,
+        service_id: &ServiceId,
+        id: &Uuid,
     ) -> Result<TransactionDb, Error> {
-        let address = self
+        let proposal = self
             .sqlx_client
-            .get_address_by_workchain_hex(account_workchain_id, account_hex.clone())
+            .get_multisig_proposal(*service_id, *id)
             .await?;
 
-        let (transaction, event) = self
+        if proposal.status != MultisigProposalStatus::Pending {
+            return Err(TonServiceError::WrongInput("Proposal is not pending".to_string()).into());
+        }
+
+        if proposal.expire_at <= Utc::now().naive_utc() {
+            return Err(TonServiceError::WrongInput("Proposal expired".to_string()).into());
+        }
+
+        let input = TransactionConfirm {
+            id: Uuid::new_v4(),
+            address: Address(format!(
+                "{}:{}",
+                proposal.account_workchain_id, proposal.account_hex
+            )),
+            transaction_id: proposal.transaction_id as u64,
+        };
+
+        self.create_confirm_transaction(service_id, input).await
+    }
+
+    /// Dismisses the pending multisig transaction. Multisig has no rejection,
+    /// the transaction stays in the contract until it expires
+    pub async fn reject_multisig_proposal(
+        &self,
+        service_id: &ServiceId,
+        id: &Uuid,
+    ) -> Result<MultisigProposalDb, Error> {
+        let proposal = self
+            .sqlx_client
+            .get_multisig_proposal(*service_id, *id)
+            .await?;
+
+        self.sqlx_client
+            .update_multisig_proposal_status(
+                proposal.id,
+                MultisigProposalStatus::Pending,
+                MultisigProposalStatus::Rejected,
+            )
+            .await?
+            .ok_or_else(|| {
+                TonServiceError::WrongInput("Proposal is not pending".to_string()).into()
+            })
+    }
+
+    /// Stores new pending transactions of the multisig wallet and notifies about them,
+    /// closes the ones which are no longer in the contract
+    async fn sync_account_multisig_proposals(
+        self: &Arc<Self>,
+        address: &AddressDb,
+    ) -> Result<(), Error> {
+        let account = repack_address(&format!("{}:{}", address.workchain_id, address.hex))?;
+        let pending = self
+            .ton_api_client
+            .get_multisig_pending_transactions(&account)
+            .await?;
+
+        let known = self
+            .sqlx_client
+            .get_open_multisig_proposals(address.workchain_id, &address.hex)
+            .await?;
+
+        for transaction in &pending {
+            let transaction_id = transaction.id as i64;
+            let signs_received = transaction.signs_received as i32;
+            let confirmations = transaction
+                .confirmations
+                .iter()
+                .map(|custodian| custodian.to_hex_string())
+                .collect::<Vec<_>>();
+
+            match known.iter().find(|p| p.transaction_id == transaction_id) {
+                Some(proposal) => {
+                    self.multisig_proposal_misses.lock().remove(&proposal.id);
+
+                    if proposal.signs_received != signs_received {
+                        self.sqlx_client
+                            .update_multisig_proposal_confirmations(
+                                proposal.id,
+                                signs_received,
+                                &confirmations,
+                            )
+                            .await?;
+                    }
+                }
+                None => {
+                    // Upper 32 bits of the id are the creation time
+                    let expire_at = NaiveDateTime::from_timestamp_opt(
+                        ((transaction.id >> 32) + SAFE_MULTISIG_TRANSACTION_LIFETIME) as i64,
+                        0,
+                    )
+                    .unwrap_or_default();
+
+                    self.sqlx_client
+                        .create_multisig_proposal(CreateMultisigProposal {
+                            id: Uuid::new_v4(),
+                            service_id: address.service_id,
+                            account_workchain_id: address.workchain_id,
+                            account_hex: address.hex.clone(),
+                            transaction_id,
+                            creator: transaction.creator.to_hex_string(),
+                            destination: transaction.dest.to_string(),
+                            value: BigDecimal::from_str(&transaction.value.to_string())
+                                .map_err(anyhow::Error::from)?,
+                            bounce: transaction.bounce,
+                            signs_required: transaction.signs_required as i32,
+                            signs_received,
+                            confirmations,
+                            expire_at,
+                        })
+                        .await?;
+
+                    self.notify();
+                }
+            }
+        }
+
+        let now = Utc::now().naive_utc();
+        for proposal in known
+            .iter()
+            .filter(|p| !pending.iter().any(|t| t.id as i64 == p.transaction_id))
+        {
+            let status = if proposal.expire_at <= now {
+                MultisigProposalStatus::Expired
+            } else {
+                // The proposal may be missed by a single read
+                let misses = {
+                    let mut misses = self.multisig_proposal_misses.lock();
+                    let misses = misses.entry(proposal.id).or_default();
+                    *misses += 1;
+                    *misses
+                };
+                if misses < MULTISIG_PROPOSAL_MISSING_READS {
+                    continue;
+                }
+                MultisigProposalStatus::Executed
+            };
+            self.multisig_proposal_misses.lock().remove(&proposal.id);
+
+            self.sqlx_client
+                .update_multisig_proposal_status(proposal.id, proposal.status, status)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn create_receive_transaction(
+        self: &Arc<Self>,
+        input: CreateReceiveTransaction,
+    ) -> Result<TransactionDb, Error> {
+        let address = self
+            .sqlx_client
+            .get_address_by_workchain_hex(input.account_workchain_id, input.account_hex.clone())
+            .await?;
+
+        let (transaction, _) = self
+            .sqlx_client
+            .create_receive_transaction(input, address.service_id)
+            .await?;
+
+        self.notify();
+
+        Ok(transaction)
+    }
+
+    pub async fn upsert_sent_transaction(
+        self: &Arc<Self>,
+        message_hash: String,
+        account_workchain_id: i32,
+        account_hex: String,
+        input: UpdateSendTransaction,
+    ) -> Result<TransactionDb, Error> {
+        let address = self
+            .sqlx_client
+            .get_address_by_workchain_hex(account_workchain_id, account_hex.clone())
+            .await?;
+
+        let (transaction, _) = self
             .sqlx_client
             .upsert_send_transaction(
                 address.service_id,
@@ -318,8 +1070,10 @@ impl TonService {
             )
             .await?;
 
-        self.notify(&address.service_id, event.into(), NotifyType::Transaction)
-            .await?;
+        self.notify();
+
+        // The next chunk of a payout batch may be waiting for this transaction
+        self.payouts_notify.notify_waiters();
 
         Ok(transaction)
     }
@@ -340,7 +1094,7 @@ impl TonService {
                 .await?;
 
             for in_message_hash in messages_hash {
-                if let Some(event) = self
+                if self
                     .sqlx_client
                     .update_token_transaction(
                         address.service_id,
@@ -348,14 +1102,9 @@ impl TonService {
                         Some(owner_message_hash.clone()),
                     )
                     .await?
+                    .is_some()
                 {
-                    let _ = self
-                        .notify(
-                            &address.service_id,
-                            event.into(),
-                            NotifyType::TokenTransaction,
-                        )
-                        .await;
+                    self.notify();
                 }
             }
         }
@@ -402,39 +1151,72 @@ impl TonService {
         Ok(transaction)
     }
 
+    pub async fn get_payout_batch(
+        &self,
+        service_id: &ServiceId,
+        id: &Uuid,
+    ) -> Result<Option<PayoutBatch>, Error> {
+        let batch = self.sqlx_client.get_payout_batch(*service_id, id).await?;
+
+        Ok(batch)
+    }
+
     pub async fn get_event_by_id(
         &self,
         service_id: &ServiceId,
         id: &Uuid,
-    ) -> Result<TransactionEventDb, Error> {
+    ) -> Result<AccountTransactionEvent, Error> {
         let event = self.sqlx_client.get_event_by_id(*service_id, id).await?;
-        Ok(event)
+        let deliveries = self
+            .sqlx_client
+            .get_webhook_deliveries_by_events(*service_id, &[event.id])
+            .await?;
+
+        Ok(AccountTransactionEvent::from(event).with_deliveries(deliveries))
     }
 
     pub async fn search_transaction(
         &self,
         service_id: &ServiceId,
         payload: &TransactionsSearch,
-    ) -> Result<Vec<TransactionDb>, Error> {
-        let transaction = self
+    ) -> Result<(Vec<TransactionDb>, Option<SearchCursor>), Error> {
+        let transactions = self
             .sqlx_client
             .get_all_transactions(*service_id, payload)
             .await?;
 
-        Ok(transaction)
+        let next_cursor = match payload.ordering {
+            None
+            | Some(TransactionsSearchOrdering::CreatedAtAsc)
+            | Some(TransactionsSearchOrdering::CreatedAtDesc) => {
+                SearchCursor::next(&transactions, payload.limit, |t| {
+                    SearchCursor::new(t.created_at, t.id)
+                })
+            }
+            _ => None,
+        };
+
+        Ok((transactions, next_cursor))
     }
 
     pub async fn search_events(
         &self,
         service_id: &ServiceId,
         payload: &TransactionsEventsSearch,
-    ) -> Result<Vec<TransactionEventDb>, Error> {
+    ) -> Result<(Vec<AccountTransactionEvent>, Option<SearchCursor>), Error> {
         let events = self
             .sqlx_client
             .get_all_transaction_events(*service_id, payload)
             .await?;
 
-        Ok(events)
+        let next_cursor = SearchCursor::next(&events, payload.limit, |e| {
+            SearchCursor::new(e.created_at, e.id)
+        });
+        let events = self
+            .with_deliveries(service_id, events.into_iter().map(From::from).collect())
+            .await?;
+
+        Ok((events, next_cursor))
     }
 
     pub async fn mark_event(
@@ -480,6 +1262,8 @@ impl TonService {
             .sqlx_client
             .get_token_transaction_by_mh(*service_id, message_hash)
             .await?;
+        self.check_root_token_allowed(service_id, &Address(transaction.root_address.clone()))
+            .await?;
 
         Ok(transaction)
     }
@@ -493,6 +1277,8 @@ impl TonService {
             .sqlx_client
             .get_token_transaction_by_id(*service_id, internal_id)
             .await?;
+        self.check_root_token_allowed(service_id, &Address(transaction.root_address.clone()))
+            .await?;
 
         Ok(transaction)
     }
@@ -501,13 +1287,20 @@ impl TonService {
         &self,
         service_id: &ServiceId,
         payload: &TokenTransactionsEventsSearch,
-    ) -> Result<Vec<TokenTransactionEventDb>, Error> {
+    ) -> Result<(Vec<AccountTransactionEvent>, Option<SearchCursor>), Error> {
         let events = self
             .sqlx_client
             .get_all_token_transaction_events(*service_id, payload)
             .await?;
 
-        Ok(events)
+        let next_cursor = SearchCursor::next(&events, payload.limit, |e| {
+            SearchCursor::new(e.created_at, e.id)
+        });
+        let events = self
+            .with_deliveries(service_id, events.into_iter().map(From::from).collect())
+            .await?;
+
+        Ok((events, next_cursor))
     }
 
     pub async fn mark_token_event(
@@ -557,11 +1350,28 @@ impl TonService {
         Ok(result)
     }
 
+    /// Sends the token transfer, or stores it for approval by another key
+    /// if it exceeds the approval threshold of the spending rules
     pub async fn create_send_token_transaction(
+        self: &Arc<Self>,
+        service_id: &ServiceId,
+        key_id: &Uuid,
+        input: &TokenTransactionSend,
+    ) -> Result<TransactionDb, Error> {
+        self.process_send_token_transaction(service_id, input, Some(key_id))
+            .await
+    }
+
+    /// `requested_by` is `None` for the already approved transfers
+    async fn process_send_token_transaction(
         self: &Arc<Self>,
         service_id: &ServiceId,
         input: &TokenTransactionSend,
+        requested_by: Option<&Uuid>,
     ) -> Result<TransactionDb, Error> {
+        self.check_root_token_allowed(service_id, &input.root_address)
+            .await?;
+
         let (_, scale) = input.value.as_bigint_and_exponent();
         if scale != 0 {
             return Err(TonServiceError::WrongInput("Invalid value".to_string()).into());
@@ -605,15 +1415,40 @@ impl TonService {
             return Err(TonServiceError::InsufficientBalance.into());
         }
 
-        let key = self.key.as_slice().try_into()?;
+        let root_address = repack_address(&input.root_address.0)?.to_string();
+        let recipient = repack_address(&input.recipient_address.0)
+            .map_err(|_| TonServiceError::WrongInput("Invalid recipient address".to_string()))?;
+        let spending = self
+            .check_spending_limits(
+                service_id,
+                &owner,
+                Some(&root_address),
+                &input.value,
+                &[recipient],
+            )
+            .await?;
 
-        let public_key = hex::decode(address_db.public_key.clone())?;
-        let private_key = decrypt_private_key(&address_db.private_key, key, &address_db.id)?;
+        let signer = self.signer(&address_db)?;
+
+        if let Some(requested_by) = requested_by.filter(|_| spending.approval_required) {
+            let approval = CreateTransferApproval {
+                id: input.id,
+                service_id: *service_id,
+                notify_type: NotifyType::TokenTransaction,
+                account_workchain_id: owner.workchain_id(),
+                account_hex: owner.address().to_hex_string(),
+                root_address: Some(root_address),
+                value: input.value.clone(),
+                input: serde_json::to_value(input)?,
+                requested_by: *requested_by,
+            };
+            return self.request_transfer_approval(approval).await;
+        }
 
         let owner_network = self.ton_api_client.get_address_info(&owner).await?;
 
         if owner_network.account_status == AccountStatus::UnInit {
-            self.deploy_wallet(service_id, &address_db, &public_key, &private_key)
+            self.deploy_wallet(service_id, &address_db, signer.as_ref())
                 .await?;
         }
 
@@ -621,17 +1456,24 @@ impl TonService {
             .ton_api_client
             .prepare_token_transaction(
                 input,
-                &public_key,
-                &private_key,
+                signer.as_ref(),
                 &address_db.account_type,
                 &address_db.custodians,
             )
             .await?;
 
-        let (transaction, event) = self
+        let (transaction, _) = self
             .sqlx_client
             .create_send_transaction(CreateSendTransaction::new(payload, *service_id))
             .await?;
+        self.record_spending(spending, Some(transaction.id)).await;
+
+        if input.retry_on_expire {
+            let resend = ResendTransfer::Token(input.clone());
+            self.sqlx_client
+                .update_transaction_resend_input(transaction.id, serde_json::to_value(resend)?)
+                .await?;
+        }
 
         self.send_transaction(
             transaction.message_hash.clone(),
@@ -643,17 +1485,58 @@ impl TonService {
         )
         .await?;
 
-        self.notify(service_id, event.into(), NotifyType::Transaction)
-            .await?;
+        self.notify();
 
         Ok(transaction)
     }
 
+    /// Builds the same message as `create_send_token_transaction` and executes it locally
+    pub async fn estimate_token_transaction_fees(
+        &self,
+        service_id: &ServiceId,
+        input: &TokenTransactionSend,
+    ) -> Result<EstimatedFees, Error> {
+        self.check_root_token_allowed(service_id, &input.root_address)
+            .await?;
+
+        let (_, scale) = input.value.as_bigint_and_exponent();
+        if scale != 0 {
+            return Err(TonServiceError::WrongInput("Invalid value".to_string()).into());
+        }
+
+        let owner = repack_address(&input.from_address.0)?;
+        let address_db = self
+            .sqlx_client
+            .get_address(
+                *service_id,
+                owner.workchain_id(),
+                owner.address().to_hex_string(),
+            )
+            .await?;
+
+        let signer = self.estimation_signer(&address_db)?;
+
+        let (_, signed_message) = self
+            .ton_api_client
+            .prepare_token_transaction(
+                input,
+                &signer,
+                &address_db.account_type,
+                &address_db.custodians,
+            )
+            .await?;
+
+        self.ton_api_client.estimate_fees(&signed_message)
+    }
+
     pub async fn create_burn_token_transaction(
         self: &Arc<Self>,
         service_id: &ServiceId,
         input: &TokenTransactionBurn,
     ) -> Result<TransactionDb, Error> {
+        self.check_root_token_allowed(service_id, &input.root_address)
+            .await?;
+
         let (_, scale) = input.value.as_bigint_and_exponent();
         if scale != 0 {
             return Err(TonServiceError::WrongInput("Invalid value".to_string()).into());
@@ -697,15 +1580,18 @@ impl TonService {
             return Err(TonServiceError::InsufficientBalance.into());
         }
 
-        let key = self.key.as_slice().try_into()?;
+        let root_address = repack_address(&input.root_address.0)?.to_string();
+        let spending = self
+            .check_spending_limits(service_id, &owner, Some(&root_address), &input.value, &[])
+            .await?
+            .without_approval()?;
 
-        let public_key = hex::decode(address_db.public_key.clone())?;
-        let private_key = decrypt_private_key(&address_db.private_key, key, &address_db.id)?;
+        let signer = self.signer(&address_db)?;
 
         let owner_network = self.ton_api_client.get_address_info(&owner).await?;
 
         if owner_network.account_status == AccountStatus::UnInit {
-            self.deploy_wallet(service_id, &address_db, &public_key, &private_key)
+            self.deploy_wallet(service_id, &address_db, signer.as_ref())
                 .await?;
         }
 
@@ -713,17 +1599,17 @@ impl TonService {
             .ton_api_client
             .prepare_token_burn(
                 input,
-                &public_key,
-                &private_key,
+                signer.as_ref(),
                 &address_db.account_type,
                 &address_db.custodians,
             )
             .await?;
 
-        let (transaction, event) = self
+        let (transaction, _) = self
             .sqlx_client
             .create_send_transaction(CreateSendTransaction::new(payload, *service_id))
             .await?;
+        self.record_spending(spending, Some(transaction.id)).await;
 
         self.send_transaction(
             transaction.message_hash.clone(),
@@ -735,8 +1621,7 @@ impl TonService {
         )
         .await?;
 
-        self.notify(service_id, event.into(), NotifyType::Transaction)
-            .await?;
+        self.notify();
 
         Ok(transaction)
     }
@@ -746,6 +1631,9 @@ impl TonService {
         service_id: &ServiceId,
         input: &TokenTransactionMint,
     ) -> Result<TransactionDb, Error> {
+        self.check_root_token_allowed(service_id, &input.root_address)
+            .await?;
+
         let (_, scale) = input.value.as_bigint_and_exponent();
         if scale != 0 {
             return Err(TonServiceError::WrongInput("Invalid value".to_string()).into());
@@ -775,23 +1663,19 @@ impl TonService {
             return Err(TonServiceError::InsufficientBalance.into());
         }
 
-        let key = self.key.as_slice().try_into()?;
-
-        let public_key = hex::decode(address_db.public_key.clone())?;
-        let private_key = decrypt_private_key(&address_db.private_key, key, &address_db.id)?;
+        let signer = self.signer(&address_db)?;
 
         let (payload, signed_message) = self
             .ton_api_client
             .prepare_token_mint(
                 input,
-                &public_key,
-                &private_key,
+                signer.as_ref(),
                 &address_db.account_type,
                 &address_db.custodians,
             )
             .await?;
 
-        let (transaction, event) = self
+        let (transaction, _) = self
             .sqlx_client
             .create_send_transaction(CreateSendTransaction::new(payload, *service_id))
             .await?;
@@ -806,8 +1690,7 @@ impl TonService {
         )
         .await?;
 
-        self.notify(service_id, event.into(), NotifyType::Transaction)
-            .await?;
+        self.notify();
 
         Ok(transaction)
     }
@@ -821,23 +1704,105 @@ impl TonService {
             .get_address_by_workchain_hex(input.account_workchain_id, input.account_hex.clone())
             .await?;
 
-        let (transaction, event) = self
+        let (transaction, _) = self
             .sqlx_client
             .create_token_transaction(input, address.service_id)
             .await?;
 
-        if transaction.direction == TonTransactionDirection::Receive
-            || transaction.owner_message_hash.is_some()
-        {
-            self.notify(
-                &address.service_id,
-                event.into(),
-                NotifyType::TokenTransaction,
+        self.notify();
+
+        Ok(transaction)
+    }
+
+    pub async fn start_rescan(
+        &self,
+        service_id: &ServiceId,
+        address: Address,
+    ) -> Result<Vec<TaskDb>, Error> {
+        let account = repack_address(&address.0)?;
+        let address = self
+            .sqlx_client
+            .get_address(
+                *service_id,
+                account.workchain_id(),
+                account.address().to_hex_string(),
+            )
+            .await?;
+
+        self.create_rescan_tasks(&address).await
+    }
+
+    /// Queues the rescan of the address and of its known token wallets
+    async fn create_rescan_tasks(&self, address: &AddressDb) -> Result<Vec<TaskDb>, Error> {
+        let tasks = self
+            .sqlx_client
+            .get_account_tasks(
+                address.service_id,
+                TaskKind::Rescan,
+                address.workchain_id,
+                address.hex.clone(),
             )
             .await?;
+        if tasks
+            .iter()
+            .any(|task| matches!(task.status, TaskStatus::Pending | TaskStatus::InProgress))
+        {
+            return Err(
+                TonServiceError::WrongInput("Rescan is already in progress".to_string()).into(),
+            );
         }
 
-        Ok(transaction)
+        // Token wallets are rescanned separately from the address itself
+        let token_wallets = self
+            .sqlx_client
+            .get_token_owners_by_owner(address.workchain_id, address.hex.clone())
+            .await?
+            .into_iter()
+            .map(|owner| Some(owner.address));
+
+        let mut tasks = Vec::new();
+        for token_wallet in std::iter::once(None).chain(token_wallets) {
+            let data = RescanTaskData {
+                token_wallet,
+                ..Default::default()
+            };
+
+            let task = self
+                .sqlx_client
+                .create_task(CreateTask {
+                    id: Uuid::new_v4(),
+                    service_id: address.service_id,
+                    account_workchain_id: address.workchain_id,
+                    account_hex: address.hex.clone(),
+                    kind: TaskKind::Rescan,
+                    data: serde_json::to_value(data)?,
+                })
+                .await?;
+            tasks.push(task);
+        }
+
+        self.rescan_notify.notify_one();
+
+        Ok(tasks)
+    }
+
+    pub async fn get_rescan_tasks(
+        &self,
+        service_id: &ServiceId,
+        address: Address,
+    ) -> Result<Vec<TaskDb>, Error> {
+        let account = repack_address(&address.0)?;
+        let tasks = self
+            .sqlx_client
+            .get_account_tasks(
+                *service_id,
+                TaskKind::Rescan,
+                account.workchain_id(),
+                account.address().to_hex_string(),
+            )
+            .await?;
+
+        Ok(tasks)
     }
 
     pub async fn get_metrics(&self) -> Result<Metrics, Error> {
@@ -859,7 +1824,15 @@ impl TonService {
         headers: Vec<Param>,
         responsible: bool,
     ) -> Result<Value, Error> {
-        let account_addr = UInt256::from_str(account_addr)?;
+        // Accounts of the base workchain can be specified by the hex of the address only
+        let account_addr = match MsgAddressInt::from_str(account_addr) {
+            Ok(address) => address,
+            Err(_) => MsgAddressInt::with_standart(
+                None,
+                ton_block::BASE_WORKCHAIN_ID as i8,
+                UInt256::from_str(account_addr)?.into(),
+            )?,
+        };
 
         let input_params: Vec<Param> = inputs.iter().map(|x| x.param.clone()).collect();
 
@@ -873,7 +1846,7 @@ impl TonService {
         let input = parse_abi_tokens(inputs)?;
         let output = match self
             .ton_api_client
-            .run_local(account_addr, function, input.as_slice(), responsible)
+            .run_local(&account_addr, function, input.as_slice(), responsible)
             .await?
         {
             Some(output) => output,
@@ -941,17 +1914,20 @@ impl TonService {
             )
             .await?;
 
-        let key = self.key.as_slice().try_into()?;
+        let recipient = repack_address(target_addr)
+            .map_err(|_| TonServiceError::WrongInput("Invalid target address".to_string()))?;
+        let spending = self
+            .check_spending_limits(service_id, &sender, None, &value, &[recipient])
+            .await?
+            .without_approval()?;
 
-        let public_key = hex::decode(address_db.public_key.clone())?;
-        let private_key = decrypt_private_key(&address_db.private_key, key, &address_db.id)?;
+        let signer = self.signer(&address_db)?;
 
         let signed_message = self
             .ton_api_client
             .prepare_signed_generic_message(
                 sender_addr,
-                &public_key,
-                &private_key,
+                signer.as_ref(),
                 target_addr,
                 execution_flag,
                 value.clone(),
@@ -974,10 +1950,11 @@ impl TonService {
             bounce,
         };
 
-        let (transaction, event) = self
+        let (transaction, _) = self
             .sqlx_client
             .create_send_transaction(CreateSendTransaction::new(sent_transaction, *service_id))
             .await?;
+        self.record_spending(spending, Some(transaction.id)).await;
 
         self.send_transaction(
             transaction.message_hash.clone(),
@@ -989,8 +1966,7 @@ impl TonService {
         )
         .await?;
 
-        self.notify(service_id, event.into(), NotifyType::Transaction)
-            .await?;
+        self.notify();
 
         Ok(transaction)
     }
@@ -1072,13 +2048,24 @@ impl TonService {
 
     pub async fn send_signed_message(
         self: &Arc<Self>,
+        service_id: &ServiceId,
         sender_addr: String,
         hash: String,
         msg: SignedMessage,
+        transfer: &PreparedTransfer,
     ) -> Result<String, Error> {
         let addr = MsgAddressInt::from_str(&sender_addr)?;
+
+        let recipient = repack_address(&transfer.recipient)
+            .map_err(|_| TonServiceError::WrongInput("Invalid target address".to_string()))?;
+        let spending = self
+            .check_spending_limits(service_id, &addr, None, &transfer.value, &[recipient])
+            .await?
+            .without_approval()?;
+        self.record_spending(spending, None).await;
+
         self.ton_api_client
-            .add_ton_account_subscription(addr.hash()?);
+            .add_ton_account_subscription(addr.clone());
 
         self.send_transaction(
             hash,
@@ -1095,221 +2082,2223 @@ impl TonService {
         Ok(hash)
     }
 
+    pub async fn get_callbacks(
+        &self,
+        service_id: &ServiceId,
+    ) -> Result<Vec<ApiServiceCallbackDb>, Error> {
+        let callbacks = self.sqlx_client.get_callbacks(*service_id).await?;
+        Ok(callbacks)
+    }
+
+    pub async fn get_callback(
+        &self,
+        service_id: &ServiceId,
+        id: &Uuid,
+    ) -> Result<ApiServiceCallbackDb, Error> {
+        let callback = self
+            .sqlx_client
+            .get_callback_by_id(*service_id, *id)
+            .await?;
+        Ok(callback)
+    }
+
     pub async fn set_callback(
         &self,
         service_id: &ServiceId,
-        callback: String,
-    ) -> Result<String, Error> {
-        let id = Uuid::new_v4();
+        input: CreateCallback,
+    ) -> Result<ApiServiceCallbackDb, Error> {
+        let (callback, filter) = parse_callback(input)?;
 
-        self.sqlx_client
-            .set_callback(ApiServiceCallbackDb::new(id, *service_id, callback.clone()))
+        let callback = self
+            .sqlx_client
+            .set_callback(ApiServiceCallbackDb::new(
+                Uuid::new_v4(),
+                *service_id,
+                callback,
+                filter,
+            ))
             .await?;
 
         Ok(callback)
     }
 
-    async fn notify(
-        self: &Arc<Self>,
+    pub async fn update_callback(
+        &self,
         service_id: &ServiceId,
-        payload: AccountTransactionEvent,
-        notify_type: NotifyType,
-    ) -> Result<(), Error> {
-        let ton_service = Arc::downgrade(self);
-        self.spawn_background_task(
-            "Send notification",
-            send_notification(ton_service, *service_id, notify_type, payload),
-        );
+        id: &Uuid,
+        input: CreateCallback,
+    ) -> Result<ApiServiceCallbackDb, Error> {
+        let (callback, filter) = parse_callback(input)?;
 
-        Ok(())
+        let callback = self
+            .sqlx_client
+            .update_callback(ApiServiceCallbackDb::new(
+                *id,
+                *service_id,
+                callback,
+                filter,
+            ))
+            .await?;
+
+        Ok(callback)
     }
 
-    async fn send_transaction(
-        self: &Arc<Self>,
-        message_hash: String,
-        account_hex: String,
-        account_workchain_id: i32,
-        signed_message: SignedMessage,
-        non_blocking: bool,
-        with_db_update: bool,
+    pub async fn delete_callback(
+        &self,
+        service_id: &ServiceId,
+        id: &Uuid,
+    ) -> Result<ApiServiceCallbackDb, Error> {
+        let callback = self.sqlx_client.delete_callback(*service_id, *id).await?;
+        Ok(callback)
+    }
+
+    pub async fn get_spending_limits(
+        &self,
+        service_id: &ServiceId,
+    ) -> Result<Vec<SpendingLimitDb>, Error> {
+        let limits = self.sqlx_client.get_spending_limits(*service_id).await?;
+        Ok(limits)
+    }
+
+    pub async fn create_spending_limit(
+        &self,
+        service_id: &ServiceId,
+        input: CreateSpendingLimit,
+    ) -> Result<SpendingLimitDb, Error> {
+        let limit = self
+            .sqlx_client
+            .create_spending_limit(parse_spending_limit(Uuid::new_v4(), service_id, input)?)
+            .await?;
+        Ok(limit)
+    }
+
+    pub async fn update_spending_limit(
+        &self,
+        service_id: &ServiceId,
+        id: &Uuid,
+        input: CreateSpendingLimit,
+    ) -> Result<SpendingLimitDb, Error> {
+        let limit = self
+            .sqlx_client
+            .update_spending_limit(parse_spending_limit(*id, service_id, input)?)
+            .await?;
+        Ok(limit)
+    }
+
+    pub async fn delete_spending_limit(
+        &self,
+        service_id: &ServiceId,
+        id: &Uuid,
+    ) -> Result<SpendingLimitDb, Error> {
+        let limit = self
+            .sqlx_client
+            .delete_spending_limit(*service_id, *id)
+            .await?;
+        Ok(limit)
+    }
+
+    pub async fn get_transfer_approvals(
+        &self,
+        service_id: &ServiceId,
+        status: Option<TonTransactionStatus>,
+    ) -> Result<Vec<TransferApprovalDb>, Error> {
+        let approvals = self
+            .sqlx_client
+            .get_transfer_approvals(*service_id, status)
+            .await?;
+        Ok(approvals)
+    }
+
+    pub async fn get_transfer_approval(
+        &self,
+        service_id: &ServiceId,
+        id: &Uuid,
+    ) -> Result<TransferApprovalDb, Error> {
+        let approval = self
+            .sqlx_client
+            .get_transfer_approval(*service_id, *id)
+            .await?;
+        Ok(approval)
+    }
+
+    /// Signs and sends the pending transfer. The transfer is checked again
+    /// against the balance and the spending rules
+    pub async fn approve_transfer(
+        self: &Arc<Self>,
+        service_id: &ServiceId,
+        key_id: &Uuid,
+        id: &Uuid,
+    ) -> Result<TransactionDb, Error> {
+        let approval = self
+            .sqlx_client
+            .get_transfer_approval(*service_id, *id)
+            .await?;
+
+        if approval.status != TonTransactionStatus::PendingApproval {
+            return Err(TonServiceError::WrongInput(
+                "Transfer is not pending approval".to_string(),
+            )
+            .into());
+        }
+
+        if approval.requested_by == *key_id {
+            return Err(TonServiceError::SelfApproval.into());
+        }
+
+        // Expired transfers are rejected by the sweep
+        let expired_before =
+            Utc::now().naive_utc() - chrono::Duration::seconds(TRANSFER_APPROVAL_TTL);
+        if approval.created_at < expired_before {
+            return Err(TonServiceError::ApprovalExpired.into());
+        }
+
+        // The id could be taken by another transfer since the request
+        if self.sqlx_client.find_transaction_by_id(id).await?.is_some() {
+            self.reject_transfer_approval(
+                service_id,
+                id,
+                TonTransactionStatus::PendingApproval,
+                None,
+                Some("Transaction id is already used".to_string()),
+            )
+            .await?;
+            return Err(TonServiceError::WrongInput(format!(
+                "Transaction `{}` already exists",
+                id
+            ))
+            .into());
+        }
+
+        let approval = self
+            .sqlx_client
+            .update_transfer_approval_status(
+                *service_id,
+                *id,
+                TonTransactionStatus::PendingApproval,
+                TonTransactionStatus::New,
+                Some(*key_id),
+                None,
+            )
+            .await?
+            .ok_or_else(|| {
+                TonServiceError::WrongInput("Transfer is not pending approval".to_string())
+            })?;
+
+        let result = match approval.notify_type {
+            NotifyType::Transaction => {
+                let input = serde_json::from_value(approval.input)?;
+                self.process_send_transaction(service_id, input, None).await
+            }
+            NotifyType::TokenTransaction => {
+                let input = serde_json::from_value(approval.input)?;
+                self.process_send_token_transaction(service_id, &input, None)
+                    .await
+            }
+        };
+
+        // The sweep settles it later otherwise
+        let error = result.as_ref().err().map(|e| e.to_string());
+        if let Err(e) = self.settle_transfer_approval(service_id, id, error).await {
+            log::error!("Failed to settle transfer approval `{}`: {:?}", id, e);
+        }
+
+        result
+    }
+
+    /// Rejects the expired transfers and settles the approved ones whose sending was interrupted
+    async fn sweep_transfer_approvals(self: &Arc<Self>) -> Result<(), Error> {
+        let now = Utc::now().naive_utc();
+
+        let expired = self
+            .sqlx_client
+            .expire_transfer_approvals(now - chrono::Duration::seconds(TRANSFER_APPROVAL_TTL))
+            .await?;
+        if !expired.is_empty() {
+            log::info!("Rejected {} expired transfer approvals", expired.len());
+            self.notify();
+        }
+
+        let stuck = self
+            .sqlx_client
+            .get_stuck_transfer_approvals(
+                now - chrono::Duration::seconds(TRANSFER_APPROVAL_SEND_TIMEOUT),
+            )
+            .await?;
+        for approval in stuck {
+            log::warn!("Transfer approval `{}` was interrupted", approval.id);
+            self.settle_transfer_approval(
+                &approval.service_id,
+                &approval.id,
+                Some("Interrupted".to_string()),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// The approved transfer is `Done` once its transaction is stored, failures after that
+    /// are tracked by the transaction. Otherwise nothing was sent and it's `Rejected`
+    async fn settle_transfer_approval(
+        self: &Arc<Self>,
+        service_id: &ServiceId,
+        id: &Uuid,
+        error: Option<String>,
     ) -> Result<(), Error> {
+        if self.sqlx_client.find_transaction_by_id(id).await?.is_some() {
+            self.sqlx_client
+                .update_transfer_approval_status(
+                    *service_id,
+                    *id,
+                    TonTransactionStatus::New,
+                    TonTransactionStatus::Done,
+                    None,
+                    None,
+                )
+                .await?;
+        } else {
+            self.reject_transfer_approval(service_id, id, TonTransactionStatus::New, None, error)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn reject_transfer(
+        self: &Arc<Self>,
+        service_id: &ServiceId,
+        key_id: &Uuid,
+        id: &Uuid,
+        reason: Option<String>,
+    ) -> Result<TransferApprovalDb, Error> {
+        self.reject_transfer_approval(
+            service_id,
+            id,
+            TonTransactionStatus::PendingApproval,
+            Some(*key_id),
+            reason,
+        )
+        .await?
+        .ok_or_else(|| {
+            TonServiceError::WrongInput("Transfer is not pending approval".to_string()).into()
+        })
+    }
+
+    /// Reserves the idempotency key for the request.
+    /// Returns the previous request with the same key if it's still in use
+    pub async fn lock_idempotency_key(
+        &self,
+        service_id: &ServiceId,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<Option<IdempotencyKeyDb>, Error> {
+        let now = Utc::now().naive_utc();
+        let expired_before = now - chrono::Duration::seconds(IDEMPOTENCY_KEY_TTL);
+
+        if self
+            .sqlx_client
+            .insert_idempotency_key(*service_id, key, request_hash, expired_before)
+            .await?
+        {
+            return Ok(None);
+        }
+
+        let previous = self
+            .sqlx_client
+            .get_idempotency_key(*service_id, key)
+            .await?;
+        Ok(Some(previous))
+    }
+
+    pub async fn save_idempotent_response(
+        &self,
+        service_id: &ServiceId,
+        key: &str,
+        status: StatusCode,
+        body: &[u8],
+    ) -> Result<(), Error> {
+        self.sqlx_client
+            .update_idempotency_key_response(*service_id, key, status.as_u16() as i32, body)
+            .await?;
+        Ok(())
+    }
+
+    /// Streams events of the service, replaying them from the database after the `cursor` first
+    pub fn stream_events(
+        self: &Arc<Self>,
+        service_id: &ServiceId,
+        cursor: Option<EventCursor>,
+    ) -> futures::channel::mpsc::Receiver<(EventCursor, NotifyType, AccountTransactionEvent)> {
+        // Subscribe before the replay so that no event is lost in between
+        let events_rx = self.events_tx.subscribe();
+        let (tx, rx) = futures::channel::mpsc::channel(EVENTS_STREAM_CAPACITY);
+
         let ton_service = Arc::downgrade(self);
+        self.spawn_background_task(
+            "Stream events",
+            stream_events(ton_service, *service_id, cursor, events_rx, tx),
+        );
+
+        rx
+    }
+
+    async fn with_deliveries(
+        &self,
+        service_id: &ServiceId,
+        events: Vec<AccountTransactionEvent>,
+    ) -> Result<Vec<AccountTransactionEvent>, Error> {
+        let ids: Vec<Uuid> = events.iter().map(|event| event.id).collect();
+
+        let mut deliveries: HashMap<Uuid, Vec<WebhookDeliveryDb>> = HashMap::new();
+        for delivery in self
+            .sqlx_client
+            .get_webhook_deliveries_by_events(*service_id, &ids)
+            .await?
+        {
+            deliveries
+                .entry(delivery.event_id)
+                .or_default()
+                .push(delivery);
+        }
+
+        Ok(events
+            .into_iter()
+            .map(|event| {
+                let deliveries = deliveries.remove(&event.id).unwrap_or_default();
+                event.with_deliveries(deliveries)
+            })
+            .collect())
+    }
+
+    async fn request_transfer_approval(
+        self: &Arc<Self>,
+        payload: CreateTransferApproval,
+    ) -> Result<TransactionDb, Error> {
+        let id = payload.id;
+        let approval = self
+            .sqlx_client
+            .create_transfer_approval(payload)
+            .await?
+            .ok_or_else(|| {
+                TonServiceError::WrongInput(format!("Transaction `{}` already exists", id))
+            })?;
+
+        self.notify();
+
+        Ok(approval.pending_transaction())
+    }
+
+    /// Rejects the transfer in the `from` state, returns `None` if it was already decided
+    async fn reject_transfer_approval(
+        self: &Arc<Self>,
+        service_id: &ServiceId,
+        id: &Uuid,
+        from: TonTransactionStatus,
+        decided_by: Option<Uuid>,
+        error: Option<String>,
+    ) -> Result<Option<TransferApprovalDb>, Error> {
+        let approval = self
+            .sqlx_client
+            .update_transfer_approval_status(
+                *service_id,
+                *id,
+                from,
+                TonTransactionStatus::Rejected,
+                decided_by,
+                error,
+            )
+            .await?;
+
+        if approval.is_some() {
+            self.notify();
+        }
+
+        Ok(approval)
+    }
+
+    /// Wakes up the webhook worker. Deliveries and the events stream are written
+    /// by the transaction which stores the event
+    fn notify(&self) {
+        self.webhook_notify.notify_one();
+    }
+
+    async fn process_rescan_task(&self, task: TaskDb) -> Result<(), Error> {
+        let mut data: RescanTaskData = serde_json::from_value(task.data.clone())?;
+
+        loop {
+            let (status, error) = match self.rescan_batch(&task, &mut data).await {
+                Ok(()) => {
+                    let status = match &data.cursor {
+                        Some(_) => TaskStatus::InProgress,
+                        None => TaskStatus::Done,
+                    };
+
+                    (status, None)
+                }
+                Err(e) => {
+                    log::error!(
+                        "Failed to rescan transactions of task `{}`: {:?}",
+                        task.id,
+                        e
+                    );
+                    (TaskStatus::Error, Some(e.to_string()))
+                }
+            };
+
+            self.sqlx_client
+                .update_task(task.id, status, serde_json::to_value(&data)?, error)
+                .await?;
+
+            if status != TaskStatus::InProgress {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Loads and stores the next batch of the account transactions history,
+    /// updates the progress of the task on success
+    async fn rescan_batch(&self, task: &TaskDb, data: &mut RescanTaskData) -> Result<(), Error> {
+        let mut found = 0;
+        let mut inserted = 0;
+
+        let (cursor, pruned_before_lt) = match &data.token_wallet {
+            None => {
+                let address = repack_address(&format!(
+                    "{}:{}",
+                    task.account_workchain_id, task.account_hex
+                ))?;
+                let batch = self
+                    .ton_api_client
+                    .load_ton_transactions_history(&address, data.cursor.clone(), RESCAN_BATCH_SIZE)
+                    .await?;
+
+                for transaction in batch.transactions {
+                    found += 1;
+                    if self
+                        .insert_history_transaction(&task.service_id, transaction)
+                        .await?
+                    {
+                        inserted += 1;
+                    }
+                }
+
+                (batch.cursor, batch.pruned_before_lt)
+            }
+            Some(token_wallet) => {
+                let token_wallet = repack_address(token_wallet)?;
+                let batch = self
+                    .ton_api_client
+                    .load_token_transactions_history(
+                        &token_wallet,
+                        data.cursor.clone(),
+                        RESCAN_BATCH_SIZE,
+                    )
+                    .await?;
+
+                for transaction in batch.transactions {
+                    found += 1;
+                    if self
+                        .insert_history_token_transaction(&task.service_id, transaction)
+                        .await?
+                    {
+                        inserted += 1;
+                    }
+                }
+
+                (batch.cursor, batch.pruned_before_lt)
+            }
+        };
+
+        data.transactions_found += found;
+        data.transactions_inserted += inserted;
+        data.cursor = cursor;
+        data.pruned_before_lt = pruned_before_lt;
+
+        Ok(())
+    }
+
+    /// Stores the transaction found by rescan unless it's already known.
+    /// Returns `true` if the transaction was inserted
+    async fn insert_history_transaction(
+        &self,
+        service_id: &ServiceId,
+        transaction: CaughtTonTransaction,
+    ) -> Result<bool, Error> {
+        let transaction_hash = match &transaction {
+            CaughtTonTransaction::Create(transaction) => transaction.transaction_hash.clone(),
+            CaughtTonTransaction::UpdateSent(transaction) => {
+                transaction.input.transaction_hash.clone()
+            }
+        };
+
+        if let Some(transaction_hash) = &transaction_hash {
+            if self
+                .sqlx_client
+                .get_transaction_by_h(*service_id, transaction_hash)
+                .await
+                .is_ok()
+            {
+                return Ok(false);
+            }
+        }
+
+        match transaction {
+            CaughtTonTransaction::Create(transaction) => {
+                self.sqlx_client
+                    .create_receive_transaction(transaction, *service_id)
+                    .await?;
+            }
+            CaughtTonTransaction::UpdateSent(transaction) => {
+                self.sqlx_client
+                    .upsert_send_transaction(
+                        *service_id,
+                        transaction.message_hash,
+                        transaction.account_workchain_id,
+                        transaction.account_hex,
+                        transaction.input,
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Stores the token transaction found by rescan unless it's already known.
+    /// Returns `true` if the transaction was inserted
+    async fn insert_history_token_transaction(
+        &self,
+        service_id: &ServiceId,
+        transaction: CreateTokenTransaction,
+    ) -> Result<bool, Error> {
+        if let Some(transaction_hash) = &transaction.transaction_hash {
+            if self
+                .sqlx_client
+                .get_token_transaction_by_h(*service_id, transaction_hash)
+                .await
+                .is_ok()
+            {
+                return Ok(false);
+            }
+        }
+
+        self.sqlx_client
+            .create_token_transaction(transaction, *service_id)
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Splits the outputs into chunks which fit into a single message of the wallet.
+    /// The first chunk is sent immediately with the id of the batch, the rest are sent
+    /// by the payouts worker one after another
+    async fn create_payout_batch(
+        self: &Arc<Self>,
+        service_id: &ServiceId,
+        input: TransactionSend,
+        address_db: &AddressDb,
+        signer: &dyn Signer,
+    ) -> Result<TransactionDb, Error> {
+        for output in input.outputs.iter() {
+            repack_address(&output.recipient_address.0).map_err(|_| {
+                TonServiceError::WrongInput("Invalid recipient address".to_string())
+            })?;
+        }
+
+        let max_outputs = max_outputs_per_message(&address_db.account_type);
+
+        let mut outputs = Vec::with_capacity(input.outputs.len());
+        for (chunk, chunk_outputs) in input.outputs.chunks(max_outputs).enumerate() {
+            let transaction_id = match chunk {
+                0 => input.id,
+                _ => Uuid::new_v4(),
+            };
+
+            for output in chunk_outputs {
+                outputs.push(CreatePayoutOutput {
+                    position: outputs.len() as i32,
+                    chunk: chunk as i32,
+                    transaction_id,
+                    output: serde_json::to_value(output)?,
+                });
+            }
+        }
+
+        let PayoutBatch { batch, outputs } = self
+            .sqlx_client
+            .create_payout_batch(CreatePayoutBatch {
+                id: input.id,
+                service_id: *service_id,
+                account_workchain_id: address_db.workchain_id,
+                account_hex: address_db.hex.clone(),
+                bounce: input.bounce.unwrap_or_default(),
+                payload: input.payload,
+                outputs,
+            })
+            .await?;
+
+        let first_chunk: Vec<_> = outputs.iter().filter(|o| o.chunk == 0).cloned().collect();
+
+        match self
+            .send_payout_chunk(&batch, &first_chunk, address_db, signer)
+            .await
+        {
+            Ok(transaction) => Ok(transaction),
+            Err(e) => {
+                // Nothing was sent, so the whole batch is failed
+                let chunk_heads = outputs
+                    .iter()
+                    .filter(|output| output.position as usize % max_outputs == 0);
+                for output in chunk_heads {
+                    self.sqlx_client
+                        .update_payout_outputs(
+                            &output.transaction_id,
+                            PayoutOutputStatus::Error,
+                            Some(e.to_string()),
+                        )
+                        .await?;
+                }
+                self.sqlx_client
+                    .update_payout_batch_status(&batch.id, PayoutBatchStatus::Failed)
+                    .await?;
+
+                Err(e)
+            }
+        }
+    }
+
+    async fn send_payout_chunk(
+        self: &Arc<Self>,
+        batch: &PayoutBatchDb,
+        chunk: &[PayoutOutputDb],
+        address_db: &AddressDb,
+        signer: &dyn Signer,
+    ) -> Result<TransactionDb, Error> {
+        let transaction_id = chunk
+            .first()
+            .map(|output| output.transaction_id)
+            .ok_or(TonClientError::RecipientNotFound)?;
+
+        let outputs = chunk
+            .iter()
+            .map(|output| serde_json::from_value(output.output.clone()))
+            .collect::<Result<Vec<TransactionSendOutput>, _>>()?;
+
+        let input = TransactionSend {
+            id: transaction_id,
+            from_address: Address(format!(
+                "{}:{}",
+                batch.account_workchain_id, batch.account_hex
+            )),
+            outputs,
+            bounce: Some(batch.bounce),
+            payload: batch.payload.clone(),
+            retry_on_expire: false,
+        };
+
+        let (payload, signed_message) = self
+            .ton_api_client
+            .prepare_transaction(
+                input,
+                signer,
+                &address_db.account_type,
+                &address_db.custodians,
+            )
+            .await?;
+
+        let (transaction, _) = self
+            .sqlx_client
+            .create_send_transaction(CreateSendTransaction::new(payload, batch.service_id))
+            .await?;
+
+        self.sqlx_client
+            .update_payout_outputs(&transaction_id, PayoutOutputStatus::Sent, None)
+            .await?;
+
+        self.send_transaction(
+            transaction.message_hash.clone(),
+            transaction.account_hex.clone(),
+            transaction.account_workchain_id,
+            signed_message,
+            true,
+            true,
+        )
+        .await?;
+
+        self.notify();
+
+        Ok(transaction)
+    }
+
+    /// Sends the next chunk of the batch when the previous one is finished.
+    /// Chunks are sent sequentially to keep the wallet seqno ordering
+    async fn process_payout_batch(self: &Arc<Self>, batch: PayoutBatchDb) -> Result<(), Error> {
+        let outputs = self.sqlx_client.get_payout_outputs(&batch.id).await?;
+
+        let mut chunks: Vec<Vec<PayoutOutputDb>> = Vec::new();
+        for output in outputs {
+            match chunks.last_mut() {
+                Some(chunk) if chunk[0].chunk == output.chunk => chunk.push(output),
+                _ => chunks.push(vec![output]),
+            }
+        }
+
+        for chunk in chunks {
+            let transaction_id = chunk[0].transaction_id;
+
+            match chunk[0].status {
+                PayoutOutputStatus::Done | PayoutOutputStatus::Error => continue,
+                PayoutOutputStatus::Pending => {
+                    // The chunk could have been sent right before the restart
+                    if self
+                        .sqlx_client
+                        .find_transaction_by_id(&transaction_id)
+                        .await?
+                        .is_some()
+                    {
+                        self.sqlx_client
+                            .update_payout_outputs(&transaction_id, PayoutOutputStatus::Sent, None)
+                            .await?;
+                        return Ok(());
+                    }
+
+                    let address_db = self
+                        .sqlx_client
+                        .get_address(
+                            batch.service_id,
+                            batch.account_workchain_id,
+                            batch.account_hex.clone(),
+                        )
+                        .await?;
+
+                    let result = match self.signer(&address_db) {
+                        Ok(signer) => {
+                            self.send_payout_chunk(&batch, &chunk, &address_db, signer.as_ref())
+                                .await
+                        }
+                        Err(e) => Err(e),
+                    };
+
+                    match result {
+                        // Wait for the chunk to be processed
+                        Ok(_) => return Ok(()),
+                        Err(e) => {
+                            log::error!(
+                                "Failed to send chunk {} of payout batch `{}`: {:?}",
+                                chunk[0].chunk,
+                                batch.id,
+                                e
+                            );
+                            self.sqlx_client
+                                .update_payout_outputs(
+                                    &transaction_id,
+                                    PayoutOutputStatus::Error,
+                                    Some(e.to_string()),
+                                )
+                                .await?;
+                        }
+                    }
+                }
+                PayoutOutputStatus::Sent => {
+                    let transaction = self
+                        .sqlx_client
+                        .find_transaction_by_id(&transaction_id)
+                        .await?;
+
+                    let (status, error) = match transaction {
+                        Some(transaction) => match transaction.status {
+                            TonTransactionStatus::New => return Ok(()),
+                            TonTransactionStatus::Done if !transaction.aborted => {
+                                (PayoutOutputStatus::Done, None)
+                            }
+                            _ => (
+                                PayoutOutputStatus::Error,
+                                Some(
+                                    transaction
+                                        .error
+                                        .unwrap_or_else(|| "Transaction failed".to_string()),
+                                ),
+                            ),
+                        },
+                        None => (
+                            PayoutOutputStatus::Error,
+                            Some("Transaction not found".to_string()),
+                        ),
+                    };
+
+                    self.sqlx_client
+                        .update_payout_outputs(&transaction_id, status, error)
+                        .await?;
+                }
+            }
+        }
+
+        // All chunks are finished
+        let outputs = self.sqlx_client.get_payout_outputs(&batch.id).await?;
+        let failed = outputs
+            .iter()
+            .filter(|output| output.status == PayoutOutputStatus::Error)
+            .count();
+
+        let status = match failed {
+            0 => PayoutBatchStatus::Done,
+            failed if failed == outputs.len() => PayoutBatchStatus::Failed,
+            _ => PayoutBatchStatus::PartiallyFailed,
+        };
+
+        self.sqlx_client
+            .update_payout_batch_status(&batch.id, status)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn deliver_notification(&self, delivery: WebhookDeliveryDb) -> Result<(), Error> {
+        let now = Utc::now().naive_utc();
+
+        let (status, next_attempt_at, last_error) = match self.send_notification(&delivery).await {
+            Ok(_) => (WebhookDeliveryStatus::Delivered, now, None),
+            Err(e) => {
+                let attempts = delivery.attempts as u32 + 1;
+
+                log::warn!(
+                    "Failed to deliver notification `{}` (attempt {}): {:?}",
+                    delivery.id,
+                    attempts,
+                    e
+                );
+
+                if attempts >= self.webhook_config.max_attempts || delivery.callback_id.is_none() {
+                    (WebhookDeliveryStatus::Failed, now, Some(format!("{:?}", e)))
+                } else {
+                    (
+                        WebhookDeliveryStatus::Pending,
+                        now + retry_delay(&self.webhook_config, attempts),
+                        Some(format!("{:?}", e)),
+                    )
+                }
+            }
+        };
+
+        // The delivery may have been failed while it was sent
+        if self
+            .sqlx_client
+            .update_webhook_delivery(delivery.id, status, next_attempt_at, last_error)
+            .await?
+            .is_none()
+        {
+            return Ok(());
+        }
+
+        if status == WebhookDeliveryStatus::Pending {
+            return Ok(());
+        }
+
+        // The event is completed when it was delivered to all matching callbacks
+        let deliveries = self
+            .sqlx_client
+            .get_webhook_deliveries_by_events(delivery.service_id, &[delivery.event_id])
+            .await?;
+
+        let event_status = if deliveries
+            .iter()
+            .any(|d| d.status == WebhookDeliveryStatus::Pending)
+        {
+            None
+        } else if deliveries
+            .iter()
+            .all(|d| d.status == WebhookDeliveryStatus::Delivered)
+        {
+            Some(TonEventStatus::Notified)
+        } else {
+            Some(TonEventStatus::Error)
+        };
+
+        // Proposal events are not stored, only delivered
+        let payload: AccountTransactionEvent = serde_json::from_value(delivery.payload)?;
+        if payload.transaction_status.is_proposal() {
+            return Ok(());
+        }
+
+        if let Some(event_status) = event_status {
+            match delivery.notify_type {
+                NotifyType::Transaction => {
+                    self.sqlx_client
+                        .update_event_status_of_transaction_event_by_id(
+                            delivery.service_id,
+                            delivery.event_id,
+                            event_status,
+                        )
+                        .await?;
+                }
+                NotifyType::TokenTransaction => {
+                    self.sqlx_client
+                        .update_event_status_of_token_transaction_event_by_id(
+                            delivery.service_id,
+                            delivery.event_id,
+                            event_status,
+                        )
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn send_notification(&self, delivery: &WebhookDeliveryDb) -> Result<(), Error> {
+        let payload: AccountTransactionEvent = serde_json::from_value(delivery.payload.clone())?;
+
+        let info = self.get_blockchain_info().await?;
+
+        let callback_id = delivery
+            .callback_id
+            .ok_or_else(|| anyhow::anyhow!("Callback was deleted"))?;
+
+        let url = self
+            .sqlx_client
+            .get_callback_by_id(delivery.service_id, callback_id)
+            .await
+            .map(|c| c.callback)?;
+        let secret = self
+            .sqlx_client
+            .get_key_by_service_id(&delivery.service_id)
+            .await
+            .map(|k| k.secret)?;
+
+        self.callback_client
+            .send(info.network_id, url, payload, secret)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn send_transaction(
+        self: &Arc<Self>,
+        message_hash: String,
+        account_hex: String,
+        account_workchain_id: i32,
+        signed_message: SignedMessage,
+        non_blocking: bool,
+        with_db_update: bool,
+    ) -> Result<(), Error> {
+        // The message can be broadcast again after a restart
+        if with_db_update {
+            let message_boc = ton_types::serialize_toc(&signed_message.message.serialize()?)?;
+            let expire_at = NaiveDateTime::from_timestamp_opt(signed_message.expire_at as i64, 0);
+            self.sqlx_client
+                .update_transaction_message(
+                    &message_hash,
+                    account_workchain_id,
+                    &account_hex,
+                    message_boc,
+                    expire_at,
+                )
+                .await?;
+        }
+
+        let ton_service = Arc::downgrade(self);
+
+        match non_blocking {
+            false => {
+                send_transaction(
+                    ton_service,
+                    message_hash,
+                    account_hex,
+                    account_workchain_id,
+                    signed_message,
+                    with_db_update,
+                )
+                .await?
+            }
+            true => {
+                self.spawn_background_task(
+                    "Send transaction",
+                    send_transaction(
+                        ton_service,
+                        message_hash,
+                        account_hex,
+                        account_workchain_id,
+                        signed_message,
+                        with_db_update,
+                    ),
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Marks the sent transaction as expired, unless it can be sent again.
+    /// Returns the hash and the new message of the transaction to send
+    async fn handle_expired_message(
+        self: &Arc<Self>,
+        message_hash: String,
+        account_workchain_id: i32,
+        account_hex: String,
+    ) -> Result<Option<(String, SignedMessage)>, Error> {
+        match self
+            .resend_expired_transaction(&message_hash, account_workchain_id, &account_hex)
+            .await
+        {
+            Ok(Some(resent)) => return Ok(Some(resent)),
+            Ok(None) => {}
+            Err(e) => log::error!(
+                "Failed to resend expired message `{}`: {:?}",
+                message_hash,
+                e
+            ),
+        }
+
+        self.upsert_sent_transaction(
+            message_hash,
+            account_workchain_id,
+            account_hex,
+            UpdateSendTransaction::error("Expired".to_string()),
+        )
+        .await?;
+
+        Ok(None)
+    }
+
+    /// Signs the transfer sent with `retry_on_expire` again with a new expiration.
+    /// The transaction keeps its id and gets the hash of the new message
+    async fn resend_expired_transaction(
+        &self,
+        message_hash: &str,
+        account_workchain_id: i32,
+        account_hex: &str,
+    ) -> Result<Option<(String, SignedMessage)>, Error> {
+        let transaction = match self
+            .sqlx_client
+            .get_transaction_resend(message_hash, account_workchain_id, account_hex)
+            .await?
+        {
+            Some(transaction) => transaction,
+            None => return Ok(None),
+        };
+
+        let (resend_input, expire_at) = match (transaction.resend_input, transaction.expire_at) {
+            (Some(resend_input), Some(expire_at)) => (resend_input, expire_at),
+            _ => return Ok(None),
+        };
+
+        if transaction.send_attempts >= MAX_SEND_ATTEMPTS {
+            return Ok(None);
+        }
+
+        // The old message must not be able to land, otherwise the transfer may be sent twice.
+        // It may be expired by a block which is ahead of the current time
+        while self.ton_api_client.current_utime() <= expire_at.timestamp() as u32 {
+            tokio::time::sleep(Duration::from_secs(EXPIRED_MESSAGE_POLL_INTERVAL)).await;
+        }
+
+        let address_db = self
+            .sqlx_client
+            .get_address(
+                transaction.service_id,
+                transaction.account_workchain_id,
+                transaction.account_hex.clone(),
+            )
+            .await?;
+        let signer = self.signer(&address_db)?;
+
+        let (payload, signed_message) = match serde_json::from_value(resend_input)? {
+            ResendTransfer::Ton(input) => {
+                self.ton_api_client
+                    .prepare_transaction(
+                        input,
+                        signer.as_ref(),
+                        &address_db.account_type,
+                        &address_db.custodians,
+                    )
+                    .await?
+            }
+            ResendTransfer::Token(input) => {
+                self.ton_api_client
+                    .prepare_token_transaction(
+                        &input,
+                        signer.as_ref(),
+                        &address_db.account_type,
+                        &address_db.custodians,
+                    )
+                    .await?
+            }
+        };
+
+        let message_boc = ton_types::serialize_toc(&signed_message.message.serialize()?)?;
+        let expire_at = NaiveDateTime::from_timestamp_opt(signed_message.expire_at as i64, 0);
+
+        let updated = self
+            .sqlx_client
+            .update_transaction_resend(
+                transaction.id,
+                message_hash,
+                &payload.message_hash,
+                message_boc,
+                expire_at,
+            )
+            .await?;
+        if !updated {
+            return Ok(None);
+        }
+
+        log::info!(
+            "Resending expired message `{}` of transaction `{}` as `{}`",
+            message_hash,
+            transaction.id,
+            payload.message_hash
+        );
+
+        Ok(Some((payload.message_hash, signed_message)))
+    }
+
+    async fn deploy_wallet(
+        self: &Arc<Self>,
+        service_id: &ServiceId,
+        address: &AddressDb,
+        signer: &dyn Signer,
+    ) -> Result<(), Error> {
+        let payload = self.ton_api_client.prepare_deploy(address, signer).await?;
+
+        if let Some((payload, signed_message)) = payload {
+            let (transaction, _) = self
+                .sqlx_client
+                .create_send_transaction(CreateSendTransaction::new(payload, *service_id))
+                .await?;
+
+            self.send_transaction(
+                transaction.message_hash,
+                transaction.account_hex,
+                transaction.account_workchain_id,
+                signed_message,
+                false,
+                true,
+            )
+            .await?;
+
+            self.notify();
+        }
+
+        Ok(())
+    }
+
+    pub async fn token_whitelist(
+        &self,
+        service_id: &ServiceId,
+    ) -> Result<Vec<WhitelistedTokenFromDb>, Error> {
+        let whitelist = self
+            .sqlx_client
+            .get_service_token_whitelist(*service_id)
+            .await?;
+
+        Ok(whitelist)
+    }
+
+    /// Limits the tokens available to the service to the enabled whitelisted roots.
+    /// All whitelisted tokens are available to the service when the list is `None`
+    pub async fn set_service_root_tokens(
+        &self,
+        service_id: &ServiceId,
+        root_addresses: Option<Vec<Address>>,
+    ) -> Result<Vec<WhitelistedTokenFromDb>, Error> {
+        let addresses = match root_addresses {
+            Some(root_addresses) => {
+                let mut addresses = Vec::with_capacity(root_addresses.len());
+                for root_address in root_addresses {
+                    let root_address = repack_address(&root_address.0)?.to_string();
+                    match self.sqlx_client.get_root_token(&root_address).await {
+                        Ok(token) if token.enabled => addresses.push(root_address),
+                        Ok(_) => {
+                            return Err(TonServiceError::WrongInput(format!(
+                                "Root token `{}` is disabled",
+                                root_address
+                            ))
+                            .into())
+                        }
+                        Err(_) => {
+                            return Err(TonServiceError::WrongInput(format!(
+                                "Root token `{}` not included in the whitelist",
+                                root_address
+                            ))
+                            .into())
+                        }
+                    }
+                }
+                Some(addresses)
+            }
+            None => None,
+        };
+
+        if !self
+            .sqlx_client
+            .set_service_root_tokens(*service_id, addresses.as_deref())
+            .await?
+        {
+            return Err(TonServiceError::WrongInput(format!(
+                "Service `{}` not found",
+                service_id.0
+            ))
+            .into());
+        }
+
+        self.service_root_tokens
+            .write()
+            .insert(*service_id, addresses.map(HashSet::from_iter));
+
+        self.token_whitelist(service_id).await
+    }
+
+    pub async fn add_root_token(
+        &self,
+        input: CreateRootToken,
+    ) -> Result<WhitelistedTokenFromDb, Error> {
+        let root_address = repack_address(&input.address.0)?;
+        let metadata = self
+            .ton_api_client
+            .check_root_token(&root_address, input.version)?;
+
+        let token = self
+            .sqlx_client
+            .create_root_token(WhitelistedTokenFromDb {
+                name: input.name,
+                address: root_address.to_string(),
+                version: input.version,
+                symbol: None,
+                decimals: None,
+                total_supply: None,
+                metadata_updated_at: None,
+                enabled: true,
+            })
+            .await?;
+
+        self.sqlx_client
+            .update_root_token_metadata(&token.address, &metadata)
+            .await?;
+        let token = self.sqlx_client.get_root_token(&token.address).await?;
+
+        self.token_metadata
+            .write()
+            .insert(token.address.clone(), metadata);
+        self.ton_api_client.update_root_token(root_address, true);
+
+        Ok(token)
+    }
+
+    pub async fn update_root_token(
+        &self,
+        address: &Address,
+        enabled: bool,
+    ) -> Result<WhitelistedTokenFromDb, Error> {
+        let root_address = repack_address(&address.0)?;
+        let token = self
+            .sqlx_client
+            .update_root_token_enabled(&root_address.to_string(), enabled)
+            .await?;
+
+        self.ton_api_client.update_root_token(root_address, enabled);
+
+        Ok(token)
+    }
+
+    pub async fn delete_root_token(
+        &self,
+        address: &Address,
+    ) -> Result<WhitelistedTokenFromDb, Error> {
+        let root_address = repack_address(&address.0)?;
+        let token = self
+            .sqlx_client
+            .delete_root_token(&root_address.to_string())
+            .await?;
+
+        self.token_metadata.write().remove(&token.address);
+        for roots in self.service_root_tokens.write().values_mut().flatten() {
+            roots.remove(&token.address);
+        }
+        self.ton_api_client.update_root_token(root_address, false);
+
+        Ok(token)
+    }
+
+    /// Returns the cached metadata of the whitelisted root token
+    pub fn token_metadata(&self, root_address: &str) -> Option<TokenMetadata> {
+        self.token_metadata.read().get(root_address).cloned()
+    }
+
+    async fn update_token_metadata(&self) -> Result<(), Error> {
+        let whitelist = self.sqlx_client.get_token_whitelist().await?;
+
+        for token in whitelist {
+            let metadata = match repack_address(&token.address)
+                .map_err(Error::from)
+                .and_then(|root_address| self.ton_api_client.get_root_token_metadata(&root_address))
+            {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to get metadata of root token `{}`: {:?}",
+                        token.address,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            // Always store the metadata to keep `metadata_updated_at` fresh
+            if let Err(e) = self
+                .sqlx_client
+                .update_root_token_metadata(&token.address, &metadata)
+                .await
+            {
+                log::error!(
+                    "Failed to update metadata of root token `{}`: {:?}",
+                    token.address,
+                    e
+                );
+            }
+
+            self.token_metadata.write().insert(token.address, metadata);
+        }
+
+        Ok(())
+    }
+
+    async fn check_root_token_allowed(
+        &self,
+        service_id: &ServiceId,
+        root_address: &Address,
+    ) -> Result<(), Error> {
+        let root_address = repack_address(&root_address.0)?.to_string();
+
+        let cached = self.service_root_tokens.read().get(service_id).cloned();
+        let roots = match cached {
+            Some(roots) => roots,
+            None => {
+                let roots = self
+                    .sqlx_client
+                    .get_service_root_tokens(*service_id)
+                    .await?
+                    .map(HashSet::from_iter);
+                self.service_root_tokens
+                    .write()
+                    .insert(*service_id, roots.clone());
+                roots
+            }
+        };
+
+        if matches!(&roots, Some(roots) if !roots.contains(&root_address)) {
+            return Err(TonServiceError::RootTokenNotAllowed(root_address).into());
+        }
+        Ok(())
+    }
+
+    /// Evaluates the spending rules of the service against the outgoing transfer and reserves
+    /// its value in the spending records, so that concurrent transfers can't exceed the limits
+    /// together. The reservation is released if the check is dropped before `record_spending`
+    async fn check_spending_limits(
+        &self,
+        service_id: &ServiceId,
+        account: &MsgAddressInt,
+        root_address: Option<&str>,
+        value: &BigDecimal,
+        recipients: &[MsgAddressInt],
+    ) -> Result<SpendingCheck, Error> {
+        let account_workchain_id = account.workchain_id();
+        let account_hex = account.address().to_hex_string();
+
+        let record = SpendingRecordDb {
+            id: Uuid::new_v4(),
+            service_id: *service_id,
+            account_workchain_id,
+            account_hex: account_hex.clone(),
+            root_address: root_address.map(ToOwned::to_owned),
+            value: value.clone(),
+            transaction_id: None,
+        };
+        let record_id = record.id;
+
+        let rules = self
+            .sqlx_client
+            .get_account_spending_limits(
+                *service_id,
+                account_workchain_id,
+                &account_hex,
+                root_address,
+            )
+            .await?;
+        if rules.is_empty() {
+            self.sqlx_client.create_spending_record(record).await?;
+            return Ok(SpendingCheck::new(
+                false,
+                record_id,
+                self.sqlx_client.clone(),
+            ));
+        }
+
+        let lock = self
+            .spending_locks
+            .lock()
+            .entry(*service_id)
+            .or_default()
+            .clone();
+        // Held only while the limits are checked and the transfer is reserved
+        let _guard = lock.lock_owned().await;
+        let now = Utc::now().naive_utc();
+        let mut approval_required = false;
+
+        for rule in rules {
+            if let Some(approval_threshold) = &rule.approval_threshold {
+                approval_required |= value >= approval_threshold;
+            }
+
+            if let Some(allowed_recipients) = &rule.allowed_recipients {
+                for recipient in recipients {
+                    let recipient = recipient.to_string();
+                    if !allowed_recipients.contains(&recipient) {
+                        return Err(TonServiceError::RecipientNotAllowed(recipient).into());
+                    }
+                }
+            }
+
+            if let Some(max_per_transaction) = &rule.max_per_transaction {
+                if value > max_per_transaction {
+                    return Err(TonServiceError::LimitExceeded(format!(
+                        "max {} per transaction of rule `{}`",
+                        max_per_transaction, rule.id
+                    ))
+                    .into());
+                }
+            }
+
+            // Per-address rules count transfers of the account, the others of the whole service
+            let account = rule
+                .account_hex
+                .is_some()
+                .then(|| (account_workchain_id, account_hex.as_str()));
+
+            let windows = [
+                ("hourly", &rule.hourly_limit, chrono::Duration::hours(1)),
+                ("daily", &rule.daily_limit, chrono::Duration::days(1)),
+            ];
+            for (name, limit, period) in windows {
+                let limit = match limit {
+                    Some(limit) => limit,
+                    None => continue,
+                };
+
+                let since = now - period;
+                let sent = match root_address {
+                    Some(root_address) => {
+                        self.sqlx_client
+                            .get_sent_token_value(*service_id, account, root_address, since)
+                            .await?
+                    }
+                    None => {
+                        self.sqlx_client
+                            .get_sent_value(*service_id, account, since)
+                            .await?
+                    }
+                };
+
+                if sent + value > *limit {
+                    return Err(TonServiceError::LimitExceeded(format!(
+                        "{} limit {} of rule `{}`",
+                        name, limit, rule.id
+                    ))
+                    .into());
+                }
+            }
+        }
+
+        self.sqlx_client.create_spending_record(record).await?;
+
+        Ok(SpendingCheck::new(
+            approval_required,
+            record_id,
+            self.sqlx_client.clone(),
+        ))
+    }
+
+    /// Keeps the reservation of the checked transfer once it is created.
+    /// Failed transfers are not counted if their transaction is specified
+    async fn record_spending(&self, mut spending: SpendingCheck, transaction_id: Option<Uuid>) {
+        spending.sqlx_client = None;
+
+        if let Some(transaction_id) = transaction_id {
+            if let Err(e) = self
+                .sqlx_client
+                .set_spending_record_transaction(spending.record_id, transaction_id)
+                .await
+            {
+                log::error!(
+                    "Failed to set transaction `{}` of spending record `{}`: {:?}",
+                    transaction_id,
+                    spending.record_id,
+                    e
+                );
+            }
+        }
+    }
+
+    fn signer(&self, address: &AddressDb) -> Result<Box<dyn Signer>, Error> {
+        if address.watch_only {
+            return Err(TonServiceError::WatchOnlyAddress.into());
+        }
+
+        let public_key = address
+            .public_key
+            .as_deref()
+            .ok_or(TonServiceError::SignerUnavailable)?;
+        let public_key = ed25519_dalek::PublicKey::from_bytes(&hex::decode(public_key)?)?;
+
+        Ok(match address.signer_type {
+            SignerType::Local => {
+                let private_key = address
+                    .private_key
+                    .as_deref()
+                    .ok_or(TonServiceError::SignerUnavailable)?;
+                let private_key = self.keyring.decrypt(private_key, &address.id)?;
+                Box::new(LocalSigner::new(public_key, &private_key)?)
+            }
+            SignerType::Remote => Box::new(RemoteSigner::new(
+                self.remote_signer()?,
+                address.id,
+                public_key,
+            )),
+        })
+    }
+
+    /// Signer for the local execution, available for watch-only addresses as well
+    fn estimation_signer(&self, address: &AddressDb) -> Result<EstimationSigner, Error> {
+        let public_key = address
+            .public_key
+            .as_deref()
+            .ok_or(TonServiceError::SignerUnavailable)?;
+        let public_key = ed25519_dalek::PublicKey::from_bytes(&hex::decode(public_key)?)?;
+
+        Ok(EstimationSigner::new(public_key))
+    }
+
+    fn remote_signer(&self) -> Result<Arc<RemoteSignerClient>, Error> {
+        self.remote_signer
+            .clone()
+            .ok_or_else(|| TonServiceError::SignerUnavailable.into())
+    }
+
+    /// Waits future in background. In case of error does nothing but logging
+    fn spawn_background_task<F>(self: &Arc<Self>, name: &'static str, fut: F)
+    where
+        F: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        tokio::spawn(async move {
+            if let Err(e) = fut.await {
+                log::error!("Failed to {}: {:?}", name, e);
+            }
+        });
+    }
+}
+
+/// Waits for the restored message, it's broadcast again once the node is synced if `rebroadcast` is set
+async fn wait_message(
+    ton_service: Weak<TonService>,
+    transaction: PendingMessageDb,
+    rx: tokio::sync::oneshot::Receiver<MessageStatus>,
+    expire_at: u32,
+    rebroadcast: bool,
+) -> Result<(), Error> {
+    let message = match transaction.message_boc.as_ref().filter(|_| rebroadcast) {
+        Some(message_boc) => {
+            wait_synced(&ton_service).await?;
+            match ton_block::Message::construct_from_bytes(message_boc) {
+                Ok(message) => Some(message),
+                Err(e) => {
+                    log::error!(
+                        "Failed to rebroadcast message `{}`: {:?}",
+                        transaction.message_hash,
+                        e
+                    );
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    let status = {
+        let service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
+        let message_hash = UInt256::from_be_bytes(&hex::decode(&transaction.message_hash)?);
+        let ton_api_client = service.ton_api_client.clone();
+        drop(service);
+
+        ton_api_client
+            .wait_pending_message(rx, message_hash, message.as_ref(), expire_at)
+            .await?
+    };
+
+    match status {
+        MessageStatus::Delivered => {
+            log::info!("Successfully sent message `{}`", transaction.message_hash)
+        }
+        MessageStatus::Expired => {
+            let service = match ton_service.upgrade() {
+                Some(ton_service) => ton_service,
+                None => return Err(TonServiceError::ServiceUnavailable.into()),
+            };
+
+            let resent = service
+                .handle_expired_message(
+                    transaction.message_hash,
+                    transaction.account_workchain_id,
+                    transaction.account_hex.clone(),
+                )
+                .await?;
+
+            drop(service);
+
+            if let Some((message_hash, signed_message)) = resent {
+                send_transaction(
+                    ton_service,
+                    message_hash,
+                    transaction.account_hex,
+                    transaction.account_workchain_id,
+                    signed_message,
+                    true,
+                )
+                .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Polls until the node is synced
+async fn wait_synced(ton_service: &Weak<TonService>) -> Result<(), Error> {
+    loop {
+        let ton_service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
+
+        if ton_service.ton_api_client.is_synced() {
+            return Ok(());
+        }
+
+        drop(ton_service);
+
+        tokio::time::sleep(Duration::from_secs(PENDING_MESSAGES_SYNC_POLL_INTERVAL)).await;
+    }
+}
+
+async fn stream_events(
+    ton_service: Weak<TonService>,
+    service_id: ServiceId,
+    cursor: Option<EventCursor>,
+    mut events_rx: EventsReceiver,
+    mut tx: futures::channel::mpsc::Sender<(EventCursor, NotifyType, AccountTransactionEvent)>,
+) -> Result<(), Error> {
+    let mut cursor = match cursor {
+        Some(cursor) => cursor,
+        None => match ton_service.upgrade() {
+            Some(ton_service) => EventCursor(
+                ton_service
+                    .sqlx_client
+                    .get_last_event_stream_seq(service_id)
+                    .await?,
+            ),
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        },
+    };
+
+    // Replayed events which may still be received from the live stream
+    let mut replayed = HashSet::new();
+    let mut replay = true;
+
+    loop {
+        if replay {
+            let ton_service = match ton_service.upgrade() {
+                Some(ton_service) => ton_service,
+                None => return Err(TonServiceError::ServiceUnavailable.into()),
+            };
+
+            replayed.clear();
+
+            loop {
+                let entries = ton_service
+                    .sqlx_client
+                    .get_event_stream_after(service_id, cursor.0, MAX_LIMIT_SEARCH)
+                    .await?;
+                let count = entries.len() as i64;
+
+                for entry in entries {
+                    cursor = cursor.max(EventCursor(entry.seq));
+                    replayed.insert(entry.seq);
+
+                    if tx.send(stream_entry(&entry)?).await.is_err() {
+                        return Ok(());
+                    }
+                }
+
+                if count < MAX_LIMIT_SEARCH {
+                    break;
+                }
+            }
+
+            replay = false;
+        }
+
+        if tx.is_closed() {
+            return Ok(());
+        }
+
+        match events_rx.recv().await {
+            Ok(Some(entry)) => {
+                if entry.service_id != service_id {
+                    continue;
+                }
+
+                // Events are received in the order of their commits, so all the replayed
+                // ones precede the first event which was not replayed
+                if replayed.remove(&entry.seq) {
+                    continue;
+                }
+                replayed.clear();
+
+                cursor = cursor.max(EventCursor(entry.seq));
+
+                if tx.send(stream_entry(&entry)?).await.is_err() {
+                    return Ok(());
+                }
+            }
+            Ok(None) => replay = true,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                log::warn!(
+                    "Events stream of service `{}` lagged by {} events",
+                    service_id,
+                    skipped
+                );
+                replay = true;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => return Ok(()),
+        }
+    }
+}
 
-        match non_blocking {
-            false => {
-                send_transaction(
-                    ton_service,
-                    message_hash,
-                    account_hex,
-                    account_workchain_id,
-                    signed_message,
-                    with_db_update,
-                )
-                .await?
+fn stream_entry(
+    entry: &EventStreamDb,
+) -> Result<(EventCursor, NotifyType, AccountTransactionEvent), Error> {
+    let event = serde_json::from_value(entry.payload.clone())?;
+    Ok((EventCursor(entry.seq), entry.notify_type, event))
+}
+
+async fn listen_event_stream(ton_service: Weak<TonService>) -> Result<(), Error> {
+    let mut listener = match ton_service.upgrade() {
+        Some(ton_service) => ton_service.sqlx_client.listen_event_stream().await?,
+        None => return Err(TonServiceError::ServiceUnavailable.into()),
+    };
+
+    loop {
+        let notification = listener.try_recv().await;
+
+        let ton_service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
+
+        let notification = match notification {
+            Ok(Some(notification)) => notification,
+            Ok(None) => {
+                log::warn!("Events stream listener reconnected");
+                let _ = ton_service.events_tx.send(None);
+                continue;
             }
-            true => {
-                self.spawn_background_task(
-                    "Send transaction",
-                    send_transaction(
-                        ton_service,
-                        message_hash,
-                        account_hex,
-                        account_workchain_id,
-                        signed_message,
-                        with_db_update,
-                    ),
+            Err(e) => {
+                log::error!("Failed to listen events stream: {:?}", e);
+                let _ = ton_service.events_tx.send(None);
+
+                drop(ton_service);
+                tokio::time::sleep(Duration::from_secs(EVENTS_LISTENER_RETRY_INTERVAL)).await;
+                continue;
+            }
+        };
+
+        // There may be no subscribers at all
+        if ton_service.events_tx.receiver_count() == 0 {
+            continue;
+        }
+
+        let entry = match notification.payload().parse() {
+            Ok(seq) => ton_service.sqlx_client.get_event_stream_entry(seq).await,
+            Err(e) => Err(e.into()),
+        };
+
+        match entry {
+            Ok(entry) => {
+                let _ = ton_service.events_tx.send(Some(Arc::new(entry)));
+            }
+            Err(e) => {
+                log::error!(
+                    "Failed to get streamed event `{}`: {:?}",
+                    notification.payload(),
+                    e
                 );
+                let _ = ton_service.events_tx.send(None);
             }
         }
+    }
+}
 
-        Ok(())
+async fn deliver_notifications(ton_service: Weak<TonService>) -> Result<(), Error> {
+    loop {
+        let ton_service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
+
+        let batch_size = ton_service.webhook_config.batch_size;
+        let poll_interval = Duration::from_millis(ton_service.webhook_config.poll_interval_ms);
+
+        let now = Utc::now().naive_utc();
+        let locked_until = now + chrono::Duration::seconds(WEBHOOK_DELIVERY_LOCK_TIMEOUT);
+
+        let deliveries = match ton_service
+            .sqlx_client
+            .take_pending_webhook_deliveries(now, locked_until, batch_size)
+            .await
+        {
+            Ok(deliveries) => deliveries,
+            Err(e) => {
+                log::error!("Failed to get pending notifications: {:?}", e);
+                Vec::new()
+            }
+        };
+
+        let count = deliveries.len() as i64;
+
+        let results = futures::future::join_all(
+            deliveries
+                .into_iter()
+                .map(|delivery| ton_service.deliver_notification(delivery)),
+        )
+        .await;
+
+        for result in results {
+            if let Err(e) = result {
+                log::error!("Failed to update notification delivery: {:?}", e);
+            }
+        }
+
+        if count < batch_size {
+            let webhook_notify = ton_service.webhook_notify.clone();
+            drop(ton_service);
+
+            tokio::select! {
+                _ = webhook_notify.notified() => {},
+                _ = tokio::time::sleep(poll_interval) => {},
+            }
+        }
     }
+}
 
-    async fn deploy_wallet(
-        self: &Arc<Self>,
-        service_id: &ServiceId,
-        address: &AddressDb,
-        public_key: &[u8],
-        private_key: &[u8],
-    ) -> Result<(), Error> {
-        let payload = self
-            .ton_api_client
-            .prepare_deploy(address, public_key, private_key)
-            .await?;
+async fn process_rescan_tasks(ton_service: Weak<TonService>) -> Result<(), Error> {
+    loop {
+        let ton_service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
 
-        if let Some((payload, signed_message)) = payload {
-            let (transaction, event) = self
+        // History is walked from the latest known shard states
+        if ton_service.ton_api_client.is_synced() {
+            match ton_service
                 .sqlx_client
-                .create_send_transaction(CreateSendTransaction::new(payload, *service_id))
-                .await?;
+                .get_unfinished_tasks(TaskKind::Rescan)
+                .await
+            {
+                Ok(tasks) => {
+                    for task in tasks {
+                        let id = task.id;
+                        if let Err(e) = ton_service.process_rescan_task(task).await {
+                            log::error!("Failed to process rescan task `{}`: {:?}", id, e);
+                        }
+                    }
+                }
+                Err(e) => log::error!("Failed to get rescan tasks: {:?}", e),
+            }
+        }
 
-            self.send_transaction(
-                transaction.message_hash,
-                transaction.account_hex,
-                transaction.account_workchain_id,
-                signed_message,
-                false,
-                true,
-            )
-            .await?;
+        let rescan_notify = ton_service.rescan_notify.clone();
+        drop(ton_service);
 
-            self.notify(service_id, event.into(), NotifyType::Transaction)
-                .await?;
+        tokio::select! {
+            _ = rescan_notify.notified() => {},
+            _ = tokio::time::sleep(Duration::from_secs(RESCAN_POLL_INTERVAL)) => {},
+        }
+    }
+}
+
+async fn process_payout_batches(ton_service: Weak<TonService>) -> Result<(), Error> {
+    loop {
+        let ton_service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
+
+        // Messages are prepared from the latest known account states
+        if ton_service.ton_api_client.is_synced() {
+            match ton_service
+                .sqlx_client
+                .get_in_progress_payout_batches()
+                .await
+            {
+                Ok(batches) => {
+                    for batch in batches {
+                        let id = batch.id;
+                        if let Err(e) = ton_service.process_payout_batch(batch).await {
+                            log::error!("Failed to process payout batch `{}`: {:?}", id, e);
+                        }
+                    }
+                }
+                Err(e) => log::error!("Failed to get payout batches: {:?}", e),
+            }
         }
 
-        Ok(())
+        let payouts_notify = ton_service.payouts_notify.clone();
+        drop(ton_service);
+
+        tokio::select! {
+            _ = payouts_notify.notified() => {},
+            _ = tokio::time::sleep(Duration::from_secs(PAYOUT_POLL_INTERVAL)) => {},
+        }
     }
+}
 
-    pub async fn token_whitelist(&self) -> Result<Vec<WhitelistedTokenFromDb>, Error> {
-        let whitelist = self.sqlx_client.get_token_whitelist().await?;
+async fn refresh_token_metadata(ton_service: Weak<TonService>) -> Result<(), Error> {
+    loop {
+        let ton_service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
 
-        Ok(whitelist)
+        // Metadata is read from the latest known root token states
+        let interval = if ton_service.ton_api_client.is_synced() {
+            if let Err(e) = ton_service.update_token_metadata().await {
+                log::error!("Failed to update token metadata: {:?}", e);
+            }
+            TOKEN_METADATA_REFRESH_INTERVAL
+        } else {
+            TOKEN_METADATA_SYNC_POLL_INTERVAL
+        };
+
+        drop(ton_service);
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
     }
+}
 
-    /// Waits future in background. In case of error does nothing but logging
-    fn spawn_background_task<F>(self: &Arc<Self>, name: &'static str, fut: F)
-    where
-        F: Future<Output = Result<(), Error>> + Send + 'static,
-    {
-        tokio::spawn(async move {
-            if let Err(e) = fut.await {
-                log::error!("Failed to {}: {:?}", name, e);
+async fn sync_multisig_proposals(ton_service: Weak<TonService>) -> Result<(), Error> {
+    loop {
+        let ton_service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
+
+        // Pending transactions are read from the latest known account states
+        if ton_service.ton_api_client.is_synced() {
+            match ton_service
+                .sqlx_client
+                .get_addresses_by_account_type(AccountType::SafeMultisig)
+                .await
+            {
+                Ok(addresses) => {
+                    for address in addresses {
+                        if let Err(e) = ton_service.sync_account_multisig_proposals(&address).await
+                        {
+                            log::error!(
+                                "Failed to sync multisig proposals of `{}:{}`: {:?}",
+                                address.workchain_id,
+                                address.hex,
+                                e
+                            );
+                        }
+                    }
+                }
+                Err(e) => log::error!("Failed to get multisig addresses: {:?}", e),
             }
-        });
+        }
+
+        drop(ton_service);
+
+        tokio::time::sleep(Duration::from_secs(MULTISIG_PROPOSALS_POLL_INTERVAL)).await;
     }
 }
 
-async fn wait_message(
-    ton_service: Weak<TonService>,
-    transaction: TransactionDb,
-    rx: tokio::sync::oneshot::Receiver<MessageStatus>,
-) -> Result<(), Error> {
-    match rx.await? {
-        MessageStatus::Delivered => {
-            log::info!("Successfully sent message `{}`", transaction.message_hash)
+async fn reconcile_balances(ton_service: Weak<TonService>) -> Result<(), Error> {
+    loop {
+        let ton_service_ref = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
+        let config = ton_service_ref.reconciliation_config.clone();
+
+        // Network balances are read from the latest known account states
+        let mut mismatched = Vec::new();
+        if ton_service_ref.ton_api_client.is_synced() {
+            let batch_size = config.batch_size.max(1);
+            let mut after: Option<(i32, String)> = None;
+            loop {
+                let addresses = match ton_service_ref
+                    .sqlx_client
+                    .get_addresses_page(
+                        after
+                            .as_ref()
+                            .map(|(workchain_id, hex)| (*workchain_id, hex.as_str())),
+                        batch_size,
+                    )
+                    .await
+                {
+                    Ok(addresses) => addresses,
+                    Err(e) => {
+                        log::error!("Failed to get addresses: {:?}", e);
+                        break;
+                    }
+                };
+
+                let count = addresses.len() as i64;
+                after = addresses
+                    .last()
+                    .map(|address| (address.workchain_id, address.hex.clone()));
+
+                mismatched.extend(
+                    ton_service_ref
+                        .reconcile_addresses_balances(addresses, false)
+                        .await,
+                );
+
+                if count < batch_size {
+                    break;
+                }
+            }
         }
-        MessageStatus::Expired => {
-            let ton_service = match ton_service.upgrade() {
-                Some(ton_service) => ton_service,
-                None => return Err(TonServiceError::ServiceUnavailable.into()),
-            };
 
-            ton_service
-                .upsert_sent_transaction(
-                    transaction.message_hash,
-                    transaction.account_workchain_id,
-                    transaction.account_hex,
-                    UpdateSendTransaction::error("Expired".to_string()),
-                )
-                .await?;
+        drop(ton_service_ref);
+
+        // Transactions of mismatched accounts may be not processed yet
+        if !mismatched.is_empty() {
+            tokio::time::sleep(Duration::from_secs(config.recheck_delay_sec)).await;
         }
-    }
 
-    Ok(())
+        let ton_service_ref = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
+
+        ton_service_ref
+            .reconcile_addresses_balances(mismatched, true)
+            .await;
+
+        if let Err(e) = ton_service_ref.update_discrepancies_metrics().await {
+            log::error!("Failed to update balance discrepancies metrics: {:?}", e);
+        }
+
+        drop(ton_service_ref);
+
+        tokio::time::sleep(Duration::from_secs(config.interval_sec)).await;
+    }
 }
 
-async fn send_notification(
-    ton_service: Weak<TonService>,
-    service_id: ServiceId,
-    notify_type: NotifyType,
-    payload: AccountTransactionEvent,
-) -> Result<(), Error> {
-    let ton_service = match ton_service.upgrade() {
-        Some(ton_service) => ton_service,
-        None => return Err(TonServiceError::ServiceUnavailable.into()),
-    };
+async fn sweep_transfer_approvals(ton_service: Weak<TonService>) -> Result<(), Error> {
+    loop {
+        let ton_service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
 
-    let info = ton_service.get_blockchain_info().await?;
+        if let Err(e) = ton_service.sweep_transfer_approvals().await {
+            log::error!("Failed to sweep transfer approvals: {:?}", e);
+        }
 
-    let sqlx_client = &ton_service.sqlx_client;
-    let callback_client = &ton_service.callback_client;
+        drop(ton_service);
 
-    let url = sqlx_client.get_callback(service_id).await?;
-    let secret = sqlx_client
-        .get_key_by_service_id(&service_id)
-        .await
-        .map(|k| k.secret)?;
+        tokio::time::sleep(Duration::from_secs(TRANSFER_APPROVALS_SWEEP_INTERVAL)).await;
+    }
+}
 
-    let event_status = match callback_client
-        .send(info.network_id, url, payload.clone(), secret)
-        .await
-    {
-        Err(_) => TonEventStatus::Error,
-        Ok(_) => TonEventStatus::Notified,
-    };
+async fn cleanup_idempotency_keys(ton_service: Weak<TonService>) -> Result<(), Error> {
+    loop {
+        let ton_service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
 
-    match notify_type {
-        NotifyType::Transaction => {
-            sqlx_client
-                .update_event_status_of_transaction_event(
-                    payload.message_hash,
-                    payload.account.workchain_id,
-                    payload.account.hex.into(),
-                    event_status,
-                )
-                .await?;
+        let expired_before =
+            Utc::now().naive_utc() - chrono::Duration::seconds(IDEMPOTENCY_KEY_TTL);
+        match ton_service
+            .sqlx_client
+            .delete_expired_idempotency_keys(expired_before)
+            .await
+        {
+            Ok(deleted) if deleted > 0 => {
+                log::info!("Deleted {} expired idempotency keys", deleted)
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to delete expired idempotency keys: {:?}", e),
         }
-        NotifyType::TokenTransaction => {
-            sqlx_client
-                .update_event_status_of_token_transaction_event(
-                    payload.message_hash,
-                    payload.account.workchain_id,
-                    payload.account.hex.into(),
-                    event_status,
-                )
-                .await?;
+
+        drop(ton_service);
+
+        tokio::time::sleep(Duration::from_secs(IDEMPOTENCY_CLEANUP_INTERVAL)).await;
+    }
+}
+
+async fn cleanup_event_stream(ton_service: Weak<TonService>) -> Result<(), Error> {
+    loop {
+        let ton_service = match ton_service.upgrade() {
+            Some(ton_service) => ton_service,
+            None => return Err(TonServiceError::ServiceUnavailable.into()),
+        };
+
+        let expired_before = Utc::now().naive_utc() - chrono::Duration::seconds(EVENTS_STREAM_TTL);
+        match ton_service
+            .sqlx_client
+            .delete_expired_event_stream(expired_before)
+            .await
+        {
+            Ok(deleted) if deleted > 0 => {
+                log::info!("Deleted {} expired streamed events", deleted)
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to delete expired streamed events: {:?}", e),
         }
+
+        drop(ton_service);
+
+        tokio::time::sleep(Duration::from_secs(EVENTS_STREAM_CLEANUP_INTERVAL)).await;
     }
+}
 
-    Ok(())
+/// Exponential backoff with jitter in the upper half of the interval
+fn retry_delay(config: &WebhookConfig, attempts: u32) -> chrono::Duration {
+    let exp = attempts.saturating_sub(1).min(32);
+    let delay_ms = config
+        .initial_retry_delay_sec
+        .saturating_mul(1 << exp)
+        .min(config.max_retry_delay_sec)
+        .saturating_mul(1000);
+
+    let jitter = rand::thread_rng().gen_range(0..=delay_ms / 2);
+
+    chrono::Duration::milliseconds((delay_ms / 2 + jitter) as i64)
 }
 
 async fn send_transaction(
     ton_service: Weak<TonService>,
-    message_hash: String,
+    mut message_hash: String,
     account_hex: String,
     account_workchain_id: i32,
-    signed_message: SignedMessage,
+    mut signed_message: SignedMessage,
     with_db_update: bool,
 ) -> Result<(), Error> {
     let ton_service = match ton_service.upgrade() {
@@ -1317,27 +4306,143 @@ async fn send_transaction(
         None => return Err(TonServiceError::ServiceUnavailable.into()),
     };
 
-    let account = UInt256::from_be_bytes(&hex::decode(&account_hex)?);
-
-    let status = ton_service
-        .ton_api_client
-        .send_transaction(account, signed_message)
-        .await?;
+    let account = MsgAddressInt::from_str(&format!("{}:{}", account_workchain_id, account_hex))?;
 
-    if status == MessageStatus::Expired && with_db_update {
-        ton_service
-            .upsert_sent_transaction(
-                message_hash,
-                account_workchain_id,
-                account_hex,
-                UpdateSendTransaction::error("Expired".to_string()),
-            )
+    loop {
+        let status = ton_service
+            .ton_api_client
+            .send_transaction(account.clone(), signed_message)
             .await?;
+
+        if status != MessageStatus::Expired || !with_db_update {
+            break;
+        }
+
+        match ton_service
+            .handle_expired_message(message_hash, account_workchain_id, account_hex.clone())
+            .await?
+        {
+            Some(resent) => (message_hash, signed_message) = resent,
+            None => break,
+        }
     }
 
     Ok(())
 }
 
+fn parse_root_address(root_address: Option<Address>) -> Result<Option<String>, Error> {
+    let root_address = root_address
+        .map(|root_address| {
+            repack_address(&root_address.0)
+                .map(|root_address| root_address.to_string())
+                .map_err(|_| TonServiceError::WrongInput("Invalid root address".to_string()))
+        })
+        .transpose()?;
+
+    Ok(root_address)
+}
+
+fn parse_callback(input: CreateCallback) -> Result<(String, CallbackFilter), Error> {
+    reqwest::Url::parse(&input.callback)
+        .map_err(|_| TonServiceError::WrongInput("Invalid callback url".to_string()))?;
+
+    let root_address = input
+        .root_address
+        .map(|root_address| {
+            repack_address(&root_address)
+                .map(|root_address| root_address.to_string())
+                .map_err(|_| TonServiceError::WrongInput("Invalid root address".to_string()))
+        })
+        .transpose()?;
+
+    let account = input
+        .account
+        .map(|account| {
+            repack_address(&account)
+                .map_err(|_| TonServiceError::WrongInput("Invalid account".to_string()))
+        })
+        .transpose()?;
+
+    let filter = CallbackFilter {
+        notify_type: input.notify_type,
+        transaction_direction: input.transaction_direction,
+        root_address,
+        account_workchain_id: account.as_ref().map(|a| a.workchain_id()),
+        account_hex: account.map(|a| a.address().to_hex_string()),
+    };
+
+    Ok((input.callback, filter))
+}
+
+fn parse_spending_limit(
+    id: Uuid,
+    service_id: &ServiceId,
+    input: CreateSpendingLimit,
+) -> Result<SpendingLimitDb, Error> {
+    let limits = [
+        &input.max_per_transaction,
+        &input.hourly_limit,
+        &input.daily_limit,
+        &input.approval_threshold,
+    ];
+    for limit in limits.into_iter().flatten() {
+        let (_, scale) = limit.as_bigint_and_exponent();
+        if scale != 0 || limit < &BigDecimal::default() {
+            return Err(TonServiceError::WrongInput("Invalid limit".to_string()).into());
+        }
+    }
+
+    let root_address = input
+        .root_address
+        .map(|root_address| {
+            repack_address(&root_address)
+                .map(|root_address| root_address.to_string())
+                .map_err(|_| TonServiceError::WrongInput("Invalid root address".to_string()))
+        })
+        .transpose()?;
+
+    let account = input
+        .account
+        .map(|account| {
+            repack_address(&account)
+                .map_err(|_| TonServiceError::WrongInput("Invalid account".to_string()))
+        })
+        .transpose()?;
+
+    let allowed_recipients = input
+        .allowed_recipients
+        .map(|recipients| {
+            recipients
+                .iter()
+                .map(|recipient| {
+                    repack_address(recipient)
+                        .map(|recipient| recipient.to_string())
+                        .map_err(|_| {
+                            TonServiceError::WrongInput("Invalid recipient address".to_string())
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?;
+
+    let now = Utc::now().naive_utc();
+
+    Ok(SpendingLimitDb {
+        id,
+        service_id: *service_id,
+        account_workchain_id: account.as_ref().map(|a| a.workchain_id()),
+        account_hex: account.map(|a| a.address().to_hex_string()),
+        root_address,
+        max_per_transaction: input.max_per_transaction,
+        hourly_limit: input.hourly_limit,
+        daily_limit: input.daily_limit,
+        approval_threshold: input.approval_threshold,
+        allowed_recipients,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
 fn parse_abi_tokens(params: Vec<InputParam>) -> Result<Vec<Token>, Error> {
     let mut tokens = Vec::<Token>::new();
     for i in params {
@@ -1348,9 +4453,46 @@ fn parse_abi_tokens(params: Vec<InputParam>) -> Result<Vec<Token>, Error> {
     Ok(tokens)
 }
 
-enum NotifyType {
-    Transaction,
-    TokenTransaction,
+/// Result of the spending rules check of the outgoing transfer
+struct SpendingCheck {
+    approval_required: bool,
+    record_id: Uuid,
+    /// Set while the reservation is released on drop
+    sqlx_client: Option<SqlxClient>,
+}
+
+impl SpendingCheck {
+    fn new(approval_required: bool, record_id: Uuid, sqlx_client: SqlxClient) -> Self {
+        Self {
+            approval_required,
+            record_id,
+            sqlx_client: Some(sqlx_client),
+        }
+    }
+
+    /// Rejects the transfers above the approval threshold on the paths without approvals
+    fn without_approval(self) -> Result<Self, Error> {
+        if self.approval_required {
+            return Err(TonServiceError::LimitExceeded(
+                "approval threshold, the transfer can't be approved".to_string(),
+            )
+            .into());
+        }
+        Ok(self)
+    }
+}
+
+impl Drop for SpendingCheck {
+    fn drop(&mut self) {
+        if let Some(sqlx_client) = self.sqlx_client.take() {
+            let record_id = self.record_id;
+            tokio::spawn(async move {
+                if let Err(e) = sqlx_client.delete_spending_record(record_id).await {
+                    log::error!("Failed to release spending record `{}`: {:?}", record_id, e);
+                }
+            });
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -1363,17 +4505,94 @@ pub enum TonServiceError {
     InsufficientBalance,
     #[error("Execute contract")]
     ExecuteContract,
+    #[error("Signer unavailable")]
+    SignerUnavailable,
+    #[error("Address is watch-only")]
+    WatchOnlyAddress,
+    #[error("Root token `{0}` is not allowed for the service")]
+    RootTokenNotAllowed(String),
+    #[error("Spending limit exceeded: {0}")]
+    LimitExceeded(String),
+    #[error("Recipient `{0}` is not allowed")]
+    RecipientNotAllowed(String),
+    #[error("Transfer must be approved by another key")]
+    SelfApproval,
+    #[error("Transfer approval expired")]
+    ApprovalExpired,
 }
 
 impl TonServiceError {
     pub fn status_code(&self) -> StatusCode {
         match self {
-            TonServiceError::WrongInput(_) | TonServiceError::InsufficientBalance => {
-                StatusCode::BAD_REQUEST
-            }
-            TonServiceError::ServiceUnavailable | TonServiceError::ExecuteContract => {
-                StatusCode::INTERNAL_SERVER_ERROR
-            }
+            TonServiceError::WrongInput(_)
+            | TonServiceError::InsufficientBalance
+            | TonServiceError::WatchOnlyAddress
+            | TonServiceError::ApprovalExpired => StatusCode::BAD_REQUEST,
+            TonServiceError::RootTokenNotAllowed(_)
+            | TonServiceError::LimitExceeded(_)
+            | TonServiceError::RecipientNotAllowed(_)
+            | TonServiceError::SelfApproval => StatusCode::FORBIDDEN,
+            TonServiceError::ServiceUnavailable
+            | TonServiceError::ExecuteContract
+            | TonServiceError::SignerUnavailable => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_config() -> WebhookConfig {
+        WebhookConfig {
+            max_attempts: 10,
+            initial_retry_delay_sec: 5,
+            max_retry_delay_sec: 60,
+            ..Default::default()
+        }
+    }
+
+    fn assert_delay(delay: chrono::Duration, expected_sec: i64) {
+        let delay = delay.num_milliseconds();
+        assert!(
+            delay >= expected_sec * 500,
+            "{} < {}/2 sec",
+            delay,
+            expected_sec
+        );
+        assert!(
+            delay <= expected_sec * 1000,
+            "{} > {} sec",
+            delay,
+            expected_sec
+        );
+    }
+
+    #[test]
+    fn retry_delay_doubles() {
+        let config = make_config();
+
+        for _ in 0..100 {
+            assert_delay(retry_delay(&config, 1), 5);
+            assert_delay(retry_delay(&config, 2), 10);
+            assert_delay(retry_delay(&config, 3), 20);
+            assert_delay(retry_delay(&config, 4), 40);
+        }
+    }
+
+    #[test]
+    fn retry_delay_is_capped() {
+        let config = make_config();
+
+        for attempts in [5, 10, 32, 33, 64, u32::MAX] {
+            assert_delay(retry_delay(&config, attempts), 60);
         }
     }
+
+    #[test]
+    fn retry_delay_without_attempts() {
+        let config = make_config();
+
+        assert_delay(retry_delay(&config, 0), 5);
+    }
 }
@@ -7,6 +7,7 @@ use nekoton_utils::TrustMe;
 use serde::{Deserialize, Serialize};
 
 use crate::ton_core::*;
+use crate::utils::Keyring;
 
 #[derive(Serialize, Deserialize)]
 pub struct AppConfig {
@@ -19,15 +20,49 @@ pub struct AppConfig {
     /// Postgres connection pools.
     pub db_pool_size: u32,
 
-    /// Key to encrypt/decrypt
-    /// accounts private key in db
+    /// Legacy key to encrypt/decrypt
+    /// accounts private key in db.
+    /// Derived from `SECRET` and `SALT` env variables when not specified
     #[serde(default = "default_key")]
-    pub key: Vec<u8>,
+    pub key: Option<Vec<u8>>,
+
+    /// Versioned keys to encrypt/decrypt
+    /// accounts private key in db
+    #[serde(default)]
+    pub encryption_keys: Vec<EncryptionKeyConfig>,
+
+    /// Id of the key to encrypt new private keys with.
+    /// The last of `encryption_keys` when not specified
+    #[serde(default)]
+    pub active_encryption_key: Option<String>,
 
     /// TON node settings
     #[serde(default)]
     pub ton_core: NodeConfig,
 
+    /// Webhook delivery settings
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+
+    /// Ledger and network balances reconciliation settings
+    #[serde(default)]
+    pub reconciliation: ReconciliationConfig,
+
+    /// Admin API settings.
+    /// Completely disable when not specified
+    #[serde(default)]
+    pub admin_api: Option<AdminApiConfig>,
+
+    /// Whether still valid sent messages are broadcast again on startup.
+    /// Otherwise they are only waited for. Default: false
+    #[serde(default)]
+    pub rebroadcast_pending_messages: bool,
+
+    /// External signing daemon settings.
+    /// Addresses with the remote signer can't be created when not specified
+    #[serde(default)]
+    pub remote_signer: Option<RemoteSignerConfig>,
+
     /// API prometheus metrics exporter settings.
     /// Completely disable when not specified
     #[serde(default)]
@@ -44,6 +79,130 @@ pub struct AppConfig {
     pub logger_settings: serde_yaml::Value,
 }
 
+impl AppConfig {
+    pub fn keyring(&self) -> Result<Keyring> {
+        let keys = self
+            .encryption_keys
+            .iter()
+            .map(|key| Ok((key.id.clone(), derive_key(&key.secret, &key.salt)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Keyring::new(self.key.clone(), keys, self.active_encryption_key.clone())
+    }
+}
+
+/// Versioned key to encrypt/decrypt accounts private key
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EncryptionKeyConfig {
+    /// Key id stored next to the encrypted private key
+    pub id: String,
+
+    pub secret: String,
+
+    pub salt: String,
+}
+
+/// Admin API settings
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdminApiConfig {
+    /// Listen address of the admin API
+    pub server_addr: SocketAddr,
+
+    /// Bearer token expected in the `Authorization` header
+    pub auth_token: String,
+}
+
+/// External signing daemon settings
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteSignerConfig {
+    /// Base url of the signing daemon
+    pub url: String,
+
+    /// Bearer token sent with each request
+    #[serde(default)]
+    pub auth_token: Option<String>,
+
+    /// Request timeout. Default: 30
+    #[serde(default = "default_remote_signer_timeout")]
+    pub request_timeout_sec: u64,
+}
+
+fn default_remote_signer_timeout() -> u64 {
+    30
+}
+
+/// Webhook delivery settings
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// Attempts before the event is marked as `Error`. Default: 10
+    pub max_attempts: u32,
+
+    /// Delay before the first retry. Doubles on each next attempt. Default: 5
+    pub initial_retry_delay_sec: u64,
+
+    /// Upper bound of the retry delay. Default: 3600
+    pub max_retry_delay_sec: u64,
+
+    /// Pending deliveries polling interval. Default: 1000
+    pub poll_interval_ms: u64,
+
+    /// Deliveries processed at once. Default: 50
+    pub batch_size: i64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            initial_retry_delay_sec: 5,
+            max_retry_delay_sec: 3600,
+            poll_interval_ms: 1000,
+            batch_size: 50,
+        }
+    }
+}
+
+/// Ledger and network balances reconciliation settings
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReconciliationConfig {
+    /// Whether balances are reconciled periodically. Default: false
+    pub enabled: bool,
+
+    /// Interval between reconciliation runs. Default: 3600
+    pub interval_sec: u64,
+
+    /// Delay before a mismatched balance is checked again, so that
+    /// transactions which are being processed are not reported. Default: 60
+    pub recheck_delay_sec: u64,
+
+    /// Whether a rescan of the account is started for a new discrepancy. Default: false
+    pub backfill: bool,
+
+    /// Addresses read from the database at once. Default: 100
+    pub batch_size: i64,
+
+    /// Addresses checked at the same time. Default: 8
+    pub concurrency: usize,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_sec: 3600,
+            recheck_delay_sec: 60,
+            backfill: false,
+            batch_size: 100,
+            concurrency: 8,
+        }
+    }
+}
+
 impl ConfigExt for ton_indexer::GlobalConfig {
     fn from_file<P>(path: &P) -> Result<Self>
     where
@@ -62,34 +221,14 @@ pub trait ConfigExt: Sized {
         P: AsRef<Path>;
 }
 
-fn default_key() -> Vec<u8> {
-    fn key() -> Result<Vec<u8>> {
-        let secret = std::env::var("SECRET")?;
-        let salt = std::env::var("SALT")?;
-
-        let mut options = argon2::ParamsBuilder::default();
-        let options = options
-            .output_len(32) //chacha key size
-            .and_then(|x| x.clone().params())
-            .trust_me();
-
-        // Argon2 with default params (Argon2id v19)
-        let argon2 =
-            argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, options);
-
-        let key = argon2
-            .hash_password(secret.as_bytes(), &salt)
-            .trust_me()
-            .hash
-            .context("No hash")?
-            .as_bytes()
-            .into();
-
-        Ok(key)
-    }
+fn default_key() -> Option<Vec<u8>> {
+    let (secret, salt) = match (std::env::var("SECRET"), std::env::var("SALT")) {
+        (Ok(secret), Ok(salt)) => (secret, salt),
+        _ => return None,
+    };
 
-    match key() {
-        Ok(key) => key,
+    match derive_key(&secret, &salt) {
+        Ok(key) => Some(key),
         Err(err) => panic!(
             "Failed to get key to encrypt/decrypt private key: {:?}",
             err
@@ -97,6 +236,27 @@ fn default_key() -> Vec<u8> {
     }
 }
 
+fn derive_key(secret: &str, salt: &str) -> Result<Vec<u8>> {
+    let mut options = argon2::ParamsBuilder::default();
+    let options = options
+        .output_len(32) //chacha key size
+        .and_then(|x| x.clone().params())
+        .trust_me();
+
+    // Argon2 with default params (Argon2id v19)
+    let argon2 = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, options);
+
+    let key = argon2
+        .hash_password(secret.as_bytes(), salt)
+        .map_err(anyhow::Error::msg)?
+        .hash
+        .context("No hash")?
+        .as_bytes()
+        .into();
+
+    Ok(key)
+}
+
 fn default_logger_settings() -> serde_yaml::Value {
     const DEFAULT_LOG4RS_SETTINGS: &str = r##"
     appenders:
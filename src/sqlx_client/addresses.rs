@@ -1,4 +1,5 @@
 use anyhow::Result;
+use uuid::Uuid;
 
 use crate::models::*;
 use crate::sqlx_client::*;
@@ -7,10 +8,10 @@ impl SqlxClient {
     pub async fn create_address(&self, payload: CreateAddressInDb) -> Result<AddressDb> {
         sqlx::query_as!(AddressDb,
                 r#"INSERT INTO address
-                (id, service_id, workchain_id, hex, base64url, public_key, private_key, account_type, custodians, confirmations, custodians_public_keys)
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8::twa_account_type, $9, $10, $11)
+                (id, service_id, workchain_id, hex, base64url, public_key, private_key, account_type, custodians, confirmations, custodians_public_keys, signer_type, watch_only)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8::twa_account_type, $9, $10, $11, $12, $13)
                 RETURNING
-                id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
+                id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, signer_type as "signer_type: _", watch_only, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
 "#,
                 payload.id,
                 payload.service_id as ServiceId,
@@ -22,7 +23,9 @@ impl SqlxClient {
                 payload.account_type as AccountType,
                 payload.custodians,
                 payload.confirmations,
-                payload.custodians_public_keys
+                payload.custodians_public_keys,
+                payload.signer_type as SignerType,
+                payload.watch_only
             )
             .fetch_one(&self.pool)
             .await
@@ -36,7 +39,7 @@ impl SqlxClient {
         hex: String,
     ) -> Result<AddressDb> {
         sqlx::query_as!(AddressDb,
-                r#"SELECT id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
+                r#"SELECT id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, signer_type as "signer_type: _", watch_only, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
                 FROM address
                 WHERE service_id = $1 AND workchain_id = $2 AND hex = $3"#,
                 service_id as ServiceId,
@@ -54,7 +57,7 @@ impl SqlxClient {
         hex: String,
     ) -> Result<AddressDb> {
         sqlx::query_as!(AddressDb,
-                r#"SELECT id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
+                r#"SELECT id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, signer_type as "signer_type: _", watch_only, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
                 FROM address
                 WHERE workchain_id = $1 AND hex = $2"#,
                 workchain_id,
@@ -67,11 +70,87 @@ impl SqlxClient {
 
     pub async fn get_all_addresses(&self) -> Result<Vec<AddressDb>> {
         sqlx::query_as!(AddressDb,
-                r#"SELECT id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
+                r#"SELECT id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, signer_type as "signer_type: _", watch_only, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
                 FROM address"#
             )
             .fetch_all(&self.pool)
             .await
             .map_err(From::from)
     }
+
+    /// Addresses ordered by account, starting after the `after` one
+    pub async fn get_addresses_page(
+        &self,
+        after: Option<(i32, &str)>,
+        limit: i64,
+    ) -> Result<Vec<AddressDb>> {
+        let (after_workchain_id, after_hex) = after.unzip();
+        sqlx::query_as!(AddressDb,
+                r#"SELECT id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, signer_type as "signer_type: _", watch_only, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
+                FROM address
+                WHERE $1::integer IS NULL OR (workchain_id, hex) > ($1, $2)
+                ORDER BY workchain_id, hex
+                LIMIT $3"#,
+                after_workchain_id,
+                after_hex,
+                limit,
+            )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(From::from)
+    }
+
+    pub async fn get_addresses_by_account_type(
+        &self,
+        account_type: AccountType,
+    ) -> Result<Vec<AddressDb>> {
+        sqlx::query_as!(AddressDb,
+                r#"SELECT id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, signer_type as "signer_type: _", watch_only, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
+                FROM address
+                WHERE account_type = $1"#,
+                account_type as AccountType,
+            )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(From::from)
+    }
+
+    pub async fn get_addresses_batch(
+        &self,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<AddressDb>> {
+        sqlx::query_as!(AddressDb,
+                r#"SELECT id, service_id as "service_id: _", workchain_id, hex, base64url, public_key, private_key, signer_type as "signer_type: _", watch_only, account_type as "account_type: _", custodians, confirmations, custodians_public_keys, balance, created_at, updated_at
+                FROM address
+                WHERE ($1::uuid IS NULL OR id > $1) AND private_key IS NOT NULL
+                ORDER BY id
+                LIMIT $2"#,
+                after,
+                limit
+            )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(From::from)
+    }
+
+    /// Replaces the encrypted private key unless it was changed concurrently
+    pub async fn update_address_private_key(
+        &self,
+        id: Uuid,
+        old_private_key: &str,
+        new_private_key: &str,
+    ) -> Result<bool> {
+        let res = sqlx::query!(
+            r#"UPDATE address SET private_key = $3
+                WHERE id = $1 AND private_key = $2"#,
+            id,
+            old_private_key,
+            new_private_key
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
 }
@@ -1,4 +1,6 @@
 use anyhow::Result;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
 
 use crate::models::*;
 use crate::sqlx_client::*;
@@ -24,22 +26,222 @@ impl SqlxClient {
         .map_err(From::from)
     }
 
+    pub async fn get_api_services(&self) -> Result<Vec<ApiServiceDb>> {
+        sqlx::query_as!(
+            ApiServiceDb,
+            r#"SELECT id as "id: _", name, created_at
+                FROM api_service
+                ORDER BY created_at"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn get_api_service(&self, service_id: ServiceId) -> Result<ApiServiceDb> {
+        sqlx::query_as!(
+            ApiServiceDb,
+            r#"SELECT id as "id: _", name, created_at
+                FROM api_service
+                WHERE id = $1"#,
+            service_id as ServiceId,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn update_api_service(
+        &self,
+        service_id: ServiceId,
+        service_name: &str,
+    ) -> Result<ApiServiceDb> {
+        sqlx::query_as!(
+            ApiServiceDb,
+            r#"UPDATE api_service SET name = $2
+                WHERE id = $1
+                RETURNING
+                id as "id: _", name, created_at"#,
+            service_id as ServiceId,
+            service_name
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Deletes the service together with its keys and allowed tokens.
+    /// Fails if the service still has addresses or callbacks
+    pub async fn delete_api_service(
+        &self,
+        service_id: ServiceId,
+    ) -> Result<(ApiServiceDb, Vec<ApiServiceKeyDb>)> {
+        let mut tx = self.pool.begin().await?;
+
+        let keys = sqlx::query_as!(
+            ApiServiceKeyDb,
+            r#"DELETE FROM api_service_key
+                WHERE service_id = $1
+                RETURNING
+                id, service_id as "service_id: _", key, secret, whitelist, scopes, expires_at, created_at"#,
+            service_id as ServiceId,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"DELETE FROM api_service_root_token WHERE service_id = $1"#,
+            service_id as ServiceId,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"DELETE FROM spending_records WHERE service_id = $1"#,
+            service_id as ServiceId,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"DELETE FROM spending_limits WHERE service_id = $1"#,
+            service_id as ServiceId,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"DELETE FROM idempotency_keys WHERE service_id = $1"#,
+            service_id as ServiceId,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"DELETE FROM transfer_approvals WHERE service_id = $1"#,
+            service_id as ServiceId,
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"DELETE FROM multisig_proposals WHERE service_id = $1"#,
+            service_id as ServiceId,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let service = sqlx::query_as!(
+            ApiServiceDb,
+            r#"DELETE FROM api_service
+                WHERE id = $1
+                RETURNING
+                id as "id: _", name, created_at"#,
+            service_id as ServiceId,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok((service, keys))
+    }
+
     pub async fn create_api_service_key(
         &self,
         service_id: ServiceId,
         key: &str,
         secret: &str,
+        whitelist: Option<serde_json::Value>,
+        scopes: Option<Vec<String>>,
+        expires_at: Option<NaiveDateTime>,
     ) -> Result<ApiServiceKeyDb> {
         sqlx::query_as!(
             ApiServiceKeyDb,
             r#"INSERT INTO api_service_key
-                (service_id, key, secret)
-                VALUES ($1, $2, $3)
+                (service_id, key, secret, whitelist, scopes, expires_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
                 RETURNING
-                id, service_id as "service_id: _", key, secret, whitelist, created_at"#,
+                id, service_id as "service_id: _", key, secret, whitelist, scopes, expires_at, created_at"#,
             service_id as ServiceId,
             key,
-            secret
+            secret,
+            whitelist,
+            scopes.as_deref(),
+            expires_at,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn get_api_service_keys(
+        &self,
+        service_id: ServiceId,
+    ) -> Result<Vec<ApiServiceKeyDb>> {
+        sqlx::query_as!(
+            ApiServiceKeyDb,
+            r#"SELECT id, service_id as "service_id: _", key, secret, whitelist, scopes, expires_at, created_at
+                FROM api_service_key
+                WHERE service_id = $1
+                ORDER BY created_at"#,
+            service_id as ServiceId,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn update_api_service_key(
+        &self,
+        id: Uuid,
+        whitelist: Option<serde_json::Value>,
+        scopes: Option<Vec<String>>,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<ApiServiceKeyDb> {
+        sqlx::query_as!(
+            ApiServiceKeyDb,
+            r#"UPDATE api_service_key
+                SET (whitelist, scopes, expires_at) = (COALESCE($2, whitelist), COALESCE($3, scopes), COALESCE($4, expires_at))
+                WHERE id = $1
+                RETURNING
+                id, service_id as "service_id: _", key, secret, whitelist, scopes, expires_at, created_at"#,
+            id,
+            whitelist,
+            scopes.as_deref(),
+            expires_at,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn update_api_service_key_secret(
+        &self,
+        id: Uuid,
+        secret: &str,
+    ) -> Result<ApiServiceKeyDb> {
+        sqlx::query_as!(
+            ApiServiceKeyDb,
+            r#"UPDATE api_service_key SET secret = $2
+                WHERE id = $1
+                RETURNING
+                id, service_id as "service_id: _", key, secret, whitelist, scopes, expires_at, created_at"#,
+            id,
+            secret,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn delete_api_service_key(&self, id: Uuid) -> Result<ApiServiceKeyDb> {
+        sqlx::query_as!(
+            ApiServiceKeyDb,
+            r#"DELETE FROM api_service_key
+                WHERE id = $1
+                RETURNING
+                id, service_id as "service_id: _", key, secret, whitelist, scopes, expires_at, created_at"#,
+            id,
         )
         .fetch_one(&self.pool)
         .await
@@ -1,36 +1,132 @@
 use anyhow::Result;
+use uuid::Uuid;
 
 use crate::models::*;
 use crate::sqlx_client::*;
 
+use super::webhook_deliveries::fail_callback_webhook_deliveries;
+
 impl SqlxClient {
-    pub async fn get_callback(&self, service_id: ServiceId) -> Result<String> {
-        sqlx::query!(
-            r#"SELECT callback
+    pub async fn get_callbacks(&self, service_id: ServiceId) -> Result<Vec<ApiServiceCallbackDb>> {
+        sqlx::query_as!(
+            ApiServiceCallbackDb,
+            r#"SELECT id, service_id as "service_id: _", callback, notify_type as "notify_type: _",
+                transaction_direction as "transaction_direction: _", root_address,
+                account_workchain_id, account_hex, created_at, updated_at
+                FROM api_service_callback
+                WHERE service_id = $1
+                ORDER BY created_at"#,
+            service_id as ServiceId,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn get_callback_by_id(
+        &self,
+        service_id: ServiceId,
+        id: Uuid,
+    ) -> Result<ApiServiceCallbackDb> {
+        sqlx::query_as!(
+            ApiServiceCallbackDb,
+            r#"SELECT id, service_id as "service_id: _", callback, notify_type as "notify_type: _",
+                transaction_direction as "transaction_direction: _", root_address,
+                account_workchain_id, account_hex, created_at, updated_at
                 FROM api_service_callback
-                WHERE service_id = $1"#,
+                WHERE service_id = $1 AND id = $2"#,
             service_id as ServiceId,
+            id,
         )
         .fetch_one(&self.pool)
         .await
         .map_err(From::from)
-        .map(|x| x.callback)
     }
 
-    pub async fn set_callback(&self, payload: ApiServiceCallbackDb) -> Result<()> {
-        sqlx::query!(
+    pub async fn set_callback(
+        &self,
+        payload: ApiServiceCallbackDb,
+    ) -> Result<ApiServiceCallbackDb> {
+        sqlx::query_as!(
+            ApiServiceCallbackDb,
             r#"INSERT INTO api_service_callback
-                (id, service_id, callback, created_at) VALUES ($1, $2, $3, $4)
-                ON CONFLICT (service_id)
-                DO UPDATE SET callback = $3"#,
+                (id, service_id, callback, notify_type, transaction_direction, root_address,
+                account_workchain_id, account_hex, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                ON CONFLICT (service_id, callback)
+                DO UPDATE SET (notify_type, transaction_direction, root_address, account_workchain_id, account_hex, updated_at) =
+                ($4, $5, $6, $7, $8, $10)
+                RETURNING id, service_id as "service_id: _", callback, notify_type as "notify_type: _",
+                    transaction_direction as "transaction_direction: _", root_address,
+                    account_workchain_id, account_hex, created_at, updated_at"#,
             payload.id,
             payload.service_id as ServiceId,
             payload.callback,
+            payload.notify_type as Option<NotifyType>,
+            payload.transaction_direction as Option<TonTransactionDirection>,
+            payload.root_address,
+            payload.account_workchain_id,
+            payload.account_hex,
             payload.created_at,
+            payload.updated_at,
         )
-        .execute(&self.pool)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn update_callback(
+        &self,
+        payload: ApiServiceCallbackDb,
+    ) -> Result<ApiServiceCallbackDb> {
+        sqlx::query_as!(
+            ApiServiceCallbackDb,
+            r#"UPDATE api_service_callback SET
+                (callback, notify_type, transaction_direction, root_address, account_workchain_id, account_hex, updated_at) =
+                ($3, $4, $5, $6, $7, $8, $9)
+                WHERE service_id = $1 AND id = $2
+                RETURNING id, service_id as "service_id: _", callback, notify_type as "notify_type: _",
+                    transaction_direction as "transaction_direction: _", root_address,
+                    account_workchain_id, account_hex, created_at, updated_at"#,
+            payload.service_id as ServiceId,
+            payload.id,
+            payload.callback,
+            payload.notify_type as Option<NotifyType>,
+            payload.transaction_direction as Option<TonTransactionDirection>,
+            payload.root_address,
+            payload.account_workchain_id,
+            payload.account_hex,
+            payload.updated_at,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn delete_callback(
+        &self,
+        service_id: ServiceId,
+        id: Uuid,
+    ) -> Result<ApiServiceCallbackDb> {
+        let mut tx = self.pool.begin().await?;
+
+        fail_callback_webhook_deliveries(&mut tx, service_id, id).await?;
+
+        let callback = sqlx::query_as!(
+            ApiServiceCallbackDb,
+            r#"DELETE FROM api_service_callback
+                WHERE service_id = $1 AND id = $2
+                RETURNING id, service_id as "service_id: _", callback, notify_type as "notify_type: _",
+                    transaction_direction as "transaction_direction: _", root_address,
+                    account_workchain_id, account_hex, created_at, updated_at"#,
+            service_id as ServiceId,
+            id,
+        )
+        .fetch_one(&mut *tx)
         .await?;
 
-        Ok(())
+        tx.commit().await?;
+
+        Ok(callback)
     }
 }
@@ -0,0 +1,457 @@
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use uuid::Uuid;
+
+use crate::models::*;
+use crate::sqlx_client::*;
+
+impl SqlxClient {
+    /// Balance of the first recorded snapshot of the account plus balance changes of its later
+    /// transactions, or the sum of balance changes of all transactions if nothing was recorded
+    pub async fn get_ledger_balance(
+        &self,
+        account_workchain_id: i32,
+        account_hex: &str,
+    ) -> Result<BigDecimal> {
+        sqlx::query!(
+            r#"WITH start AS (
+                    SELECT balance, last_transaction_lt
+                    FROM balance_history
+                    WHERE account_workchain_id = $1 AND account_hex = $2 AND COALESCE(root_address, '') = ''
+                    ORDER BY last_transaction_lt
+                    LIMIT 1
+                )
+                SELECT COALESCE((SELECT balance FROM start), 0) + COALESCE(SUM(balance_change), 0) as "balance!"
+                FROM transactions
+                WHERE account_workchain_id = $1 AND account_hex = $2
+                    AND (NOT EXISTS (SELECT 1 FROM start) OR transaction_lt > (SELECT last_transaction_lt FROM start))"#,
+            account_workchain_id,
+            account_hex,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map(|row| row.balance)
+        .map_err(From::from)
+    }
+
+    /// Token balances of the owner per root address, counted in the same way as the native one.
+    /// Token transactions have no lt, so the ones of later blocks are taken by the block time
+    pub async fn get_token_ledger_balances(
+        &self,
+        account_workchain_id: i32,
+        account_hex: &str,
+    ) -> Result<Vec<(String, BigDecimal)>> {
+        sqlx::query!(
+            r#"WITH start AS (
+                    SELECT DISTINCT ON (root_address) root_address, balance, block_time
+                    FROM balance_history
+                    WHERE account_workchain_id = $1 AND account_hex = $2 AND root_address IS NOT NULL
+                    ORDER BY root_address, last_transaction_lt
+                ), changes AS (
+                    SELECT t.root_address, COALESCE(SUM(t.value) FILTER (WHERE t.status = 'Done'), 0) as value
+                    FROM token_transactions t
+                    LEFT JOIN start s ON s.root_address = t.root_address
+                    WHERE t.account_workchain_id = $1 AND t.account_hex = $2
+                        AND (s.block_time IS NULL OR t.block_time > EXTRACT(EPOCH FROM s.block_time))
+                    GROUP BY t.root_address
+                )
+                SELECT COALESCE(s.root_address, c.root_address) as "root_address!",
+                    COALESCE(s.balance, 0) + COALESCE(c.value, 0) as "balance!"
+                FROM start s
+                FULL JOIN changes c ON c.root_address = s.root_address"#,
+            account_workchain_id,
+            account_hex,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| (row.root_address, row.balance))
+                .collect()
+        })
+        .map_err(From::from)
+    }
+
+    /// Opens a discrepancy for the account balance or updates the balances
+    /// of the already open one. Returns whether a new discrepancy was opened
+    pub async fn upsert_balance_discrepancy(
+        &self,
+        payload: BalanceCheck,
+    ) -> Result<(BalanceDiscrepancyDb, bool)> {
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query_as!(
+            BalanceDiscrepancyDb,
+            r#"UPDATE balance_discrepancies
+                SET ledger_balance = $4, network_balance = $5, updated_at = current_timestamp
+                WHERE account_workchain_id = $1 AND account_hex = $2
+                    AND root_address IS NOT DISTINCT FROM $3 AND status = 'Open'
+                RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex, root_address,
+                    ledger_balance, network_balance, status as "status: _", rescan_requested, created_at, updated_at"#,
+            payload.account_workchain_id,
+            payload.account_hex,
+            payload.root_address,
+            payload.ledger_balance,
+            payload.network_balance,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let result = match updated {
+            Some(discrepancy) => (discrepancy, false),
+            None => {
+                let discrepancy = sqlx::query_as!(
+                    BalanceDiscrepancyDb,
+                    r#"INSERT INTO balance_discrepancies
+                        (id, service_id, account_workchain_id, account_hex, root_address, ledger_balance, network_balance)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7)
+                        RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex, root_address,
+                            ledger_balance, network_balance, status as "status: _", rescan_requested, created_at, updated_at"#,
+                    Uuid::new_v4(),
+                    payload.service_id as ServiceId,
+                    payload.account_workchain_id,
+                    payload.account_hex,
+                    payload.root_address,
+                    payload.ledger_balance,
+                    payload.network_balance,
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+                (discrepancy, true)
+            }
+        };
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
+    pub async fn resolve_balance_discrepancy(
+        &self,
+        account_workchain_id: i32,
+        account_hex: &str,
+        root_address: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE balance_discrepancies
+                SET status = 'Resolved', updated_at = current_timestamp
+                WHERE account_workchain_id = $1 AND account_hex = $2
+                    AND root_address IS NOT DISTINCT FROM $3 AND status = 'Open'"#,
+            account_workchain_id,
+            account_hex,
+            root_address,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_discrepancy_rescan_requested(&self, id: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE balance_discrepancies
+                SET rescan_requested = TRUE, updated_at = current_timestamp
+                WHERE id = $1"#,
+            id,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_balance_discrepancies(
+        &self,
+        service_id: ServiceId,
+        status: Option<DiscrepancyStatus>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<BalanceDiscrepancyDb>> {
+        sqlx::query_as!(
+            BalanceDiscrepancyDb,
+            r#"SELECT id, service_id as "service_id: _", account_workchain_id, account_hex, root_address,
+                ledger_balance, network_balance, status as "status: _", rescan_requested, created_at, updated_at
+                FROM balance_discrepancies
+                WHERE service_id = $1 AND ($2::twa_discrepancy_status IS NULL OR status = $2)
+                ORDER BY created_at DESC, id
+                LIMIT $3 OFFSET $4"#,
+            service_id as ServiceId,
+            status as Option<DiscrepancyStatus>,
+            limit,
+            offset,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Numbers of open native and token balance discrepancies
+    pub async fn count_open_balance_discrepancies(&self) -> Result<(i64, i64)> {
+        sqlx::query!(
+            r#"SELECT COUNT(*) FILTER (WHERE root_address IS NULL) as "ton!",
+                COUNT(*) FILTER (WHERE root_address IS NOT NULL) as "token!"
+                FROM balance_discrepancies
+                WHERE status = 'Open'"#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map(|row| (row.ton, row.token))
+        .map_err(From::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    const ROOT_ADDRESS: &str = "0:1111111111111111111111111111111111111111111111111111111111111111";
+
+    fn random_hash() -> String {
+        hex::encode(Uuid::new_v4().as_bytes()).repeat(2)
+    }
+
+    async fn receive(sqlx_client: &SqlxClient, address: &AddressDb, lt: i64, balance_change: i64) {
+        sqlx_client
+            .create_receive_transaction(
+                CreateReceiveTransaction {
+                    id: Uuid::new_v4(),
+                    message_hash: random_hash(),
+                    transaction_hash: Some(random_hash()),
+                    transaction_lt: Some(BigDecimal::from(lt)),
+                    transaction_timeout: None,
+                    transaction_scan_lt: None,
+                    transaction_timestamp: 1_700_000_000,
+                    sender_workchain_id: None,
+                    sender_hex: None,
+                    account_workchain_id: address.workchain_id,
+                    account_hex: address.hex.clone(),
+                    messages: None,
+                    messages_hash: None,
+                    data: None,
+                    original_value: None,
+                    original_outputs: None,
+                    value: Some(BigDecimal::from(balance_change)),
+                    fee: None,
+                    balance_change: Some(BigDecimal::from(balance_change)),
+                    direction: TonTransactionDirection::Receive,
+                    status: TonTransactionStatus::Done,
+                    error: None,
+                    aborted: false,
+                    bounce: false,
+                    multisig_transaction_id: None,
+                },
+                address.service_id,
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn receive_token(
+        sqlx_client: &SqlxClient,
+        address: &AddressDb,
+        block_time: i32,
+        value: i64,
+        status: TonTokenTransactionStatus,
+    ) {
+        sqlx_client
+            .create_token_transaction(
+                CreateTokenTransaction {
+                    id: Uuid::new_v4(),
+                    transaction_hash: Some(random_hash()),
+                    transaction_timestamp: block_time as u32,
+                    message_hash: random_hash(),
+                    owner_message_hash: None,
+                    account_workchain_id: address.workchain_id,
+                    account_hex: address.hex.clone(),
+                    sender_workchain_id: None,
+                    sender_hex: None,
+                    value: BigDecimal::from(value),
+                    root_address: ROOT_ADDRESS.to_owned(),
+                    payload: None,
+                    error: None,
+                    block_hash: random_hash(),
+                    block_time,
+                    direction: TonTransactionDirection::Receive,
+                    status,
+                    in_message_hash: None,
+                },
+                address.service_id,
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn snapshot(
+        sqlx_client: &SqlxClient,
+        address: &AddressDb,
+        root_address: Option<&str>,
+        balance: i64,
+        lt: i64,
+        block_time: i64,
+    ) {
+        sqlx_client
+            .create_balance_snapshot(CreateBalanceSnapshot {
+                account_workchain_id: address.workchain_id,
+                account_hex: address.hex.clone(),
+                root_address: root_address.map(ToOwned::to_owned),
+                balance: BigDecimal::from(balance),
+                last_transaction_lt: BigDecimal::from(lt),
+                account_status: AccountStatus::Active,
+                block_time: NaiveDateTime::from_timestamp_opt(block_time, 0).unwrap(),
+            })
+            .await
+            .unwrap();
+    }
+
+    async fn token_ledger_balance(sqlx_client: &SqlxClient, address: &AddressDb) -> BigDecimal {
+        let balances = sqlx_client
+            .get_token_ledger_balances(address.workchain_id, &address.hex)
+            .await
+            .unwrap();
+        assert_eq!(balances.len(), 1);
+        assert_eq!(balances[0].0, ROOT_ADDRESS);
+        balances[0].1.clone()
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn ledger_balance_starts_from_the_first_snapshot() {
+        let sqlx_client = test_client().await;
+        let address = create_test_address(&sqlx_client).await;
+
+        // Without snapshots all transactions are counted
+        receive(&sqlx_client, &address, 10, 100).await;
+        receive(&sqlx_client, &address, 20, 50).await;
+        assert_eq!(
+            sqlx_client
+                .get_ledger_balance(address.workchain_id, &address.hex)
+                .await
+                .unwrap(),
+            BigDecimal::from(150)
+        );
+
+        // The balance before the first snapshot includes the history which isn't tracked
+        snapshot(&sqlx_client, &address, None, 1000, 20, 1_700_000_000).await;
+        snapshot(&sqlx_client, &address, None, 1030, 30, 1_700_000_010).await;
+        receive(&sqlx_client, &address, 30, 30).await;
+        receive(&sqlx_client, &address, 40, 5).await;
+        assert_eq!(
+            sqlx_client
+                .get_ledger_balance(address.workchain_id, &address.hex)
+                .await
+                .unwrap(),
+            BigDecimal::from(1035)
+        );
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn token_ledger_balance_starts_from_the_first_snapshot() {
+        let sqlx_client = test_client().await;
+        let address = create_test_address(&sqlx_client).await;
+
+        // Without snapshots completed transactions are counted
+        receive_token(
+            &sqlx_client,
+            &address,
+            1_700_000_000,
+            7,
+            TonTokenTransactionStatus::Done,
+        )
+        .await;
+        receive_token(
+            &sqlx_client,
+            &address,
+            1_700_000_000,
+            9,
+            TonTokenTransactionStatus::Error,
+        )
+        .await;
+        assert_eq!(
+            token_ledger_balance(&sqlx_client, &address).await,
+            BigDecimal::from(7)
+        );
+
+        // Transactions of the snapshot block are already in its balance
+        snapshot(&sqlx_client, &address, None, 1000, 20, 1_700_000_000).await;
+        snapshot(
+            &sqlx_client,
+            &address,
+            Some(ROOT_ADDRESS),
+            100,
+            5,
+            1_700_000_000,
+        )
+        .await;
+        receive_token(
+            &sqlx_client,
+            &address,
+            1_700_000_010,
+            3,
+            TonTokenTransactionStatus::Done,
+        )
+        .await;
+        assert_eq!(
+            token_ledger_balance(&sqlx_client, &address).await,
+            BigDecimal::from(103)
+        );
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn discrepancy_is_opened_once_and_resolved() {
+        let sqlx_client = test_client().await;
+        let address = create_test_address(&sqlx_client).await;
+
+        let check = |network_balance: i64| BalanceCheck {
+            service_id: address.service_id,
+            account_workchain_id: address.workchain_id,
+            account_hex: address.hex.clone(),
+            root_address: None,
+            ledger_balance: BigDecimal::from(10),
+            network_balance: BigDecimal::from(network_balance),
+        };
+        assert!(check(10).is_consistent());
+        assert!(!check(12).is_consistent());
+
+        let (discrepancy, created) = sqlx_client
+            .upsert_balance_discrepancy(check(12))
+            .await
+            .unwrap();
+        assert!(created);
+        let (updated, created) = sqlx_client
+            .upsert_balance_discrepancy(check(15))
+            .await
+            .unwrap();
+        assert!(!created);
+        assert_eq!(updated.id, discrepancy.id);
+        assert_eq!(updated.network_balance, BigDecimal::from(15));
+
+        sqlx_client
+            .resolve_balance_discrepancy(address.workchain_id, &address.hex, None)
+            .await
+            .unwrap();
+        let (reopened, created) = sqlx_client
+            .upsert_balance_discrepancy(check(12))
+            .await
+            .unwrap();
+        assert!(created);
+
+        let open = sqlx_client
+            .get_balance_discrepancies(address.service_id, Some(DiscrepancyStatus::Open), 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].id, reopened.id);
+
+        let all = sqlx_client
+            .get_balance_discrepancies(address.service_id, None, 1, 1)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].id, discrepancy.id);
+        assert_eq!(all[0].status, DiscrepancyStatus::Resolved);
+    }
+}
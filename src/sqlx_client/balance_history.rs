@@ -0,0 +1,240 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::models::*;
+use crate::sqlx_client::*;
+
+impl SqlxClient {
+    /// Stores the snapshot if the account belongs to one of the services
+    pub async fn create_balance_snapshot(&self, payload: CreateBalanceSnapshot) -> Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO balance_history
+                (id, service_id, account_workchain_id, account_hex, root_address, balance, last_transaction_lt,
+                account_status, block_time)
+                SELECT $1, service_id, workchain_id, hex, $4, $5, $6, $7, $8
+                FROM address
+                WHERE workchain_id = $2 AND hex = $3
+                ON CONFLICT DO NOTHING"#,
+            Uuid::new_v4(),
+            payload.account_workchain_id,
+            payload.account_hex,
+            payload.root_address,
+            payload.balance,
+            payload.last_transaction_lt,
+            payload.account_status as AccountStatus,
+            payload.block_time,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The last snapshot made at or before `block_time`
+    pub async fn get_balance_snapshot_at(
+        &self,
+        service_id: ServiceId,
+        account_workchain_id: i32,
+        account_hex: &str,
+        root_address: Option<&str>,
+        block_time: NaiveDateTime,
+    ) -> Result<Option<BalanceSnapshotDb>> {
+        sqlx::query_as!(
+            BalanceSnapshotDb,
+            r#"SELECT id, service_id as "service_id: _", account_workchain_id, account_hex, root_address, balance,
+                last_transaction_lt, account_status as "account_status: _", block_time, created_at
+                FROM balance_history
+                WHERE service_id = $1 AND account_workchain_id = $2 AND account_hex = $3
+                    AND COALESCE(root_address, '') = COALESCE($4, '') AND block_time <= $5
+                ORDER BY block_time DESC, last_transaction_lt DESC
+                LIMIT 1"#,
+            service_id as ServiceId,
+            account_workchain_id,
+            account_hex,
+            root_address,
+            block_time,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Snapshots in the time range, oldest first
+    pub async fn get_balance_history(
+        &self,
+        service_id: ServiceId,
+        account_workchain_id: i32,
+        account_hex: &str,
+        root_address: Option<&str>,
+        from: Option<NaiveDateTime>,
+        to: Option<NaiveDateTime>,
+        limit: i64,
+    ) -> Result<Vec<BalanceSnapshotDb>> {
+        sqlx::query_as!(
+            BalanceSnapshotDb,
+            r#"SELECT id, service_id as "service_id: _", account_workchain_id, account_hex, root_address, balance,
+                last_transaction_lt, account_status as "account_status: _", block_time, created_at
+                FROM balance_history
+                WHERE service_id = $1 AND account_workchain_id = $2 AND account_hex = $3
+                    AND COALESCE(root_address, '') = COALESCE($4, '')
+                    AND ($5::TIMESTAMP IS NULL OR block_time >= $5)
+                    AND ($6::TIMESTAMP IS NULL OR block_time <= $6)
+                ORDER BY block_time, last_transaction_lt
+                LIMIT $7"#,
+            service_id as ServiceId,
+            account_workchain_id,
+            account_hex,
+            root_address,
+            from,
+            to,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bigdecimal::BigDecimal;
+
+    use super::*;
+
+    fn snapshot(
+        address: &AddressDb,
+        root_address: Option<&str>,
+        balance: i64,
+        lt: i64,
+        block_time: NaiveDateTime,
+    ) -> CreateBalanceSnapshot {
+        CreateBalanceSnapshot {
+            account_workchain_id: address.workchain_id,
+            account_hex: address.hex.clone(),
+            root_address: root_address.map(ToOwned::to_owned),
+            balance: BigDecimal::from(balance),
+            last_transaction_lt: BigDecimal::from(lt),
+            account_status: AccountStatus::Active,
+            block_time,
+        }
+    }
+
+    async fn balance_at(
+        sqlx_client: &SqlxClient,
+        address: &AddressDb,
+        root_address: Option<&str>,
+        block_time: NaiveDateTime,
+    ) -> Option<BigDecimal> {
+        sqlx_client
+            .get_balance_snapshot_at(
+                address.service_id,
+                address.workchain_id,
+                &address.hex,
+                root_address,
+                block_time,
+            )
+            .await
+            .unwrap()
+            .map(|snapshot| snapshot.balance)
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn balances_are_read_per_root_address() {
+        let sqlx_client = test_client().await;
+        let address = create_test_address(&sqlx_client).await;
+
+        let root_address = "0:1111111111111111111111111111111111111111111111111111111111111111";
+        let t0 = NaiveDateTime::from_timestamp_opt(1_700_000_000, 0).unwrap();
+        let t1 = t0 + chrono::Duration::minutes(1);
+        let t2 = t0 + chrono::Duration::minutes(2);
+
+        for payload in [
+            snapshot(&address, None, 100, 1, t0),
+            snapshot(&address, Some(root_address), 5, 2, t1),
+            snapshot(&address, None, 70, 3, t1),
+            snapshot(&address, None, 40, 4, t2),
+            // Repeated snapshots of one block are ignored
+            snapshot(&address, None, 40, 4, t2),
+        ] {
+            sqlx_client.create_balance_snapshot(payload).await.unwrap();
+        }
+
+        assert_eq!(
+            balance_at(
+                &sqlx_client,
+                &address,
+                None,
+                t0 - chrono::Duration::seconds(1)
+            )
+            .await,
+            None
+        );
+        assert_eq!(
+            balance_at(&sqlx_client, &address, None, t0).await,
+            Some(BigDecimal::from(100))
+        );
+        assert_eq!(
+            balance_at(&sqlx_client, &address, None, t1).await,
+            Some(BigDecimal::from(70))
+        );
+        assert_eq!(
+            balance_at(&sqlx_client, &address, None, t2).await,
+            Some(BigDecimal::from(40))
+        );
+        assert_eq!(
+            balance_at(&sqlx_client, &address, Some(root_address), t0).await,
+            None
+        );
+        assert_eq!(
+            balance_at(&sqlx_client, &address, Some(root_address), t2).await,
+            Some(BigDecimal::from(5))
+        );
+
+        let history = sqlx_client
+            .get_balance_history(
+                address.service_id,
+                address.workchain_id,
+                &address.hex,
+                None,
+                Some(t1),
+                None,
+                10,
+            )
+            .await
+            .unwrap();
+        let balances: Vec<_> = history.into_iter().map(|s| s.balance).collect();
+        assert_eq!(balances, [BigDecimal::from(70), BigDecimal::from(40)]);
+
+        let history = sqlx_client
+            .get_balance_history(
+                address.service_id,
+                address.workchain_id,
+                &address.hex,
+                None,
+                None,
+                Some(t1),
+                1,
+            )
+            .await
+            .unwrap();
+        let balances: Vec<_> = history.into_iter().map(|s| s.balance).collect();
+        assert_eq!(balances, [BigDecimal::from(100)]);
+
+        let history = sqlx_client
+            .get_balance_history(
+                address.service_id,
+                address.workchain_id,
+                &address.hex,
+                Some(root_address),
+                None,
+                None,
+                10,
+            )
+            .await
+            .unwrap();
+        let balances: Vec<_> = history.into_iter().map(|s| s.balance).collect();
+        assert_eq!(balances, [BigDecimal::from(5)]);
+    }
+}
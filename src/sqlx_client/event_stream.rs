@@ -0,0 +1,220 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::postgres::PgListener;
+use sqlx::PgConnection;
+
+use crate::models::*;
+use crate::sqlx_client::*;
+
+use super::token_whitelist::get_token_metadata;
+use super::webhook_deliveries::enqueue_webhook_deliveries;
+
+/// Channel notified with the sequence number of each appended event on commit
+const EVENT_STREAM_CHANNEL: &str = "twa_event_stream";
+
+/// Advisory lock serializing the appends, so that the events are committed in the order
+/// of their sequence numbers and a reader never sees a number before a smaller one
+const EVENT_STREAM_LOCK: i64 = 0x7477_615f_6576_656e;
+
+impl SqlxClient {
+    /// Subscribes to the events appended to the stream in the order of their commits
+    pub async fn listen_event_stream(&self) -> Result<PgListener> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(EVENT_STREAM_CHANNEL).await?;
+        Ok(listener)
+    }
+
+    pub async fn get_event_stream_entry(&self, seq: i64) -> Result<EventStreamDb> {
+        sqlx::query_as!(
+            EventStreamDb,
+            r#"SELECT seq, service_id as "service_id: _", event_id, notify_type as "notify_type: _", payload, created_at
+                FROM event_stream
+                WHERE seq = $1"#,
+            seq,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Returns events of the service appended after the specified sequence number
+    pub async fn get_event_stream_after(
+        &self,
+        service_id: ServiceId,
+        seq: i64,
+        limit: i64,
+    ) -> Result<Vec<EventStreamDb>> {
+        sqlx::query_as!(
+            EventStreamDb,
+            r#"SELECT seq, service_id as "service_id: _", event_id, notify_type as "notify_type: _", payload, created_at
+                FROM event_stream
+                WHERE service_id = $1 AND seq > $2
+                ORDER BY seq
+                LIMIT $3"#,
+            service_id as ServiceId,
+            seq,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn get_last_event_stream_seq(&self, service_id: ServiceId) -> Result<i64> {
+        let row = sqlx::query!(
+            r#"SELECT COALESCE(MAX(seq), 0) as "seq!" FROM event_stream WHERE service_id = $1"#,
+            service_id as ServiceId,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.seq)
+    }
+
+    pub async fn delete_expired_event_stream(&self, expired_before: NaiveDateTime) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"DELETE FROM event_stream WHERE created_at < $1"#,
+            expired_before,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Queues webhook deliveries of the event and appends it to the events stream.
+/// Must be called within the transaction which stores the event
+pub(super) async fn publish_event(
+    conn: &mut PgConnection,
+    service_id: ServiceId,
+    notify_type: NotifyType,
+    event: &AccountTransactionEvent,
+) -> Result<()> {
+    // Token amounts are also delivered in tokens
+    let metadata = match &event.root_address {
+        Some(root_address) => get_token_metadata(&mut *conn, root_address).await?,
+        None => None,
+    };
+    let event = &event.clone().with_metadata(metadata);
+
+    enqueue_webhook_deliveries(&mut *conn, service_id, notify_type, event).await?;
+
+    // Held until the transaction ends
+    sqlx::query!(
+        r#"SELECT FROM pg_advisory_xact_lock($1)"#,
+        EVENT_STREAM_LOCK
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // Token events of the roots not allowed for the service are not streamed
+    let seq = sqlx::query_scalar!(
+        r#"
+        INSERT INTO event_stream (service_id, event_id, notify_type, payload)
+        SELECT $1, $2, $3, $4
+        WHERE $5::VARCHAR IS NULL
+            OR NOT EXISTS (SELECT 1 FROM api_service WHERE id = $1 AND restrict_root_tokens)
+            OR EXISTS (SELECT 1 FROM api_service_root_token WHERE service_id = $1 AND root_address = $5)
+        RETURNING seq"#,
+        service_id as ServiceId,
+        event.id,
+        notify_type as NotifyType,
+        serde_json::to_value(event)?,
+        event.root_address,
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if let Some(seq) = seq {
+        sqlx::query!(
+            r#"SELECT FROM pg_notify($1, $2)"#,
+            EVENT_STREAM_CHANNEL,
+            seq.to_string(),
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn event(address: &AddressDb) -> AccountTransactionEvent {
+        AccountTransactionEvent {
+            id: Uuid::new_v4(),
+            transaction_id: Uuid::new_v4(),
+            transaction_hash: None,
+            message_hash: hex::encode(Uuid::new_v4().as_bytes()).repeat(2),
+            owner_message_hash: None,
+            account: address.clone().into(),
+            sender: None,
+            balance_change: None,
+            root_address: None,
+            symbol: None,
+            decimals: None,
+            human_balance_change: None,
+            transaction_direction: TonTransactionDirection::Receive,
+            transaction_status: TonTransactionStatus::Done,
+            event_status: TonEventStatus::New,
+            multisig_transaction_id: None,
+            created_at: 0,
+            updated_at: 0,
+            deliveries: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn events_are_committed_in_sequence_order() {
+        let sqlx_client = test_client().await;
+        let address = create_test_address(&sqlx_client).await;
+        let service_id = address.service_id;
+
+        let first = event(&address);
+        let mut first_tx = sqlx_client.pool.begin().await.unwrap();
+        publish_event(&mut first_tx, service_id, NotifyType::Transaction, &first)
+            .await
+            .unwrap();
+
+        // The second transaction tries to commit while the first one is still open
+        let second = event(&address);
+        let second_task = tokio::spawn({
+            let sqlx_client = sqlx_client.clone();
+            let second = second.clone();
+            async move {
+                let mut second_tx = sqlx_client.pool.begin().await.unwrap();
+                publish_event(&mut second_tx, service_id, NotifyType::Transaction, &second)
+                    .await
+                    .unwrap();
+                second_tx.commit().await.unwrap();
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!second_task.is_finished());
+        assert!(sqlx_client
+            .get_event_stream_after(service_id, 0, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        first_tx.commit().await.unwrap();
+        second_task.await.unwrap();
+
+        let events = sqlx_client
+            .get_event_stream_after(service_id, 0, 10)
+            .await
+            .unwrap();
+        let ids: Vec<_> = events.iter().map(|event| event.event_id).collect();
+        assert_eq!(ids, [first.id, second.id]);
+        assert!(events[0].seq < events[1].seq);
+    }
+}
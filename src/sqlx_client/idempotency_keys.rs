@@ -0,0 +1,159 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+
+use crate::models::*;
+use crate::sqlx_client::*;
+
+impl SqlxClient {
+    /// Stores the key without a response. A key created before `expired_before` is replaced,
+    /// a key still without a response is never taken over by another request.
+    /// Returns `false` if the key is already in use
+    pub async fn insert_idempotency_key(
+        &self,
+        service_id: ServiceId,
+        key: &str,
+        request_hash: &str,
+        expired_before: NaiveDateTime,
+    ) -> Result<bool> {
+        let row = sqlx::query!(
+            r#"INSERT INTO idempotency_keys (service_id, key, request_hash)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (service_id, key) DO UPDATE
+                SET (request_hash, response_status, response_body, created_at) =
+                    ($3, NULL, NULL, current_timestamp)
+                WHERE idempotency_keys.created_at < $4
+                RETURNING service_id"#,
+            service_id as ServiceId,
+            key,
+            request_hash,
+            expired_before,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    pub async fn get_idempotency_key(
+        &self,
+        service_id: ServiceId,
+        key: &str,
+    ) -> Result<IdempotencyKeyDb> {
+        sqlx::query_as!(
+            IdempotencyKeyDb,
+            r#"SELECT service_id as "service_id: _", key, request_hash, response_status,
+                response_body, created_at
+                FROM idempotency_keys
+                WHERE service_id = $1 AND key = $2"#,
+            service_id as ServiceId,
+            key,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn update_idempotency_key_response(
+        &self,
+        service_id: ServiceId,
+        key: &str,
+        response_status: i32,
+        response_body: &[u8],
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE idempotency_keys SET (response_status, response_body) = ($3, $4)
+                WHERE service_id = $1 AND key = $2"#,
+            service_id as ServiceId,
+            key,
+            response_status,
+            response_body,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_expired_idempotency_keys(
+        &self,
+        expired_before: NaiveDateTime,
+    ) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"DELETE FROM idempotency_keys WHERE created_at < $1"#,
+            expired_before,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn idempotency_key_is_replaced_once_expired() {
+        let sqlx_client = test_client().await;
+
+        let service_id = ServiceId::generate();
+        sqlx_client
+            .create_api_service(service_id, "idempotency test")
+            .await
+            .unwrap();
+
+        let now = Utc::now().naive_utc();
+        let long_ago = now - Duration::hours(1);
+        let later = now + Duration::minutes(1);
+
+        // A new key is stored, a repeated one is in use
+        assert!(sqlx_client
+            .insert_idempotency_key(service_id, "key", "hash", long_ago)
+            .await
+            .unwrap());
+        assert!(!sqlx_client
+            .insert_idempotency_key(service_id, "key", "other", long_ago)
+            .await
+            .unwrap());
+        let stored = sqlx_client
+            .get_idempotency_key(service_id, "key")
+            .await
+            .unwrap();
+        assert_eq!(stored.request_hash, "hash");
+        assert_eq!(stored.response_status, None);
+
+        // A server error is kept like any other response
+        sqlx_client
+            .update_idempotency_key_response(service_id, "key", 500, b"{}")
+            .await
+            .unwrap();
+        assert!(!sqlx_client
+            .insert_idempotency_key(service_id, "key", "hash", long_ago)
+            .await
+            .unwrap());
+        let stored = sqlx_client
+            .get_idempotency_key(service_id, "key")
+            .await
+            .unwrap();
+        assert_eq!(stored.request_hash, "hash");
+        assert_eq!(stored.response_status, Some(500));
+
+        assert!(sqlx_client
+            .insert_idempotency_key(service_id, "key", "expired", later)
+            .await
+            .unwrap());
+        let stored = sqlx_client
+            .get_idempotency_key(service_id, "key")
+            .await
+            .unwrap();
+        assert_eq!(stored.request_hash, "expired");
+        assert_eq!(stored.response_status, None);
+        assert_eq!(stored.response_body, None);
+
+        sqlx_client.delete_api_service(service_id).await.unwrap();
+    }
+}
@@ -12,6 +12,8 @@ impl SqlxClient {
                     key,
                     secret,
                     whitelist,
+                    scopes,
+                    expires_at,
                     created_at
                     FROM api_service_key WHERE key = $1"#,
             &api_key
@@ -28,6 +30,8 @@ impl SqlxClient {
                     key,
                     secret,
                     whitelist,
+                    scopes,
+                    expires_at,
                     created_at
                     FROM api_service_key WHERE service_id = $1"#,
             service_id.0
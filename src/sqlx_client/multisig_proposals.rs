@@ -0,0 +1,176 @@
+use anyhow::Result;
+use uuid::Uuid;
+
+use crate::models::*;
+use crate::sqlx_client::*;
+
+use super::event_stream::publish_event;
+
+impl SqlxClient {
+    pub async fn create_multisig_proposal(
+        &self,
+        payload: CreateMultisigProposal,
+    ) -> Result<MultisigProposalDb> {
+        let mut tx = self.pool.begin().await?;
+
+        let proposal = sqlx::query_as!(
+            MultisigProposalDb,
+            r#"INSERT INTO multisig_proposals
+                (id, service_id, account_workchain_id, account_hex, transaction_id, creator, destination, value, bounce,
+                signs_required, signs_received, confirmations, expire_at, status)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+                RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex, transaction_id, creator,
+                    destination, value, bounce, signs_required, signs_received, confirmations, expire_at,
+                    status as "status: _", created_at, updated_at"#,
+            payload.id,
+            payload.service_id as ServiceId,
+            payload.account_workchain_id,
+            payload.account_hex,
+            payload.transaction_id,
+            payload.creator,
+            payload.destination,
+            payload.value,
+            payload.bounce,
+            payload.signs_required,
+            payload.signs_received,
+            payload.confirmations.as_slice(),
+            payload.expire_at,
+            MultisigProposalStatus::Pending as MultisigProposalStatus,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        publish_event(
+            &mut tx,
+            proposal.service_id,
+            NotifyType::Transaction,
+            &proposal.event()?,
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(proposal)
+    }
+
+    pub async fn get_multisig_proposal(
+        &self,
+        service_id: ServiceId,
+        id: Uuid,
+    ) -> Result<MultisigProposalDb> {
+        sqlx::query_as!(
+            MultisigProposalDb,
+            r#"SELECT id, service_id as "service_id: _", account_workchain_id, account_hex, transaction_id, creator,
+                destination, value, bounce, signs_required, signs_received, confirmations, expire_at,
+                status as "status: _", created_at, updated_at
+                FROM multisig_proposals
+                WHERE service_id = $1 AND id = $2"#,
+            service_id as ServiceId,
+            id,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Proposals of the service, of all its addresses if `account` is `None`
+    pub async fn get_multisig_proposals(
+        &self,
+        service_id: ServiceId,
+        account: Option<(i32, &str)>,
+        status: Option<MultisigProposalStatus>,
+    ) -> Result<Vec<MultisigProposalDb>> {
+        let (account_workchain_id, account_hex) = match account {
+            Some((workchain_id, hex)) => (Some(workchain_id), Some(hex)),
+            None => (None, None),
+        };
+
+        sqlx::query_as!(
+            MultisigProposalDb,
+            r#"SELECT id, service_id as "service_id: _", account_workchain_id, account_hex, transaction_id, creator,
+                destination, value, bounce, signs_required, signs_received, confirmations, expire_at,
+                status as "status: _", created_at, updated_at
+                FROM multisig_proposals
+                WHERE service_id = $1
+                    AND ($2::INT IS NULL OR (account_workchain_id = $2 AND account_hex = $3))
+                    AND ($4::twa_multisig_proposal_status IS NULL OR status = $4)
+                ORDER BY created_at DESC"#,
+            service_id as ServiceId,
+            account_workchain_id,
+            account_hex,
+            status as Option<MultisigProposalStatus>,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Proposals of the account which were still pending in the contract at the last sync
+    pub async fn get_open_multisig_proposals(
+        &self,
+        account_workchain_id: i32,
+        account_hex: &str,
+    ) -> Result<Vec<MultisigProposalDb>> {
+        sqlx::query_as!(
+            MultisigProposalDb,
+            r#"SELECT id, service_id as "service_id: _", account_workchain_id, account_hex, transaction_id, creator,
+                destination, value, bounce, signs_required, signs_received, confirmations, expire_at,
+                status as "status: _", created_at, updated_at
+                FROM multisig_proposals
+                WHERE account_workchain_id = $1 AND account_hex = $2
+                    AND status IN ('Pending', 'Rejected')"#,
+            account_workchain_id,
+            account_hex,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn update_multisig_proposal_confirmations(
+        &self,
+        id: Uuid,
+        signs_received: i32,
+        confirmations: &[String],
+    ) -> Result<MultisigProposalDb> {
+        sqlx::query_as!(
+            MultisigProposalDb,
+            r#"UPDATE multisig_proposals
+                SET (signs_received, confirmations, updated_at) = ($2, $3, current_timestamp)
+                WHERE id = $1
+                RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex, transaction_id, creator,
+                    destination, value, bounce, signs_required, signs_received, confirmations, expire_at,
+                    status as "status: _", created_at, updated_at"#,
+            id,
+            signs_received,
+            confirmations,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Moves the proposal from the `from` status, returns `None` if its status was different
+    pub async fn update_multisig_proposal_status(
+        &self,
+        id: Uuid,
+        from: MultisigProposalStatus,
+        to: MultisigProposalStatus,
+    ) -> Result<Option<MultisigProposalDb>> {
+        sqlx::query_as!(
+            MultisigProposalDb,
+            r#"UPDATE multisig_proposals
+                SET (status, updated_at) = ($3, current_timestamp)
+                WHERE id = $1 AND status = $2
+                RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex, transaction_id, creator,
+                    destination, value, bounce, signs_required, signs_received, confirmations, expire_at,
+                    status as "status: _", created_at, updated_at"#,
+            id,
+            from as MultisigProposalStatus,
+            to as MultisigProposalStatus,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+}
@@ -0,0 +1,152 @@
+use anyhow::Result;
+use uuid::Uuid;
+
+use crate::models::*;
+use crate::sqlx_client::*;
+
+impl SqlxClient {
+    pub async fn create_payout_batch(&self, payload: CreatePayoutBatch) -> Result<PayoutBatch> {
+        let mut tx = self.pool.begin().await?;
+
+        let batch = sqlx::query_as!(
+            PayoutBatchDb,
+            r#"
+            INSERT INTO payout_batches
+            (id, service_id, account_workchain_id, account_hex, bounce, payload)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex,
+                bounce, payload, status as "status: _", created_at, updated_at"#,
+            payload.id,
+            payload.service_id as ServiceId,
+            payload.account_workchain_id,
+            payload.account_hex,
+            payload.bounce,
+            payload.payload,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let mut outputs = Vec::with_capacity(payload.outputs.len());
+        for output in payload.outputs {
+            let output = sqlx::query_as!(
+                PayoutOutputDb,
+                r#"
+                INSERT INTO payout_outputs
+                (batch_id, position, chunk, transaction_id, output)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING batch_id, position, chunk, transaction_id, output,
+                    status as "status: _", error, updated_at"#,
+                batch.id,
+                output.position,
+                output.chunk,
+                output.transaction_id,
+                output.output,
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            outputs.push(output);
+        }
+
+        tx.commit().await?;
+
+        Ok(PayoutBatch { batch, outputs })
+    }
+
+    pub async fn get_payout_batch(
+        &self,
+        service_id: ServiceId,
+        id: &Uuid,
+    ) -> Result<Option<PayoutBatch>> {
+        let batch = match sqlx::query_as!(
+            PayoutBatchDb,
+            r#"
+            SELECT id, service_id as "service_id: _", account_workchain_id, account_hex,
+                bounce, payload, status as "status: _", created_at, updated_at
+            FROM payout_batches
+            WHERE service_id = $1 AND id = $2"#,
+            service_id as ServiceId,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        {
+            Some(batch) => batch,
+            None => return Ok(None),
+        };
+
+        let outputs = self.get_payout_outputs(&batch.id).await?;
+
+        Ok(Some(PayoutBatch { batch, outputs }))
+    }
+
+    pub async fn get_in_progress_payout_batches(&self) -> Result<Vec<PayoutBatchDb>> {
+        sqlx::query_as!(
+            PayoutBatchDb,
+            r#"
+            SELECT id, service_id as "service_id: _", account_workchain_id, account_hex,
+                bounce, payload, status as "status: _", created_at, updated_at
+            FROM payout_batches
+            WHERE status = 'InProgress'::twa_payout_batch_status
+            ORDER BY created_at"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn get_payout_outputs(&self, batch_id: &Uuid) -> Result<Vec<PayoutOutputDb>> {
+        sqlx::query_as!(
+            PayoutOutputDb,
+            r#"
+            SELECT batch_id, position, chunk, transaction_id, output,
+                status as "status: _", error, updated_at
+            FROM payout_outputs
+            WHERE batch_id = $1
+            ORDER BY position"#,
+            batch_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Updates all outputs of the chunk sent by the transaction
+    pub async fn update_payout_outputs(
+        &self,
+        transaction_id: &Uuid,
+        status: PayoutOutputStatus,
+        error: Option<String>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE payout_outputs SET (status, error, updated_at) = ($2, $3, current_timestamp)
+            WHERE transaction_id = $1"#,
+            transaction_id,
+            status as PayoutOutputStatus,
+            error,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_payout_batch_status(
+        &self,
+        id: &Uuid,
+        status: PayoutBatchStatus,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE payout_batches SET (status, updated_at) = ($2, current_timestamp)
+            WHERE id = $1"#,
+            id,
+            status as PayoutBatchStatus,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
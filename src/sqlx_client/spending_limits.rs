@@ -0,0 +1,225 @@
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::models::*;
+use crate::sqlx_client::*;
+
+impl SqlxClient {
+    pub async fn get_spending_limits(&self, service_id: ServiceId) -> Result<Vec<SpendingLimitDb>> {
+        sqlx::query_as!(
+            SpendingLimitDb,
+            r#"SELECT id, service_id as "service_id: _", account_workchain_id, account_hex, root_address,
+                max_per_transaction, hourly_limit, daily_limit, approval_threshold, allowed_recipients, created_at, updated_at
+                FROM spending_limits
+                WHERE service_id = $1
+                ORDER BY created_at"#,
+            service_id as ServiceId,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Rules of the service which apply to transfers of the account,
+    /// native ones if `root_address` is `None`
+    pub async fn get_account_spending_limits(
+        &self,
+        service_id: ServiceId,
+        account_workchain_id: i32,
+        account_hex: &str,
+        root_address: Option<&str>,
+    ) -> Result<Vec<SpendingLimitDb>> {
+        sqlx::query_as!(
+            SpendingLimitDb,
+            r#"SELECT id, service_id as "service_id: _", account_workchain_id, account_hex, root_address,
+                max_per_transaction, hourly_limit, daily_limit, approval_threshold, allowed_recipients, created_at, updated_at
+                FROM spending_limits
+                WHERE service_id = $1
+                    AND (account_hex IS NULL OR (account_workchain_id = $2 AND account_hex = $3))
+                    AND root_address IS NOT DISTINCT FROM $4"#,
+            service_id as ServiceId,
+            account_workchain_id,
+            account_hex,
+            root_address,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn create_spending_limit(&self, payload: SpendingLimitDb) -> Result<SpendingLimitDb> {
+        sqlx::query_as!(
+            SpendingLimitDb,
+            r#"INSERT INTO spending_limits
+                (id, service_id, account_workchain_id, account_hex, root_address,
+                max_per_transaction, hourly_limit, daily_limit, approval_threshold, allowed_recipients)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex, root_address,
+                    max_per_transaction, hourly_limit, daily_limit, approval_threshold, allowed_recipients, created_at, updated_at"#,
+            payload.id,
+            payload.service_id as ServiceId,
+            payload.account_workchain_id,
+            payload.account_hex,
+            payload.root_address,
+            payload.max_per_transaction,
+            payload.hourly_limit,
+            payload.daily_limit,
+            payload.approval_threshold,
+            payload.allowed_recipients.as_deref(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn update_spending_limit(&self, payload: SpendingLimitDb) -> Result<SpendingLimitDb> {
+        sqlx::query_as!(
+            SpendingLimitDb,
+            r#"UPDATE spending_limits SET (account_workchain_id, account_hex, root_address,
+                max_per_transaction, hourly_limit, daily_limit, approval_threshold, allowed_recipients, updated_at) =
+                ($3, $4, $5, $6, $7, $8, $9, $10, current_timestamp)
+                WHERE service_id = $1 AND id = $2
+                RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex, root_address,
+                    max_per_transaction, hourly_limit, daily_limit, approval_threshold, allowed_recipients, created_at, updated_at"#,
+            payload.service_id as ServiceId,
+            payload.id,
+            payload.account_workchain_id,
+            payload.account_hex,
+            payload.root_address,
+            payload.max_per_transaction,
+            payload.hourly_limit,
+            payload.daily_limit,
+            payload.approval_threshold,
+            payload.allowed_recipients.as_deref(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn delete_spending_limit(
+        &self,
+        service_id: ServiceId,
+        id: Uuid,
+    ) -> Result<SpendingLimitDb> {
+        sqlx::query_as!(
+            SpendingLimitDb,
+            r#"DELETE FROM spending_limits
+                WHERE service_id = $1 AND id = $2
+                RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex, root_address,
+                    max_per_transaction, hourly_limit, daily_limit, approval_threshold, allowed_recipients, created_at, updated_at"#,
+            service_id as ServiceId,
+            id,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn create_spending_record(&self, payload: SpendingRecordDb) -> Result<()> {
+        sqlx::query!(
+            r#"INSERT INTO spending_records
+                (id, service_id, account_workchain_id, account_hex, root_address, value, transaction_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+            payload.id,
+            payload.service_id as ServiceId,
+            payload.account_workchain_id,
+            payload.account_hex,
+            payload.root_address,
+            payload.value,
+            payload.transaction_id,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_spending_record_transaction(
+        &self,
+        id: Uuid,
+        transaction_id: Uuid,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE spending_records SET transaction_id = $2 WHERE id = $1"#,
+            id,
+            transaction_id,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_spending_record(&self, id: Uuid) -> Result<()> {
+        sqlx::query!(r#"DELETE FROM spending_records WHERE id = $1"#, id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Total value sent by the service since `since`, by the account only if specified.
+    /// Transfers which failed to be sent are not counted
+    pub async fn get_sent_value(
+        &self,
+        service_id: ServiceId,
+        account: Option<(i32, &str)>,
+        since: NaiveDateTime,
+    ) -> Result<BigDecimal> {
+        let account_workchain_id = account.map(|(workchain_id, _)| workchain_id);
+        let account_hex = account.map(|(_, hex)| hex);
+
+        let row = sqlx::query!(
+            r#"SELECT COALESCE(SUM(r.value), 0) as "value!"
+                FROM spending_records r
+                LEFT JOIN transactions t ON t.id = r.transaction_id
+                WHERE r.service_id = $1 AND r.root_address IS NULL
+                    AND ($2::INT IS NULL OR (r.account_workchain_id = $2 AND r.account_hex = $3))
+                    AND r.created_at >= $4
+                    AND (t.status IS NULL OR t.status != 'Error')"#,
+            service_id as ServiceId,
+            account_workchain_id,
+            account_hex,
+            since,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.value)
+    }
+
+    /// Total amount of tokens sent by the service since `since`, by the account only if specified.
+    /// Transfers which failed to be sent are not counted
+    pub async fn get_sent_token_value(
+        &self,
+        service_id: ServiceId,
+        account: Option<(i32, &str)>,
+        root_address: &str,
+        since: NaiveDateTime,
+    ) -> Result<BigDecimal> {
+        let account_workchain_id = account.map(|(workchain_id, _)| workchain_id);
+        let account_hex = account.map(|(_, hex)| hex);
+
+        let row = sqlx::query!(
+            r#"SELECT COALESCE(SUM(r.value), 0) as "value!"
+                FROM spending_records r
+                LEFT JOIN transactions t ON t.id = r.transaction_id
+                WHERE r.service_id = $1 AND r.root_address = $2
+                    AND ($3::INT IS NULL OR (r.account_workchain_id = $3 AND r.account_hex = $4))
+                    AND r.created_at >= $5
+                    AND (t.status IS NULL OR t.status != 'Error')"#,
+            service_id as ServiceId,
+            root_address,
+            account_workchain_id,
+            account_hex,
+            since,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.value)
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use uuid::Uuid;
+
+use crate::models::*;
+use crate::sqlx_client::*;
+
+impl SqlxClient {
+    pub async fn create_task(&self, payload: CreateTask) -> Result<TaskDb> {
+        sqlx::query_as!(
+            TaskDb,
+            r#"
+            INSERT INTO tasks
+            (id, service_id, account_workchain_id, account_hex, status, kind, data, created_at)
+            VALUES ($1, $2, $3, $4, 'Pending'::twa_task_status, $5, $6, current_timestamp)
+            RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex,
+                status as "status: _", kind as "kind: _", data, error, created_at, updated_at"#,
+            payload.id,
+            payload.service_id as ServiceId,
+            payload.account_workchain_id,
+            payload.account_hex,
+            payload.kind as TaskKind,
+            payload.data,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn get_account_tasks(
+        &self,
+        service_id: ServiceId,
+        kind: TaskKind,
+        account_workchain_id: i32,
+        account_hex: String,
+    ) -> Result<Vec<TaskDb>> {
+        sqlx::query_as!(
+            TaskDb,
+            r#"
+            SELECT id, service_id as "service_id: _", account_workchain_id, account_hex,
+                status as "status: _", kind as "kind: _", data, error, created_at, updated_at
+            FROM tasks
+            WHERE service_id = $1 AND kind = $2 AND account_workchain_id = $3 AND account_hex = $4
+            ORDER BY created_at"#,
+            service_id as ServiceId,
+            kind as TaskKind,
+            account_workchain_id,
+            account_hex,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Returns pending and interrupted tasks of the specified kind
+    pub async fn get_unfinished_tasks(&self, kind: TaskKind) -> Result<Vec<TaskDb>> {
+        sqlx::query_as!(
+            TaskDb,
+            r#"
+            SELECT id, service_id as "service_id: _", account_workchain_id, account_hex,
+                status as "status: _", kind as "kind: _", data, error, created_at, updated_at
+            FROM tasks
+            WHERE kind = $1 AND status IN ('Pending'::twa_task_status, 'InProgress'::twa_task_status)
+            ORDER BY created_at"#,
+            kind as TaskKind,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn update_task(
+        &self,
+        id: Uuid,
+        status: TaskStatus,
+        data: serde_json::Value,
+        error: Option<String>,
+    ) -> Result<TaskDb> {
+        sqlx::query_as!(
+            TaskDb,
+            r#"
+            UPDATE tasks SET (status, data, error, updated_at) = ($2, $3, $4, current_timestamp)
+            WHERE id = $1
+            RETURNING id, service_id as "service_id: _", account_workchain_id, account_hex,
+                status as "status: _", kind as "kind: _", data, error, created_at, updated_at"#,
+            id,
+            status as TaskStatus,
+            data,
+            error,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+}
@@ -75,7 +75,9 @@ impl SqlxClient {
         sqlx::query_as!(TokenBalanceFromDb,
                 r#"SELECT service_id as "service_id: _", account_workchain_id, account_hex, balance, root_address, created_at, updated_at
                 FROM token_balances
-                WHERE service_id = $1 AND account_workchain_id = $2 AND account_hex = $3 "#,
+                WHERE service_id = $1 AND account_workchain_id = $2 AND account_hex = $3
+                    AND (NOT EXISTS (SELECT 1 FROM api_service WHERE id = $1 AND restrict_root_tokens)
+                        OR root_address IN (SELECT root_address FROM api_service_root_token WHERE service_id = $1))"#,
                 service_id as ServiceId,
                 account_workchain_id,
                 account_hex
@@ -69,4 +69,22 @@ impl SqlxClient {
         .await
         .map_err(anyhow::Error::new)
     }
+
+    pub async fn get_token_owners_by_owner(
+        &self,
+        owner_account_workchain_id: i32,
+        owner_account_hex: String,
+    ) -> Result<Vec<TokenOwnerFromDb>, anyhow::Error> {
+        sqlx::query_as!(
+            TokenOwnerFromDb,
+            r#"SELECT address, owner_account_workchain_id, owner_account_hex, root_address, code_hash, created_at, version as "version: _"
+            FROM token_owners
+            WHERE owner_account_workchain_id = $1 AND owner_account_hex = $2"#,
+            owner_account_workchain_id,
+            owner_account_hex,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(anyhow::Error::new)
+    }
 }
@@ -4,6 +4,8 @@ use chrono::prelude::*;
 use crate::models::*;
 use crate::sqlx_client::*;
 
+use super::event_stream::publish_event;
+
 impl SqlxClient {
     pub async fn create_token_transaction(
         &self,
@@ -113,6 +115,19 @@ impl SqlxClient {
         .fetch_one(&mut *tx)
         .await?;
 
+        // Outgoing transfers are notified once the owner message is known
+        if transaction.direction == TonTransactionDirection::Receive
+            || transaction.owner_message_hash.is_some()
+        {
+            publish_event(
+                &mut tx,
+                event.service_id,
+                NotifyType::TokenTransaction,
+                &event.clone().into(),
+            )
+            .await?;
+        }
+
         tx.commit().await?;
 
         Ok((transaction, event))
@@ -162,7 +177,6 @@ impl SqlxClient {
         .map_err(From::from)
     }
 
-    #[allow(dead_code)]
     pub async fn get_token_transaction_by_h(
         &self,
         service_id: ServiceId,
@@ -258,6 +272,14 @@ impl SqlxClient {
             .fetch_one(&mut *tx)
             .await?;
 
+            publish_event(
+                &mut tx,
+                event.service_id,
+                NotifyType::TokenTransaction,
+                &event.clone().into(),
+            )
+            .await?;
+
             res = Some(event);
         }
 
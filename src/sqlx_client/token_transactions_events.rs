@@ -172,7 +172,18 @@ impl SqlxClient {
         args.add(service_id.inner()).map_err(sqlx::Error::Encode)?;
         let mut args_len = 1;
 
-        let updates = filter_token_transaction_query(&mut args, &mut args_len, input);
+        let mut updates = filter_token_transaction_query(&mut args, &mut args_len, input);
+
+        if let Some(cursor) = input.cursor {
+            updates.push(format!(
+                " AND (tte.created_at, tte.id) < (${}, ${}) ",
+                args_len + 1,
+                args_len + 2
+            ));
+            args_len += 2;
+            args.add(cursor.created_at).map_err(sqlx::Error::Encode)?;
+            args.add(cursor.id).map_err(sqlx::Error::Encode)?;
+        }
 
         let query: String = format!(
             r#"SELECT
@@ -195,7 +206,10 @@ impl SqlxClient {
                 tt.transaction_hash as token_transaction_hash
                 FROM token_transaction_events tte
                     LEFT JOIN token_transactions tt on tt.id = tte.token_transaction_id
-                WHERE tte.service_id = $1 {} ORDER BY tte.created_at DESC OFFSET ${} LIMIT ${}"#,
+                WHERE tte.service_id = $1
+                    AND (NOT EXISTS (SELECT 1 FROM api_service WHERE id = $1 AND restrict_root_tokens)
+                        OR tte.root_address IN (SELECT root_address FROM api_service_root_token WHERE service_id = $1))
+                    {} ORDER BY tte.created_at DESC, tte.id DESC OFFSET ${} LIMIT ${}"#,
             updates.iter().format(""),
             args_len + 1,
             args_len + 2
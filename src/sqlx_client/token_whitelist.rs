@@ -1,12 +1,13 @@
 use crate::models::*;
 use crate::sqlx_client::*;
 use anyhow::Result;
+use sqlx::PgConnection;
 
 impl SqlxClient {
     pub async fn get_root_token(&self, address: &str) -> Result<WhitelistedTokenFromDb> {
         let res = sqlx::query_as!(
             WhitelistedTokenFromDb,
-            r#"SELECT name, address, version as "version: _"
+            r#"SELECT name, address, version as "version: _", symbol, decimals, total_supply, metadata_updated_at, enabled
                 FROM token_whitelist
                 WHERE address = $1"#,
             address
@@ -23,13 +24,14 @@ impl SqlxClient {
         sqlx::query_as!(
             WhitelistedTokenFromDb,
             r#"INSERT INTO token_whitelist
-                (name, address, version)
-                VALUES ($1, $2, $3::twa_token_wallet_version)
+                (name, address, version, enabled)
+                VALUES ($1, $2, $3::twa_token_wallet_version, $4)
                 RETURNING
-                name, address, version as "version: _" "#,
+                name, address, version as "version: _", symbol, decimals, total_supply, metadata_updated_at, enabled"#,
             root_token.name,
             root_token.address,
             root_token.version as TokenWalletVersionDb,
+            root_token.enabled,
         )
         .fetch_one(&self.pool)
         .await
@@ -39,11 +41,165 @@ impl SqlxClient {
     pub async fn get_token_whitelist(&self) -> Result<Vec<WhitelistedTokenFromDb>> {
         sqlx::query_as!(
             WhitelistedTokenFromDb,
-            r#"SELECT name, address, version as "version: _"
+            r#"SELECT name, address, version as "version: _", symbol, decimals, total_supply, metadata_updated_at, enabled
                 FROM token_whitelist"#
         )
         .fetch_all(&self.pool)
         .await
         .map_err(From::from)
     }
+
+    pub async fn update_root_token_metadata(
+        &self,
+        address: &str,
+        metadata: &TokenMetadata,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE token_whitelist
+                SET (symbol, decimals, total_supply, metadata_updated_at) = ($2, $3, $4, current_timestamp)
+                WHERE address = $1"#,
+            address,
+            metadata.symbol,
+            metadata.decimals,
+            metadata.total_supply,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_root_token_enabled(
+        &self,
+        address: &str,
+        enabled: bool,
+    ) -> Result<WhitelistedTokenFromDb> {
+        sqlx::query_as!(
+            WhitelistedTokenFromDb,
+            r#"UPDATE token_whitelist SET enabled = $2
+                WHERE address = $1
+                RETURNING
+                name, address, version as "version: _", symbol, decimals, total_supply, metadata_updated_at, enabled"#,
+            address,
+            enabled,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn delete_root_token(&self, address: &str) -> Result<WhitelistedTokenFromDb> {
+        sqlx::query_as!(
+            WhitelistedTokenFromDb,
+            r#"DELETE FROM token_whitelist
+                WHERE address = $1
+                RETURNING
+                name, address, version as "version: _", symbol, decimals, total_supply, metadata_updated_at, enabled"#,
+            address,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Returns whitelisted tokens available to the service.
+    /// A service without the limit has access to all of them
+    pub async fn get_service_token_whitelist(
+        &self,
+        service_id: ServiceId,
+    ) -> Result<Vec<WhitelistedTokenFromDb>> {
+        sqlx::query_as!(
+            WhitelistedTokenFromDb,
+            r#"SELECT name, address, version as "version: _", symbol, decimals, total_supply, metadata_updated_at, enabled
+                FROM token_whitelist
+                WHERE NOT EXISTS (SELECT 1 FROM api_service WHERE id = $1 AND restrict_root_tokens)
+                    OR address IN (SELECT root_address FROM api_service_root_token WHERE service_id = $1)"#,
+            service_id as ServiceId,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Returns the roots available to the service, or `None` when it isn't limited
+    pub async fn get_service_root_tokens(
+        &self,
+        service_id: ServiceId,
+    ) -> Result<Option<Vec<String>>> {
+        let row = sqlx::query!(
+            r#"SELECT restrict_root_tokens,
+                ARRAY(SELECT root_address FROM api_service_root_token WHERE service_id = $1) as "root_addresses!"
+                FROM api_service
+                WHERE id = $1"#,
+            service_id as ServiceId,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row
+            .filter(|row| row.restrict_root_tokens)
+            .map(|row| row.root_addresses))
+    }
+
+    /// Limits the service to the roots, or removes the limit when they are `None`.
+    /// Returns `false` when the service doesn't exist
+    pub async fn set_service_root_tokens(
+        &self,
+        service_id: ServiceId,
+        root_addresses: Option<&[String]>,
+    ) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query!(
+            r#"UPDATE api_service SET restrict_root_tokens = $2 WHERE id = $1"#,
+            service_id as ServiceId,
+            root_addresses.is_some(),
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            r#"DELETE FROM api_service_root_token WHERE service_id = $1"#,
+            service_id as ServiceId,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(root_addresses) = root_addresses {
+            sqlx::query!(
+                r#"INSERT INTO api_service_root_token (service_id, root_address)
+                    SELECT $1, UNNEST($2::VARCHAR[])"#,
+                service_id as ServiceId,
+                root_addresses,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+}
+
+/// Metadata of the whitelisted root token, if it was already loaded
+pub(super) async fn get_token_metadata(
+    conn: &mut PgConnection,
+    address: &str,
+) -> Result<Option<TokenMetadata>> {
+    let token = sqlx::query_as!(
+        WhitelistedTokenFromDb,
+        r#"SELECT name, address, version as "version: _", symbol, decimals, total_supply, metadata_updated_at, enabled
+            FROM token_whitelist
+            WHERE address = $1"#,
+        address
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(token.as_ref().and_then(TokenMetadata::from_whitelist))
 }
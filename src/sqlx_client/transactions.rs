@@ -5,6 +5,8 @@ use uuid::Uuid;
 use crate::models::*;
 use crate::sqlx_client::*;
 
+use super::event_stream::publish_event;
+
 use itertools::Itertools;
 use nekoton_utils::{repack_address, TrustMe};
 use sqlx::postgres::PgArguments;
@@ -77,6 +79,14 @@ impl SqlxClient {
             .fetch_one(&mut *tx)
             .await?;
 
+        publish_event(
+            &mut tx,
+            event.service_id,
+            NotifyType::Transaction,
+            &event.clone().into(),
+        )
+        .await?;
+
         tx.commit().await?;
 
         Ok((transaction, event))
@@ -278,6 +288,14 @@ impl SqlxClient {
             }
         };
 
+        publish_event(
+            &mut tx,
+            event.service_id,
+            NotifyType::Transaction,
+            &event.clone().into(),
+        )
+        .await?;
+
         tx.commit().await?;
 
         Ok((transaction, event))
@@ -371,6 +389,14 @@ impl SqlxClient {
         .fetch_one(&mut *tx)
         .await?;
 
+        publish_event(
+            &mut tx,
+            event.service_id,
+            NotifyType::Transaction,
+            &event.clone().into(),
+        )
+        .await?;
+
         tx.commit().await?;
 
         Ok((transaction, event))
@@ -462,6 +488,14 @@ impl SqlxClient {
             .fetch_one(&mut *tx)
             .await?;
 
+        publish_event(
+            &mut tx,
+            event.service_id,
+            NotifyType::Transaction,
+            &event.clone().into(),
+        )
+        .await?;
+
         tx.commit().await?;
 
         Ok((transaction, event))
@@ -554,6 +588,22 @@ impl SqlxClient {
             .map_err(From::from)
     }
 
+    pub async fn find_transaction_by_id(&self, id: &Uuid) -> Result<Option<TransactionDb>> {
+        sqlx::query_as!(TransactionDb,
+                r#"
+            SELECT id, service_id as "service_id: _", message_hash, transaction_hash, transaction_lt, transaction_timeout,
+                transaction_scan_lt, transaction_timestamp, sender_workchain_id, sender_hex, account_workchain_id, account_hex, messages, messages_hash, data,
+                original_value, original_outputs, value, fee, balance_change, direction as "direction: _", status as "status: _",
+                error, aborted, bounce, multisig_transaction_id, created_at, updated_at
+            FROM transactions
+            WHERE id = $1"#,
+                id,
+            )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(From::from)
+    }
+
     #[allow(dead_code)]
     pub async fn get_all_transactions_by_status(
         &self,
@@ -574,6 +624,141 @@ impl SqlxClient {
             .map_err(From::from)
     }
 
+    /// Stores the signed message to be able to broadcast it again
+    pub async fn update_transaction_message(
+        &self,
+        message_hash: &str,
+        account_workchain_id: i32,
+        account_hex: &str,
+        message_boc: Vec<u8>,
+        expire_at: Option<NaiveDateTime>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE transactions SET (message_boc, expire_at) = ($4, $5)
+            WHERE message_hash = $1 AND account_workchain_id = $2 AND account_hex = $3
+                AND direction = 'Send'::twa_transaction_direction"#,
+            message_hash,
+            account_workchain_id,
+            account_hex,
+            message_boc,
+            expire_at,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_transaction_resend_input(
+        &self,
+        id: Uuid,
+        resend_input: serde_json::Value,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"UPDATE transactions SET resend_input = $2 WHERE id = $1"#,
+            id,
+            resend_input,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_transaction_resend(
+        &self,
+        message_hash: &str,
+        account_workchain_id: i32,
+        account_hex: &str,
+    ) -> Result<Option<ResendTransactionDb>> {
+        sqlx::query_as!(
+            ResendTransactionDb,
+            r#"SELECT id, service_id as "service_id: _", message_hash, account_workchain_id, account_hex,
+                resend_input, send_attempts, expire_at
+            FROM transactions
+            WHERE message_hash = $1 AND account_workchain_id = $2 AND account_hex = $3
+                AND direction = 'Send'::twa_transaction_direction AND status = 'New'::twa_transaction_status"#,
+            message_hash,
+            account_workchain_id,
+            account_hex,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Replaces the expired message of the transaction and its events with the new one.
+    /// Returns `false` if the transaction was updated in the meantime
+    pub async fn update_transaction_resend(
+        &self,
+        id: Uuid,
+        expired_message_hash: &str,
+        message_hash: &str,
+        message_boc: Vec<u8>,
+        expire_at: Option<NaiveDateTime>,
+    ) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let service_id = sqlx::query_scalar!(
+            r#"UPDATE transactions SET (message_hash, message_boc, expire_at, send_attempts) = ($3, $4, $5, send_attempts + 1)
+            WHERE id = $1 AND message_hash = $2 AND status = 'New'::twa_transaction_status
+            RETURNING service_id"#,
+            id,
+            expired_message_hash,
+            message_hash,
+            message_boc,
+            expire_at,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let service_id = match service_id {
+            Some(service_id) => service_id,
+            None => return Ok(false),
+        };
+
+        sqlx::query!(
+            r#"UPDATE transaction_events SET message_hash = $3
+            WHERE transaction_id = $1 AND message_hash = $2"#,
+            id,
+            expired_message_hash,
+            message_hash,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Replayed events refer to the message which can still be found
+        sqlx::query!(
+            r#"UPDATE event_stream SET payload = jsonb_set(payload, '{messageHash}', to_jsonb($4::VARCHAR))
+            WHERE service_id = $1 AND notify_type = 'Transaction'::twa_notify_type
+                AND payload->>'transactionId' = $2::UUID::TEXT AND payload->>'messageHash' = $3"#,
+            service_id,
+            id,
+            expired_message_hash,
+            message_hash,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Messages of the sent transactions which are not found in the blockchain yet
+    pub async fn get_pending_messages(&self) -> Result<Vec<PendingMessageDb>> {
+        sqlx::query_as!(
+            PendingMessageDb,
+            r#"SELECT id, service_id as "service_id: _", message_hash, account_workchain_id, account_hex,
+                message_boc, expire_at, created_at
+            FROM transactions
+            WHERE status = 'New'::twa_transaction_status"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
     pub async fn get_transaction_by_out_msg(&self, message_hash: &str) -> Result<TransactionDb> {
         let j_value = serde_json::json!(message_hash);
         sqlx::query_as!(TransactionDb,
@@ -601,22 +786,37 @@ impl SqlxClient {
         let mut args_len = 1;
 
         let order_by = match input.ordering {
-            None => "",
-            Some(ref o) => match o {
-                TransactionsSearchOrdering::CreatedAtAsc => "ORDER BY created_at asc",
-                TransactionsSearchOrdering::CreatedAtDesc => "ORDER BY created_at desc",
-                TransactionsSearchOrdering::TransactionLtAsc => "ORDER BY transaction_lt asc",
-                TransactionsSearchOrdering::TransactionLtDesc => "ORDER BY transaction_lt desc",
-                TransactionsSearchOrdering::TransactionTimestampAsc => {
-                    "ORDER BY transaction_timestamp asc"
-                }
-                TransactionsSearchOrdering::TransactionTimestampDesc => {
-                    "ORDER BY transaction_timestamp desc"
-                }
-            },
+            None | Some(TransactionsSearchOrdering::CreatedAtDesc) => {
+                "ORDER BY created_at desc, id desc"
+            }
+            Some(TransactionsSearchOrdering::CreatedAtAsc) => "ORDER BY created_at asc, id asc",
+            Some(TransactionsSearchOrdering::TransactionLtAsc) => "ORDER BY transaction_lt asc",
+            Some(TransactionsSearchOrdering::TransactionLtDesc) => "ORDER BY transaction_lt desc",
+            Some(TransactionsSearchOrdering::TransactionTimestampAsc) => {
+                "ORDER BY transaction_timestamp asc"
+            }
+            Some(TransactionsSearchOrdering::TransactionTimestampDesc) => {
+                "ORDER BY transaction_timestamp desc"
+            }
         };
 
-        let updates = filter_transaction_query(&mut args, &mut args_len, input);
+        let mut updates = filter_transaction_query(&mut args, &mut args_len, input);
+
+        if let Some(cursor) = input.cursor {
+            let op = match input.ordering {
+                Some(TransactionsSearchOrdering::CreatedAtAsc) => ">",
+                _ => "<",
+            };
+            updates.push(format!(
+                " AND (created_at, id) {} (${}, ${}) ",
+                op,
+                args_len + 1,
+                args_len + 2
+            ));
+            args_len += 2;
+            args.add(cursor.created_at).map_err(sqlx::Error::Encode)?;
+            args.add(cursor.id).map_err(sqlx::Error::Encode)?;
+        }
 
         let query: String = format!(
             r#"SELECT id, service_id as "service_id: _", message_hash, transaction_hash, transaction_lt, transaction_timeout,
@@ -762,4 +962,160 @@ pub fn filter_transaction_query(
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore]
+    async fn resend_replaces_the_expired_message_once() {
+        let sqlx_client = test_client().await;
+        let address = create_test_address(&sqlx_client).await;
+
+        let id = Uuid::new_v4();
+        let expired_message_hash = hex::encode(Uuid::new_v4().as_bytes()).repeat(2);
+        let message_hash = hex::encode(Uuid::new_v4().as_bytes()).repeat(2);
+        let (transaction, _) = sqlx_client
+            .create_send_transaction(CreateSendTransaction {
+                id,
+                service_id: address.service_id,
+                message_hash: expired_message_hash.clone(),
+                account_workchain_id: address.workchain_id,
+                account_hex: address.hex.clone(),
+                original_value: None,
+                original_outputs: None,
+                direction: TonTransactionDirection::Send,
+                status: TonTransactionStatus::New,
+                aborted: false,
+                bounce: false,
+            })
+            .await
+            .unwrap();
+
+        assert!(sqlx_client
+            .update_transaction_resend(
+                transaction.id,
+                &expired_message_hash,
+                &message_hash,
+                vec![1],
+                None,
+            )
+            .await
+            .unwrap());
+
+        // The expired message is already replaced
+        assert!(!sqlx_client
+            .update_transaction_resend(
+                transaction.id,
+                &expired_message_hash,
+                &message_hash,
+                vec![2],
+                None,
+            )
+            .await
+            .unwrap());
+
+        let transaction = sqlx_client
+            .get_transaction_by_id(address.service_id, &id)
+            .await
+            .unwrap();
+        assert_eq!(transaction.message_hash, message_hash);
+
+        let event = sqlx_client
+            .get_transaction_event_by_mh(
+                address.service_id,
+                message_hash.clone(),
+                address.workchain_id,
+                address.hex.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(event.transaction_id, id);
+        assert!(sqlx_client
+            .get_transaction_event_by_mh(
+                address.service_id,
+                expired_message_hash,
+                address.workchain_id,
+                address.hex.clone(),
+            )
+            .await
+            .is_err());
+
+        // Done transactions are not resent
+        let next_message_hash = hex::encode(Uuid::new_v4().as_bytes()).repeat(2);
+        sqlx_client
+            .upsert_send_transaction(
+                address.service_id,
+                message_hash.clone(),
+                address.workchain_id,
+                address.hex.clone(),
+                UpdateSendTransaction::error("Expired".to_string()),
+            )
+            .await
+            .unwrap();
+        assert!(!sqlx_client
+            .update_transaction_resend(id, &message_hash, &next_message_hash, vec![3], None)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn transactions_without_ordering_are_paged_newest_first() {
+        let sqlx_client = test_client().await;
+        let address = create_test_address(&sqlx_client).await;
+
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let (transaction, _) = sqlx_client
+                .create_send_transaction(CreateSendTransaction {
+                    id: Uuid::new_v4(),
+                    service_id: address.service_id,
+                    message_hash: hex::encode(Uuid::new_v4().as_bytes()).repeat(2),
+                    account_workchain_id: address.workchain_id,
+                    account_hex: address.hex.clone(),
+                    original_value: None,
+                    original_outputs: None,
+                    direction: TonTransactionDirection::Send,
+                    status: TonTransactionStatus::New,
+                    aborted: false,
+                    bounce: false,
+                })
+                .await
+                .unwrap();
+            ids.push(transaction.id);
+        }
+        ids.reverse();
+
+        let mut search = TransactionsSearch {
+            id: None,
+            message_hash: None,
+            transaction_hash: None,
+            account: None,
+            status: None,
+            direction: None,
+            created_at_min: None,
+            created_at_max: None,
+            ordering: None,
+            limit: 2,
+            offset: 0,
+            cursor: None,
+        };
+
+        let page = sqlx_client
+            .get_all_transactions(address.service_id, &search)
+            .await
+            .unwrap();
+        assert_eq!(page.iter().map(|t| t.id).collect::<Vec<_>>(), ids[..2]);
+
+        search.cursor = SearchCursor::next(&page, search.limit, |t| {
+            SearchCursor::new(t.created_at, t.id)
+        });
+        assert!(search.cursor.is_some());
+
+        let page = sqlx_client
+            .get_all_transactions(address.service_id, &search)
+            .await
+            .unwrap();
+        assert_eq!(page.iter().map(|t| t.id).collect::<Vec<_>>(), ids[2..]);
+    }
+}
@@ -231,7 +231,18 @@ impl SqlxClient {
 
         let mut args_len = 1;
 
-        let updates = filter_transaction_query(&mut args, &mut args_len, input);
+        let mut updates = filter_transaction_query(&mut args, &mut args_len, input);
+
+        if let Some(cursor) = input.cursor {
+            updates.push(format!(
+                " AND (te.created_at, te.id) < (${}, ${}) ",
+                args_len + 1,
+                args_len + 2
+            ));
+            args_len += 2;
+            args.add(cursor.created_at).map_err(sqlx::Error::Encode)?;
+            args.add(cursor.id).map_err(sqlx::Error::Encode)?;
+        }
 
         let query: String = format!(
             r#"SELECT
@@ -253,7 +264,7 @@ impl SqlxClient {
                 t.transaction_hash
                 FROM transaction_events te
                     LEFT JOIN transactions t ON t.id = te.transaction_id
-                WHERE te.service_id = $1 {} ORDER BY te.created_at DESC OFFSET ${} LIMIT ${}"#,
+                WHERE te.service_id = $1 {} ORDER BY te.created_at DESC, te.id DESC OFFSET ${} LIMIT ${}"#,
             updates.iter().format(""),
             args_len + 1,
             args_len + 2
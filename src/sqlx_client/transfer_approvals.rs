@@ -0,0 +1,196 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::models::*;
+use crate::sqlx_client::*;
+
+use super::event_stream::publish_event;
+
+impl SqlxClient {
+    /// Returns `None` if a transfer with the same id already exists
+    pub async fn create_transfer_approval(
+        &self,
+        payload: CreateTransferApproval,
+    ) -> Result<Option<TransferApprovalDb>> {
+        let mut tx = self.pool.begin().await?;
+
+        let approval = sqlx::query_as!(
+            TransferApprovalDb,
+            r#"INSERT INTO transfer_approvals
+                (id, service_id, notify_type, account_workchain_id, account_hex, root_address, value, input, status, requested_by)
+                SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9, $10
+                WHERE NOT EXISTS (SELECT 1 FROM transactions WHERE id = $1)
+                ON CONFLICT (id) DO NOTHING
+                RETURNING id, service_id as "service_id: _", notify_type as "notify_type: _", account_workchain_id, account_hex,
+                    root_address, value, input, status as "status: _", requested_by, decided_by, error, created_at, updated_at"#,
+            payload.id,
+            payload.service_id as ServiceId,
+            payload.notify_type as NotifyType,
+            payload.account_workchain_id,
+            payload.account_hex,
+            payload.root_address,
+            payload.value,
+            payload.input,
+            TonTransactionStatus::PendingApproval as TonTransactionStatus,
+            payload.requested_by,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(approval) = &approval {
+            publish_event(
+                &mut tx,
+                approval.service_id,
+                approval.notify_type,
+                &approval.event()?,
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(approval)
+    }
+
+    pub async fn get_transfer_approval(
+        &self,
+        service_id: ServiceId,
+        id: Uuid,
+    ) -> Result<TransferApprovalDb> {
+        sqlx::query_as!(
+            TransferApprovalDb,
+            r#"SELECT id, service_id as "service_id: _", notify_type as "notify_type: _", account_workchain_id, account_hex,
+                root_address, value, input, status as "status: _", requested_by, decided_by, error, created_at, updated_at
+                FROM transfer_approvals
+                WHERE service_id = $1 AND id = $2"#,
+            service_id as ServiceId,
+            id,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    pub async fn get_transfer_approvals(
+        &self,
+        service_id: ServiceId,
+        status: Option<TonTransactionStatus>,
+    ) -> Result<Vec<TransferApprovalDb>> {
+        sqlx::query_as!(
+            TransferApprovalDb,
+            r#"SELECT id, service_id as "service_id: _", notify_type as "notify_type: _", account_workchain_id, account_hex,
+                root_address, value, input, status as "status: _", requested_by, decided_by, error, created_at, updated_at
+                FROM transfer_approvals
+                WHERE service_id = $1 AND ($2::twa_transaction_status IS NULL OR status = $2)
+                ORDER BY created_at DESC"#,
+            service_id as ServiceId,
+            status as Option<TonTransactionStatus>,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Moves the approval from the `from` status, returns `None` if it was already decided
+    pub async fn update_transfer_approval_status(
+        &self,
+        service_id: ServiceId,
+        id: Uuid,
+        from: TonTransactionStatus,
+        to: TonTransactionStatus,
+        decided_by: Option<Uuid>,
+        error: Option<String>,
+    ) -> Result<Option<TransferApprovalDb>> {
+        let mut tx = self.pool.begin().await?;
+
+        let approval = sqlx::query_as!(
+            TransferApprovalDb,
+            r#"UPDATE transfer_approvals
+                SET (status, decided_by, error, updated_at) = ($4, COALESCE($5, decided_by), $6, current_timestamp)
+                WHERE service_id = $1 AND id = $2 AND status = $3
+                RETURNING id, service_id as "service_id: _", notify_type as "notify_type: _", account_workchain_id, account_hex,
+                    root_address, value, input, status as "status: _", requested_by, decided_by, error, created_at, updated_at"#,
+            service_id as ServiceId,
+            id,
+            from as TonTransactionStatus,
+            to as TonTransactionStatus,
+            decided_by,
+            error,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        // Approved transfers are notified with their transactions
+        if let Some(approval) = &approval {
+            if approval.status == TonTransactionStatus::Rejected {
+                publish_event(
+                    &mut tx,
+                    approval.service_id,
+                    approval.notify_type,
+                    &approval.event()?,
+                )
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(approval)
+    }
+
+    /// Rejects the transfers still pending approval which were requested before `expired_before`
+    pub async fn expire_transfer_approvals(
+        &self,
+        expired_before: NaiveDateTime,
+    ) -> Result<Vec<TransferApprovalDb>> {
+        let mut tx = self.pool.begin().await?;
+
+        let approvals = sqlx::query_as!(
+            TransferApprovalDb,
+            r#"UPDATE transfer_approvals
+                SET (status, error, updated_at) = ($2, 'Expired', current_timestamp)
+                WHERE status = $1 AND created_at < $3
+                RETURNING id, service_id as "service_id: _", notify_type as "notify_type: _", account_workchain_id, account_hex,
+                    root_address, value, input, status as "status: _", requested_by, decided_by, error, created_at, updated_at"#,
+            TonTransactionStatus::PendingApproval as TonTransactionStatus,
+            TonTransactionStatus::Rejected as TonTransactionStatus,
+            expired_before,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for approval in &approvals {
+            publish_event(
+                &mut tx,
+                approval.service_id,
+                approval.notify_type,
+                &approval.event()?,
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(approvals)
+    }
+
+    /// Approved transfers which are still being sent since `updated_before`
+    pub async fn get_stuck_transfer_approvals(
+        &self,
+        updated_before: NaiveDateTime,
+    ) -> Result<Vec<TransferApprovalDb>> {
+        sqlx::query_as!(
+            TransferApprovalDb,
+            r#"SELECT id, service_id as "service_id: _", notify_type as "notify_type: _", account_workchain_id, account_hex,
+                root_address, value, input, status as "status: _", requested_by, decided_by, error, created_at, updated_at
+                FROM transfer_approvals
+                WHERE status = $1 AND updated_at < $2"#,
+            TonTransactionStatus::New as TonTransactionStatus,
+            updated_before,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::models::*;
+use crate::sqlx_client::*;
+
+impl SqlxClient {
+    /// Takes due pending deliveries and postpones them until `locked_until`,
+    /// so that a crashed worker doesn't hold them forever.
+    pub async fn take_pending_webhook_deliveries(
+        &self,
+        now: NaiveDateTime,
+        locked_until: NaiveDateTime,
+        limit: i64,
+    ) -> Result<Vec<WebhookDeliveryDb>> {
+        sqlx::query_as!(
+            WebhookDeliveryDb,
+            r#"
+            UPDATE webhook_deliveries SET next_attempt_at = $1
+            WHERE id IN (
+                SELECT id FROM webhook_deliveries
+                WHERE status = 'Pending'::twa_webhook_delivery_status AND next_attempt_at <= $2
+                ORDER BY next_attempt_at
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, service_id as "service_id: _", callback_id, event_id, notify_type as "notify_type: _",
+                payload, status as "status: _", attempts, next_attempt_at, last_error, created_at, updated_at"#,
+            locked_until,
+            now,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Records the attempt of the pending delivery, returns `None` if it was already failed
+    pub async fn update_webhook_delivery(
+        &self,
+        id: Uuid,
+        status: WebhookDeliveryStatus,
+        next_attempt_at: NaiveDateTime,
+        last_error: Option<String>,
+    ) -> Result<Option<WebhookDeliveryDb>> {
+        sqlx::query_as!(
+            WebhookDeliveryDb,
+            r#"
+            UPDATE webhook_deliveries SET
+            (status, attempts, next_attempt_at, last_error, updated_at) =
+            ($2, attempts + 1, $3, $4, current_timestamp)
+            WHERE id = $1 AND status = 'Pending'::twa_webhook_delivery_status
+            RETURNING id, service_id as "service_id: _", callback_id, event_id, notify_type as "notify_type: _",
+                payload, status as "status: _", attempts, next_attempt_at, last_error, created_at, updated_at"#,
+            id,
+            status as WebhookDeliveryStatus,
+            next_attempt_at,
+            last_error,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+
+    /// Returns the latest delivery of each of the specified events to each callback
+    pub async fn get_webhook_deliveries_by_events(
+        &self,
+        service_id: ServiceId,
+        event_ids: &[Uuid],
+    ) -> Result<Vec<WebhookDeliveryDb>> {
+        sqlx::query_as!(
+            WebhookDeliveryDb,
+            r#"
+            SELECT DISTINCT ON (event_id, callback_id)
+                id, service_id as "service_id: _", callback_id, event_id, notify_type as "notify_type: _",
+                payload, status as "status: _", attempts, next_attempt_at, last_error, created_at, updated_at
+            FROM webhook_deliveries
+            WHERE service_id = $1 AND event_id = ANY($2)
+            ORDER BY event_id, callback_id, created_at DESC"#,
+            service_id as ServiceId,
+            event_ids,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(From::from)
+    }
+}
+
+/// Queues deliveries of the event to the matching callbacks of the service.
+/// Must be called within the transaction which stores the event
+pub(super) async fn enqueue_webhook_deliveries(
+    conn: &mut PgConnection,
+    service_id: ServiceId,
+    notify_type: NotifyType,
+    event: &AccountTransactionEvent,
+) -> Result<u64> {
+    let payload = serde_json::to_value(event)?;
+
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO webhook_deliveries
+        (id, service_id, callback_id, event_id, notify_type, payload)
+        SELECT uuid_generate_v4(), c.service_id, c.id, $2, $3, $4
+        FROM api_service_callback c
+        WHERE c.service_id = $1
+            AND (c.notify_type IS NULL OR c.notify_type = $3)
+            AND (c.transaction_direction IS NULL OR c.transaction_direction = $5)
+            AND (c.root_address IS NULL OR c.root_address = $6)
+            AND (c.account_workchain_id IS NULL OR c.account_workchain_id = $7)
+            AND (c.account_hex IS NULL OR c.account_hex = $8)
+            AND ($6::VARCHAR IS NULL
+                OR NOT EXISTS (SELECT 1 FROM api_service WHERE id = $1 AND restrict_root_tokens)
+                OR EXISTS (SELECT 1 FROM api_service_root_token WHERE service_id = $1 AND root_address = $6))"#,
+        service_id as ServiceId,
+        event.id,
+        notify_type as NotifyType,
+        payload,
+        event.transaction_direction.clone() as TonTransactionDirection,
+        event.root_address,
+        event.account.workchain_id,
+        event.account.hex.0,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    // Stored events which can't be delivered anywhere fail at once.
+    // Proposal events are not stored, only delivered
+    if result.rows_affected() == 0 && !event.transaction_status.is_proposal() {
+        match notify_type {
+            NotifyType::Transaction => {
+                sqlx::query!(
+                    r#"UPDATE transaction_events SET event_status = $2 WHERE id = $1"#,
+                    event.id,
+                    TonEventStatus::Error as TonEventStatus,
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+            NotifyType::TokenTransaction => {
+                sqlx::query!(
+                    r#"UPDATE token_transaction_events SET event_status = $2 WHERE id = $1"#,
+                    event.id,
+                    TonEventStatus::Error as TonEventStatus,
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+        }
+    }
+
+    Ok(result.rows_affected())
+}
+
+/// Fails pending deliveries to the callback and completes the events which have no more of them.
+/// Must be called within the transaction which deletes the callback
+pub(super) async fn fail_callback_webhook_deliveries(
+    conn: &mut PgConnection,
+    service_id: ServiceId,
+    callback_id: Uuid,
+) -> Result<()> {
+    let event_ids = sqlx::query_scalar!(
+        r#"
+        UPDATE webhook_deliveries SET (status, last_error, updated_at) = ($3, $4, current_timestamp)
+        WHERE service_id = $1 AND callback_id = $2 AND status = 'Pending'::twa_webhook_delivery_status
+        RETURNING event_id"#,
+        service_id as ServiceId,
+        callback_id,
+        WebhookDeliveryStatus::Failed as WebhookDeliveryStatus,
+        "Callback was deleted",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE transaction_events te SET event_status = $2
+        WHERE te.id = ANY($1) AND NOT EXISTS (
+            SELECT 1 FROM webhook_deliveries wd
+            WHERE wd.event_id = te.id AND wd.status = 'Pending'::twa_webhook_delivery_status
+        )"#,
+        &event_ids,
+        TonEventStatus::Error as TonEventStatus,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE token_transaction_events te SET event_status = $2
+        WHERE te.id = ANY($1) AND NOT EXISTS (
+            SELECT 1 FROM webhook_deliveries wd
+            WHERE wd.event_id = te.id AND wd.status = 'Pending'::twa_webhook_delivery_status
+        )"#,
+        &event_ids,
+        TonEventStatus::Error as TonEventStatus,
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
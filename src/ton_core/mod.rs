@@ -1,6 +1,9 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use nekoton::transport::models::*;
@@ -8,8 +11,10 @@ use nekoton_abi::*;
 use parking_lot::Mutex;
 use tokio::sync::{mpsc, oneshot};
 use ton_block::{GetRepresentationHash, MsgAddressInt, Serializable};
+use ton_executor::{ExecuteParams, OrdinaryTransactionExecutor, TransactionExecutor};
 use ton_indexer::GlobalConfig;
 use ton_types::UInt256;
+use ton_vm::executor::BehaviorModifiers;
 
 use self::monitoring::*;
 use self::ton_subscriber::*;
@@ -38,9 +43,16 @@ impl TonCore {
         owners_cache: OwnersCache,
         ton_transaction_producer: TonTransactionTx,
         token_transaction_producer: TokenTransactionTx,
+        balance_snapshot_producer: BalanceSnapshotTx,
     ) -> Result<Arc<Self>> {
-        let context =
-            TonCoreContext::new(node_config, global_config, sqlx_client, owners_cache).await?;
+        let context = TonCoreContext::new(
+            node_config,
+            global_config,
+            sqlx_client,
+            owners_cache,
+            balance_snapshot_producer,
+        )
+        .await?;
 
         let full_state = FullState::new(context.clone()).await?;
 
@@ -68,20 +80,48 @@ impl TonCore {
 
     pub fn add_ton_account_subscription<I>(&self, accounts: I)
     where
-        I: IntoIterator<Item = UInt256>,
+        I: IntoIterator<Item = MsgAddressInt>,
     {
         self.ton_transaction
             .lock()
             .add_account_subscription(accounts);
     }
 
-    pub fn get_contract_state(&self, account: &UInt256) -> Result<ExistingContract> {
-        self.context.get_contract_state(account)
+    pub fn update_root_token(&self, root_address: MsgAddressInt, enabled: bool) {
+        self.context
+            .ton_subscriber
+            .update_root_token(root_address, enabled)
+    }
+
+    pub fn get_contract_state(&self, address: &MsgAddressInt) -> Result<ExistingContract> {
+        self.context.get_contract_state(address)
+    }
+
+    pub async fn load_ton_transactions_history(
+        &self,
+        address: &MsgAddressInt,
+        cursor: Option<TransactionsHistoryCursor>,
+        limit: usize,
+    ) -> Result<TransactionsHistoryBatch<CaughtTonTransaction>> {
+        self.context
+            .load_ton_transactions_history(address, cursor, limit)
+            .await
+    }
+
+    pub async fn load_token_transactions_history(
+        &self,
+        address: &MsgAddressInt,
+        cursor: Option<TransactionsHistoryCursor>,
+        limit: usize,
+    ) -> Result<TransactionsHistoryBatch<CreateTokenTransaction>> {
+        self.context
+            .load_token_transactions_history(address, cursor, limit)
+            .await
     }
 
     pub async fn send_ton_message(
         &self,
-        account: &UInt256,
+        account: &MsgAddressInt,
         message: &ton_block::Message,
         expire_at: u32,
     ) -> Result<MessageStatus> {
@@ -92,7 +132,7 @@ impl TonCore {
 
     pub fn add_pending_message(
         &self,
-        account: UInt256,
+        account: MsgAddressInt,
         message_hash: UInt256,
         expire_at: u32,
     ) -> Result<oneshot::Receiver<MessageStatus>> {
@@ -100,6 +140,36 @@ impl TonCore {
             .add_pending_message(account, message_hash, expire_at)
     }
 
+    /// Waits for the message restored into the pending messages queue. The `message`
+    /// is broadcast again if specified, as the sent ones
+    pub async fn wait_pending_message(
+        &self,
+        rx: oneshot::Receiver<MessageStatus>,
+        message_hash: UInt256,
+        message: Option<&ton_block::Message>,
+        expire_at: u32,
+    ) -> Result<MessageStatus> {
+        let mut attempts = 0;
+        if let Some(message) = message.filter(|_| self.current_utime() <= expire_at) {
+            match self.context.broadcast_ton_message(message) {
+                Ok(()) => attempts += 1,
+                Err(e) => log::warn!(
+                    "Failed to rebroadcast message `{}`: {:?}",
+                    message_hash.to_hex_string(),
+                    e
+                ),
+            }
+        }
+
+        self.context
+            .wait_ton_message(rx, message_hash, message, expire_at, attempts)
+            .await
+    }
+
+    pub fn estimate_fees(&self, message: &ton_block::Message) -> Result<EstimatedFees> {
+        self.context.estimate_fees(message)
+    }
+
     pub fn current_utime(&self) -> u32 {
         self.context.ton_subscriber.current_utime()
     }
@@ -115,6 +185,9 @@ pub struct TonCoreContext {
     pub messages_queue: Arc<PendingMessagesQueue>,
     pub ton_subscriber: Arc<TonSubscriber>,
     pub ton_engine: Arc<ton_indexer::Engine>,
+    pub balance_snapshot_producer: BalanceSnapshotTx,
+    pub broadcast_metrics: BroadcastMetrics,
+    rebroadcast: RebroadcastConfig,
 }
 
 impl Drop for TonCoreContext {
@@ -129,8 +202,11 @@ impl TonCoreContext {
         global_config: GlobalConfig,
         sqlx_client: SqlxClient,
         owners_cache: OwnersCache,
+        balance_snapshot_producer: BalanceSnapshotTx,
     ) -> Result<Arc<Self>> {
         let recover_indexer = node_config.recover_indexer;
+        node_config.rebroadcast.validate()?;
+        let rebroadcast = node_config.rebroadcast.clone();
 
         let node_config = node_config
             .build_indexer_config()
@@ -148,7 +224,8 @@ impl TonCoreContext {
 
         let messages_queue = PendingMessagesQueue::new(512);
 
-        let ton_subscriber = TonSubscriber::new(messages_queue.clone());
+        let ton_subscriber =
+            TonSubscriber::new(messages_queue.clone(), balance_snapshot_producer.clone());
 
         let ton_engine = ton_indexer::Engine::new(
             node_config,
@@ -163,10 +240,22 @@ impl TonCoreContext {
             messages_queue,
             ton_subscriber,
             ton_engine,
+            balance_snapshot_producer,
+            broadcast_metrics: Default::default(),
+            rebroadcast,
         }))
     }
 
     async fn start(&self) -> Result<()> {
+        // Track token wallets of the enabled whitelisted roots
+        let whitelist = self.sqlx_client.get_token_whitelist().await?;
+        self.ton_subscriber.set_root_tokens(
+            whitelist
+                .into_iter()
+                .filter(|token| token.enabled)
+                .filter_map(|token| nekoton_utils::repack_address(&token.address).ok()),
+        );
+
         self.ton_engine.start().await?;
 
         // Load last states if exists
@@ -183,23 +272,79 @@ impl TonCoreContext {
         Ok(())
     }
 
-    fn get_contract_state(&self, account: &UInt256) -> Result<ExistingContract> {
+    fn get_contract_state(&self, address: &MsgAddressInt) -> Result<ExistingContract> {
         match self
             .ton_subscriber
-            .get_contract_state(account)
+            .get_contract_state(address)
             .and_then(make_existing_contract)?
         {
             Some(contract) => Ok(contract),
-            None => Err(TonCoreError::AccountNotExist(account.to_hex_string()).into()),
+            None => Err(TonCoreError::AccountNotExist(address.to_string()).into()),
         }
     }
 
     async fn send_ton_message(
         &self,
-        account: &UInt256,
+        account: &MsgAddressInt,
         message: &ton_block::Message,
         expire_at: u32,
     ) -> Result<MessageStatus> {
+        let message_hash = message.write_to_new_cell()?.into_cell()?.repr_hash();
+
+        let rx = self
+            .messages_queue
+            .add_message(account.clone(), message_hash, expire_at)?;
+
+        self.broadcast_ton_message(message)?;
+
+        self.wait_ton_message(rx, message_hash, Some(message), expire_at, 1)
+            .await
+    }
+
+    /// Waits for the status of the pending message. The broadcast may be dropped by the overlay,
+    /// so the `message` is sent again while it's neither included nor expired.
+    /// `attempts` is the number of its broadcasts so far
+    async fn wait_ton_message(
+        &self,
+        mut rx: oneshot::Receiver<MessageStatus>,
+        message_hash: UInt256,
+        message: Option<&ton_block::Message>,
+        expire_at: u32,
+        mut attempts: u32,
+    ) -> Result<MessageStatus> {
+        let interval = Duration::from_secs(self.rebroadcast.interval_sec);
+        let status = loop {
+            let message = match message {
+                Some(message)
+                    if attempts < self.rebroadcast.max_attempts
+                        && self.ton_subscriber.current_utime() <= expire_at =>
+                {
+                    message
+                }
+                _ => break (&mut rx).await?,
+            };
+
+            match tokio::time::timeout(interval, &mut rx).await {
+                Ok(status) => break status?,
+                Err(_) => {
+                    attempts += 1;
+                    if let Err(e) = self.broadcast_ton_message(message) {
+                        log::warn!(
+                            "Failed to rebroadcast message `{}`: {:?}",
+                            message_hash.to_hex_string(),
+                            e
+                        );
+                    }
+                }
+            }
+        };
+
+        self.broadcast_metrics.record(status, attempts);
+
+        Ok(status)
+    }
+
+    fn broadcast_ton_message(&self, message: &ton_block::Message) -> Result<()> {
         let to = match message.header() {
             ton_block::CommonMsgInfo::ExtInMsgInfo(header) => header.dst.workchain_id(),
             _ => return Err(TonCoreError::ExternalTonMessageExpected.into()),
@@ -208,26 +353,104 @@ impl TonCoreContext {
         let cells = message.write_to_new_cell()?.into_cell()?;
         let serialized = ton_types::serialize_toc(&cells)?;
 
-        let rx = self
-            .messages_queue
-            .add_message(*account, cells.repr_hash(), expire_at)?;
-
         self.ton_engine
             .broadcast_external_message(to, &serialized)?;
+        self.broadcast_metrics
+            .broadcasts
+            .fetch_add(1, Ordering::Relaxed);
 
-        let status = rx.await?;
-        Ok(status)
+        Ok(())
     }
 
     fn add_pending_message(
         &self,
-        account: UInt256,
+        account: MsgAddressInt,
         message_hash: UInt256,
         expire_at: u32,
     ) -> Result<oneshot::Receiver<MessageStatus>> {
         self.messages_queue
             .add_message(account, message_hash, expire_at)
     }
+
+    /// Executes the external message locally against the cached account state.
+    /// Signature checks always succeed, so the message may be signed with a dummy signature
+    fn estimate_fees(&self, message: &ton_block::Message) -> Result<EstimatedFees> {
+        let account = match message.header() {
+            ton_block::CommonMsgInfo::ExtInMsgInfo(header) => &header.dst,
+            _ => return Err(TonCoreError::ExternalTonMessageExpected.into()),
+        };
+
+        let config = self
+            .ton_subscriber
+            .blockchain_config()
+            .ok_or(TonCoreError::BlockchainConfigNotFound)?;
+
+        let contract = self.get_contract_state(account)?;
+        let balance = contract.account.storage.balance.grams.as_u128() as u64;
+        let last_trans_lt = contract.account.storage.last_trans_lt;
+
+        let mut account_root = ton_block::Account::Account(contract.account).serialize()?;
+
+        let executor = OrdinaryTransactionExecutor::new(config);
+        let transaction = executor.execute_with_libs_and_params(
+            Some(message),
+            &mut account_root,
+            ExecuteParams {
+                block_unixtime: self.ton_subscriber.current_utime(),
+                block_lt: last_trans_lt,
+                last_tr_lt: Arc::new(AtomicU64::new(last_trans_lt)),
+                behavior_modifiers: Some(BehaviorModifiers {
+                    chksig_always_succeed: true,
+                }),
+                ..Default::default()
+            },
+        )?;
+
+        let mut fees = EstimatedFees {
+            gas_used: 0,
+            gas_fees: 0,
+            fwd_fees: 0,
+            storage_fees: 0,
+            total_fees: transaction.total_fees().grams.as_u128() as u64,
+            balance,
+            sufficient_balance: false,
+        };
+
+        let mut aborted = true;
+        if let ton_block::TransactionDescr::Ordinary(description) =
+            transaction.read_description()?
+        {
+            if let Some(storage_phase) = &description.storage_ph {
+                fees.storage_fees = storage_phase.storage_fees_collected.as_u128() as u64;
+            }
+            if let ton_block::TrComputePhase::Vm(compute_phase) = &description.compute_ph {
+                fees.gas_used = compute_phase.gas_used.as_u64();
+                fees.gas_fees = compute_phase.gas_fees.as_u128() as u64;
+            }
+            if let Some(action_phase) = &description.action {
+                fees.fwd_fees = action_phase
+                    .total_fwd_fees
+                    .as_ref()
+                    .map(|fwd_fees| fwd_fees.as_u128() as u64)
+                    .unwrap_or_default();
+            }
+            aborted = description.aborted;
+        }
+
+        // Value sent by the outgoing messages must also be covered by the balance
+        let mut outgoing_value = 0;
+        transaction.iterate_out_msgs(|message| {
+            if let Some(value) = message.get_value() {
+                outgoing_value += value.grams.as_u128() as u64;
+            }
+            Ok(true)
+        })?;
+
+        fees.sufficient_balance =
+            !aborted && balance >= fees.total_fees.saturating_add(outgoing_value);
+
+        Ok(fees)
+    }
 }
 
 #[derive(Debug)]
@@ -246,9 +469,49 @@ pub type TokenTransactionTx =
 pub type TokenTransactionRx =
     mpsc::UnboundedReceiver<(CreateTokenTransaction, HandleTransactionStatusTx)>;
 
+pub type BalanceSnapshotTx = mpsc::UnboundedSender<CreateBalanceSnapshot>;
+pub type BalanceSnapshotRx = mpsc::UnboundedReceiver<CreateBalanceSnapshot>;
+
 pub type FullStateTx = mpsc::UnboundedSender<(ShardAccounts, HandleTransactionStatusTx)>;
 pub type FullStateRx = mpsc::UnboundedReceiver<(ShardAccounts, HandleTransactionStatusTx)>;
 
+/// Broadcasts of the sent external messages
+#[derive(Default)]
+pub struct BroadcastMetrics {
+    broadcasts: AtomicU64,
+    expired: AtomicU64,
+    /// Delivered messages by the number of broadcasts they needed. Restored messages
+    /// count only the broadcasts since the restart
+    delivered: Mutex<BTreeMap<u32, u64>>,
+}
+
+impl BroadcastMetrics {
+    pub fn broadcasts(&self) -> u64 {
+        self.broadcasts.load(Ordering::Relaxed)
+    }
+
+    pub fn expired(&self) -> u64 {
+        self.expired.load(Ordering::Relaxed)
+    }
+
+    pub fn delivered(&self) -> Vec<(u32, u64)> {
+        self.delivered
+            .lock()
+            .iter()
+            .map(|(attempts, count)| (*attempts, *count))
+            .collect()
+    }
+
+    fn record(&self, status: MessageStatus, attempts: u32) {
+        match status {
+            MessageStatus::Delivered => *self.delivered.lock().entry(attempts).or_default() += 1,
+            MessageStatus::Expired => {
+                self.expired.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 enum TonCoreError {
     #[error("External ton message expected")]
@@ -259,4 +522,8 @@ enum TonCoreError {
     InvalidRootToken(String),
     #[error("Invalid contract address")]
     InvalidContractAddress,
+    #[error("Transaction `{0}` not found")]
+    TransactionNotFound(String),
+    #[error("Blockchain config not found")]
+    BlockchainConfigNotFound,
 }
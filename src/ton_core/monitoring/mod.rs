@@ -1,9 +1,11 @@
 pub use self::full_state::*;
 pub use self::token_transaction::*;
 pub use self::ton_transaction::*;
+pub use self::transactions_history::*;
 
 mod full_state;
 mod token_transaction;
 mod token_transaction_parser;
 mod ton_transaction;
 mod ton_transaction_parser;
+mod transactions_history;
@@ -1,7 +1,11 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
 use nekoton::core::models::*;
+use nekoton_utils::TrustMe;
+use num_traits::FromPrimitive;
 use tokio::sync::mpsc;
 use ton_types::UInt256;
 
@@ -51,6 +55,9 @@ impl TokenTransaction {
                     }
                 };
 
+                let token_state = event.ctx.token_state.clone();
+                let block_utime = event.ctx.block_utime;
+
                 match token_transaction_parser::parse_token_transaction(
                     event.ctx,
                     event.parsed,
@@ -60,6 +67,19 @@ impl TokenTransaction {
                 .await
                 {
                     Ok(transaction) => {
+                        match make_token_balance_snapshot(&transaction, token_state, block_utime) {
+                            Ok(snapshot) => {
+                                token_transaction
+                                    .context
+                                    .balance_snapshot_producer
+                                    .send(snapshot)
+                                    .ok();
+                            }
+                            Err(e) => {
+                                log::error!("Failed to make token balance snapshot: {:?}", e);
+                            }
+                        }
+
                         token_transaction
                             .token_transaction_producer
                             .send((transaction, event.state))
@@ -78,9 +98,29 @@ impl TokenTransaction {
     }
 }
 
+/// Token balance of the owner after the block with the transaction
+fn make_token_balance_snapshot(
+    transaction: &CreateTokenTransaction,
+    token_state: ExistingContract,
+    block_utime: u32,
+) -> Result<CreateBalanceSnapshot> {
+    let (_, balance) = get_token_wallet_basic_info(&token_state)?;
+    let storage = token_state.account.storage;
+
+    Ok(CreateBalanceSnapshot {
+        account_workchain_id: transaction.account_workchain_id,
+        account_hex: transaction.account_hex.clone(),
+        root_address: Some(transaction.root_address.clone()),
+        balance,
+        last_transaction_lt: BigDecimal::from_u64(storage.last_trans_lt).trust_me(),
+        account_status: storage.state.into(),
+        block_time: NaiveDateTime::from_timestamp_opt(block_utime as i64, 0).trust_me(),
+    })
+}
+
 #[derive(Debug)]
 pub struct TokenTransactionContext {
-    pub account: UInt256,
+    pub account: MsgAddressInt,
     pub block_hash: UInt256,
     pub block_utime: u32,
     pub transaction_hash: UInt256,
@@ -111,7 +151,7 @@ impl ReadFromTransaction for TokenTransactionEvent {
             if let Some(token_state) = &ctx.token_state {
                 event = Some(TokenTransactionEvent {
                     ctx: TokenTransactionContext {
-                        account: *ctx.account,
+                        account: ctx.account.clone(),
                         block_hash: *ctx.block_hash,
                         block_utime: ctx.block_info.gen_utime().as_u32(),
                         transaction_hash: *ctx.transaction_hash,
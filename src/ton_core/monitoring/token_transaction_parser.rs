@@ -3,7 +3,7 @@ use bigdecimal::BigDecimal;
 use nekoton::core::models::{TokenIncomingTransfer, TokenWalletTransaction};
 use num_bigint::BigUint;
 use ton_block::MsgAddressInt;
-use ton_types::{AccountId, BuilderData};
+use ton_types::BuilderData;
 use uuid::Uuid;
 
 use crate::ton_core::*;
@@ -58,11 +58,7 @@ async fn internal_transfer_send(
     payload_cell: Option<ton_types::Cell>,
     parse_ctx: ParseContext<'_>,
 ) -> Result<CreateTokenTransaction> {
-    let address = MsgAddressInt::with_standart(
-        None,
-        ton_block::BASE_WORKCHAIN_ID as i8,
-        AccountId::from(token_transaction_ctx.account),
-    )?;
+    let address = token_transaction_ctx.account.clone();
 
     let owner_info =
         get_token_wallet_info(&address, &parse_ctx, &token_transaction_ctx.token_state).await?;
@@ -112,11 +108,7 @@ async fn internal_transfer_receive(
     token_transfer: TokenIncomingTransfer,
     parse_ctx: ParseContext<'_>,
 ) -> Result<CreateTokenTransaction> {
-    let address = MsgAddressInt::with_standart(
-        None,
-        ton_block::BASE_WORKCHAIN_ID as i8,
-        AccountId::from(token_transaction_ctx.account),
-    )?;
+    let address = token_transaction_ctx.account.clone();
 
     let owner_info =
         get_token_wallet_info(&address, &parse_ctx, &token_transaction_ctx.token_state).await?;
@@ -169,11 +161,7 @@ async fn internal_transfer_bounced(
     tokens: BigUint,
     parse_ctx: ParseContext<'_>,
 ) -> Result<CreateTokenTransaction> {
-    let address = MsgAddressInt::with_standart(
-        None,
-        ton_block::BASE_WORKCHAIN_ID as i8,
-        AccountId::from(token_transaction_ctx.account),
-    )?;
+    let address = token_transaction_ctx.account.clone();
 
     let owner_info =
         get_token_wallet_info(&address, &parse_ctx, &token_transaction_ctx.token_state).await?;
@@ -214,11 +202,7 @@ async fn internal_transfer_mint(
     tokens: BigUint,
     parse_ctx: ParseContext<'_>,
 ) -> Result<CreateTokenTransaction> {
-    let address = MsgAddressInt::with_standart(
-        None,
-        ton_block::BASE_WORKCHAIN_ID as i8,
-        AccountId::from(token_transaction_ctx.account),
-    )?;
+    let address = token_transaction_ctx.account.clone();
 
     let owner_info =
         get_token_wallet_info(&address, &parse_ctx, &token_transaction_ctx.token_state).await?;
@@ -273,7 +257,9 @@ async fn get_token_wallet_info(
                 .sqlx_client
                 .get_root_token(&info.root_address.to_string())
                 .await
-                .map_err(|_| TonCoreError::InvalidRootToken(info.root_address.to_string()))?;
+                .ok()
+                .filter(|root_token| root_token.enabled)
+                .ok_or_else(|| TonCoreError::InvalidRootToken(info.root_address.to_string()))?;
 
             parse_ctx
                 .owners_cache
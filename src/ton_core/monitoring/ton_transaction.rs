@@ -33,7 +33,7 @@ impl TonTransaction {
 
     pub fn add_account_subscription<I>(&self, accounts: I)
     where
-        I: IntoIterator<Item = UInt256>,
+        I: IntoIterator<Item = MsgAddressInt>,
     {
         self.context
             .ton_subscriber
@@ -87,7 +87,7 @@ impl TonTransaction {
 
 #[derive(Debug)]
 pub struct TonTransactionEvent {
-    pub account: UInt256,
+    pub account: MsgAddressInt,
     pub block_utime: u32,
     pub transaction_hash: UInt256,
     pub transaction: ton_block::Transaction,
@@ -100,7 +100,7 @@ impl ReadFromTransaction for TonTransactionEvent {
         state: HandleTransactionStatusTx,
     ) -> Option<Self> {
         Some(TonTransactionEvent {
-            account: *ctx.account,
+            account: ctx.account.clone(),
             block_utime: ctx.block_info.gen_utime().as_u32(),
             transaction_hash: *ctx.transaction_hash,
             transaction: ctx.transaction.clone(),
@@ -5,13 +5,12 @@ use nekoton::core::ton_wallet::MultisigType;
 use num_traits::FromPrimitive;
 use serde::{Deserialize, Serialize};
 use ton_block::CommonMsgInfo;
-use ton_types::AccountId;
 use uuid::Uuid;
 
 use crate::ton_core::*;
 
 pub async fn parse_ton_transaction(
-    account: UInt256,
+    address: MsgAddressInt,
     block_utime: u32,
     transaction_hash: UInt256,
     transaction: ton_block::Transaction,
@@ -22,12 +21,6 @@ pub async fn parse_ton_transaction(
             .map_err(|_| TransactionError::InvalidStructure)?,
         None => return Err(TransactionError::Unsupported.into()),
     };
-    let address = MsgAddressInt::with_standart(
-        None,
-        ton_block::BASE_WORKCHAIN_ID as i8,
-        AccountId::from(account),
-    )?;
-
     let sender_address = get_sender_address(&transaction)?;
     let (sender_workchain_id, sender_hex) = match &sender_address {
         Some(address) => (
@@ -0,0 +1,298 @@
+use std::str::FromStr;
+
+use anyhow::Result;
+use nekoton::core::models::TokenWalletVersion;
+use nekoton_utils::TrustMe;
+use ton_block::{Deserializable, HashmapAugType};
+use ton_indexer::utils::BlockStuff;
+use ton_types::{HashmapType, UInt256};
+
+use crate::ton_core::monitoring::*;
+use crate::ton_core::*;
+
+pub struct HistoryTransaction {
+    pub block_id: ton_block::BlockIdExt,
+    pub block_utime: u32,
+    pub hash: UInt256,
+    pub transaction: ton_block::Transaction,
+}
+
+/// Blocks visited by a single batch, bounds the walk through the blocks without the account transactions
+const MAX_BLOCKS_PER_BATCH: usize = 1000;
+
+impl TonCoreContext {
+    /// Walks the account transactions chain backwards using the local blocks storage,
+    /// starting from the cursor or from the last account transaction.
+    /// Loads at most `limit` transactions and visits at most `MAX_BLOCKS_PER_BATCH` blocks
+    pub async fn load_account_history(
+        &self,
+        address: &MsgAddressInt,
+        cursor: Option<TransactionsHistoryCursor>,
+        limit: usize,
+    ) -> Result<TransactionsHistoryBatch<HistoryTransaction>> {
+        let account = &UInt256::from_be_bytes(&address.address().get_bytestring(0));
+
+        let (mut next, mut block_id) = match cursor {
+            Some(cursor) => (
+                Some(TransactionId {
+                    lt: cursor.transaction_lt,
+                    hash: UInt256::from_be_bytes(&hex::decode(&cursor.transaction_hash)?),
+                }),
+                ton_block::BlockIdExt::from_str(&cursor.block_id)?,
+            ),
+            None => {
+                let block_id = self
+                    .ton_subscriber
+                    .get_shard_block_id(address)
+                    .ok_or(TonCoreError::InvalidContractAddress)?;
+
+                let contract = match self
+                    .ton_subscriber
+                    .get_contract_state(address)
+                    .and_then(make_existing_contract)?
+                {
+                    Some(contract) => contract,
+                    None => return Ok(TransactionsHistoryBatch::empty()),
+                };
+
+                let last_transaction_id = match contract.last_transaction_id {
+                    LastTransactionId::Exact(id) if id.lt != 0 => id,
+                    _ => return Ok(TransactionsHistoryBatch::empty()),
+                };
+
+                (Some(last_transaction_id), block_id)
+            }
+        };
+
+        let mut block: Option<BlockStuff> = None;
+        let mut blocks_visited = 0;
+        let mut batch = TransactionsHistoryBatch::empty();
+
+        while let Some(transaction_id) = next {
+            let loaded = matches!(&block, Some(block) if block.id() == &block_id);
+
+            if batch.transactions.len() >= limit
+                || (!loaded && blocks_visited >= MAX_BLOCKS_PER_BATCH)
+            {
+                batch.cursor = Some(TransactionsHistoryCursor {
+                    transaction_lt: transaction_id.lt,
+                    transaction_hash: transaction_id.hash.to_hex_string(),
+                    block_id: block_id.to_string(),
+                });
+                return Ok(batch);
+            }
+
+            if !loaded {
+                blocks_visited += 1;
+                block = match self.load_block(&block_id).await? {
+                    Some(block) => Some(block),
+                    None => {
+                        // Older history is only available in the archives
+                        log::warn!(
+                            "History of `{}` is cut at transaction {}: block `{}` is pruned",
+                            address,
+                            transaction_id.lt,
+                            block_id
+                        );
+                        batch.pruned_before_lt = Some(transaction_id.lt);
+                        return Ok(batch);
+                    }
+                };
+            }
+            let current = block.as_ref().trust_me();
+
+            let info = current.block().read_info()?;
+            if transaction_id.lt < info.start_lt() {
+                // Transaction is in one of the previous blocks of the shard
+                let (prev1, prev2) = current.construct_prev_id()?;
+                block_id = match prev2 {
+                    Some(prev2) if contains_account(&prev2.shard_id, account) => prev2,
+                    _ => prev1,
+                };
+                continue;
+            }
+
+            let transaction = find_transaction(current.block(), account, &transaction_id.hash)?
+                .ok_or_else(|| {
+                    TonCoreError::TransactionNotFound(transaction_id.hash.to_hex_string())
+                })?;
+
+            next = (transaction.prev_trans_lt != 0).then(|| TransactionId {
+                lt: transaction.prev_trans_lt,
+                hash: transaction.prev_trans_hash,
+            });
+
+            batch.transactions.push(HistoryTransaction {
+                block_id: block_id.clone(),
+                block_utime: info.gen_utime().as_u32(),
+                hash: transaction_id.hash,
+                transaction,
+            });
+        }
+
+        Ok(batch)
+    }
+
+    pub async fn load_ton_transactions_history(
+        &self,
+        address: &MsgAddressInt,
+        cursor: Option<TransactionsHistoryCursor>,
+        limit: usize,
+    ) -> Result<TransactionsHistoryBatch<CaughtTonTransaction>> {
+        let history = self.load_account_history(address, cursor, limit).await?;
+
+        let mut transactions = Vec::with_capacity(history.transactions.len());
+        for item in history.transactions {
+            // Skip non-ordinary transactions
+            if !matches!(
+                item.transaction.description.read_struct(),
+                Ok(ton_block::TransactionDescr::Ordinary(_))
+            ) {
+                continue;
+            }
+
+            match ton_transaction_parser::parse_ton_transaction(
+                address.clone(),
+                item.block_utime,
+                item.hash,
+                item.transaction,
+            )
+            .await
+            {
+                Ok(transaction) => transactions.push(transaction),
+                Err(e) => log::warn!(
+                    "Failed to parse history ton transaction `{}`: {}",
+                    item.hash.to_hex_string(),
+                    e
+                ),
+            }
+        }
+
+        Ok(TransactionsHistoryBatch {
+            transactions,
+            cursor: history.cursor,
+            pruned_before_lt: history.pruned_before_lt,
+        })
+    }
+
+    pub async fn load_token_transactions_history(
+        &self,
+        address: &MsgAddressInt,
+        cursor: Option<TransactionsHistoryCursor>,
+        limit: usize,
+    ) -> Result<TransactionsHistoryBatch<CreateTokenTransaction>> {
+        let token_state = match self
+            .ton_subscriber
+            .get_contract_state(address)
+            .and_then(make_existing_contract)?
+        {
+            Some(token_state) => token_state,
+            None => return Ok(TransactionsHistoryBatch::empty()),
+        };
+
+        let history = self.load_account_history(address, cursor, limit).await?;
+
+        let mut transactions = Vec::with_capacity(history.transactions.len());
+        for item in history.transactions {
+            let transaction_info = match item.transaction.description.read_struct() {
+                Ok(ton_block::TransactionDescr::Ordinary(info)) if !info.aborted => info,
+                _ => continue,
+            };
+
+            let parsed = match nekoton::core::parsing::parse_token_transaction(
+                &item.transaction,
+                &transaction_info,
+                TokenWalletVersion::Tip3,
+            )
+            .or_else(|| {
+                nekoton::core::parsing::parse_token_transaction(
+                    &item.transaction,
+                    &transaction_info,
+                    TokenWalletVersion::OldTip3v4,
+                )
+            }) {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+
+            let in_msg = match item.transaction.in_msg.as_ref().map(|m| m.read_struct()) {
+                Some(Ok(message)) => message,
+                _ => continue,
+            };
+
+            let ctx = TokenTransactionContext {
+                account: address.clone(),
+                block_hash: item.block_id.root_hash,
+                block_utime: item.block_utime,
+                transaction_hash: item.hash,
+                transaction: item.transaction,
+                token_state: token_state.clone(),
+                in_msg,
+            };
+
+            match token_transaction_parser::parse_token_transaction(
+                ctx,
+                parsed,
+                &self.sqlx_client,
+                &self.owners_cache,
+            )
+            .await
+            {
+                Ok(transaction) => transactions.push(transaction),
+                Err(e) => log::warn!(
+                    "Failed to parse history token transaction `{}`: {}",
+                    item.hash.to_hex_string(),
+                    e
+                ),
+            }
+        }
+
+        Ok(TransactionsHistoryBatch {
+            transactions,
+            cursor: history.cursor,
+            pruned_before_lt: history.pruned_before_lt,
+        })
+    }
+
+    /// Returns `None` if the block is not in the local storage anymore
+    async fn load_block(&self, block_id: &ton_block::BlockIdExt) -> Result<Option<BlockStuff>> {
+        let storage = self.ton_engine.storage();
+
+        let handle = match storage
+            .block_handle_storage()
+            .load_handle(block_id)?
+            .filter(|handle| handle.meta().has_data())
+        {
+            Some(handle) => handle,
+            None => return Ok(None),
+        };
+
+        storage
+            .block_storage()
+            .load_block_data(&handle)
+            .await
+            .map(Some)
+    }
+}
+
+fn find_transaction(
+    block: &ton_block::Block,
+    account: &UInt256,
+    hash: &UInt256,
+) -> Result<Option<ton_block::Transaction>> {
+    let account_blocks = block.read_extra()?.read_account_blocks()?;
+    let account_block = match account_blocks.get(account)? {
+        Some(account_block) => account_block,
+        None => return Ok(None),
+    };
+
+    for item in account_block.transactions().iter() {
+        let (_, value) = item?;
+        let cell = value.into_cell().reference(0)?;
+        if &cell.repr_hash() == hash {
+            return Ok(Some(ton_block::Transaction::construct_from_cell(cell)?));
+        }
+    }
+
+    Ok(None)
+}
@@ -47,6 +47,9 @@ pub struct NodeConfig {
 
     /// Recover indexer db
     pub recover_indexer: bool,
+
+    /// Rebroadcast of sent external messages
+    pub rebroadcast: RebroadcastConfig,
 }
 
 impl NodeConfig {
@@ -121,6 +124,38 @@ impl Default for NodeConfig {
             neighbours_options: Default::default(),
             overlay_shard_options: Default::default(),
             recover_indexer: false,
+            rebroadcast: Default::default(),
+        }
+    }
+}
+
+/// Sent external messages are broadcast again until they are included
+/// into a block, expire or the attempts run out
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RebroadcastConfig {
+    /// Delay between broadcasts of the message. Default: 10
+    pub interval_sec: u64,
+
+    /// Broadcasts of the message including the first one, 1 disables rebroadcast. Default: 5
+    pub max_attempts: u32,
+}
+
+impl RebroadcastConfig {
+    pub fn validate(&self) -> Result<()> {
+        anyhow::ensure!(
+            self.interval_sec > 0,
+            "Rebroadcast interval must be greater than zero"
+        );
+        Ok(())
+    }
+}
+
+impl Default for RebroadcastConfig {
+    fn default() -> Self {
+        Self {
+            interval_sec: 10,
+            max_attempts: 5,
         }
     }
 }
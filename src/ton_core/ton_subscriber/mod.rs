@@ -3,19 +3,22 @@ use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Weak};
 
 use anyhow::Result;
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 use nekoton::core::models::TokenWalletVersion;
 use nekoton::transport::models::ExistingContract;
 use nekoton_utils::TrustMe;
+use num_traits::FromPrimitive;
 use parking_lot::{Mutex, RwLock, RwLockReadGuard};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use tokio::sync::Notify;
 use ton_block::{Deserializable, HashmapAugType, ShardIdent};
 use ton_indexer::utils::{BlockIdExtExtension, RefMcStateHandle, ShardStateStuff};
 use ton_indexer::{BriefBlockMeta, EngineStatus, ProcessBlockContext};
-use ton_types::{HashmapType, UInt256};
+use ton_types::{AccountId, HashmapType, UInt256};
 
 use crate::ton_core::*;
 
@@ -25,26 +28,34 @@ pub struct TonSubscriber {
     // tip block timestamp
     current_utime: AtomicU32,
     signature_id: SignatureId,
-    state_subscriptions: RwLock<FxHashMap<UInt256, StateSubscription>>,
+    blockchain_config: RwLock<Option<ton_executor::BlockchainConfig>>,
+    state_subscriptions: RwLock<FxHashMap<MsgAddressInt, StateSubscription>>,
     token_subscription: RwLock<Option<TokenSubscription>>,
+    root_tokens: RwLock<FxHashSet<MsgAddressInt>>,
     full_state_subscription: RwLock<Option<FullStateSubscription>>,
     shards_accounts_cache: RwLock<FxHashMap<ShardIdent, ShardAccounts>>,
     mc_block_awaiters: Mutex<FxHashMap<usize, Box<dyn BlockAwaiter>>>,
     messages_queue: Arc<PendingMessagesQueue>,
+    balance_snapshot_producer: BalanceSnapshotTx,
 }
 
 impl TonSubscriber {
-    pub fn new(messages_queue: Arc<PendingMessagesQueue>) -> Arc<Self> {
+    pub fn new(
+        messages_queue: Arc<PendingMessagesQueue>,
+        balance_snapshot_producer: BalanceSnapshotTx,
+    ) -> Arc<Self> {
         Arc::new(Self {
             ready: AtomicBool::new(false),
             ready_signal: Notify::new(),
             current_utime: AtomicU32::new(0),
             signature_id: SignatureId::default(),
+            blockchain_config: Default::default(),
             state_subscriptions: RwLock::new(FxHashMap::with_capacity_and_hasher(
                 1024,
                 Default::default(),
             )),
             token_subscription: Default::default(),
+            root_tokens: Default::default(),
             full_state_subscription: Default::default(),
             shards_accounts_cache: RwLock::new(FxHashMap::with_capacity_and_hasher(
                 16,
@@ -55,6 +66,7 @@ impl TonSubscriber {
                 Default::default(),
             )),
             messages_queue,
+            balance_snapshot_producer,
         })
     }
 
@@ -69,7 +81,7 @@ impl TonSubscriber {
 
     pub async fn start(self: &Arc<Self>, engine: &ton_indexer::Engine) -> Result<()> {
         let last_key_block = engine.load_last_key_block().await?;
-        self.update_signature_id(last_key_block.block())?;
+        self.update_key_block_params(last_key_block.block())?;
 
         self.wait_sync().await;
         Ok(())
@@ -83,9 +95,14 @@ impl TonSubscriber {
         self.signature_id.load()
     }
 
+    /// Returns the config from the last known key block
+    pub fn blockchain_config(&self) -> Option<ton_executor::BlockchainConfig> {
+        self.blockchain_config.read().clone()
+    }
+
     pub fn add_transactions_subscription<I, T>(&self, accounts: I, subscription: &Arc<T>)
     where
-        I: IntoIterator<Item = UInt256>,
+        I: IntoIterator<Item = MsgAddressInt>,
         T: TransactionsSubscription + 'static,
     {
         let mut state_subscriptions = self.state_subscriptions.write();
@@ -119,6 +136,23 @@ impl TonSubscriber {
         });
     }
 
+    /// Replaces the set of root tokens whose wallets are tracked
+    pub fn set_root_tokens<I>(&self, root_tokens: I)
+    where
+        I: IntoIterator<Item = MsgAddressInt>,
+    {
+        *self.root_tokens.write() = root_tokens.into_iter().collect();
+    }
+
+    pub fn update_root_token(&self, root_token: MsgAddressInt, enabled: bool) {
+        let mut root_tokens = self.root_tokens.write();
+        if enabled {
+            root_tokens.insert(root_token);
+        } else {
+            root_tokens.remove(&root_token);
+        }
+    }
+
     pub fn add_full_state_subscription<T>(&self, subscription: &Arc<T>)
     where
         T: FullStatesSubscription + 'static,
@@ -132,17 +166,35 @@ impl TonSubscriber {
         });
     }
 
-    pub fn get_contract_state(&self, account: &UInt256) -> Result<Option<ShardAccount>> {
+    pub fn get_contract_state(&self, address: &MsgAddressInt) -> Result<Option<ShardAccount>> {
+        let account = UInt256::from_be_bytes(&address.address().get_bytestring(0));
+
         let cache = self.shards_accounts_cache.read();
         for (shard_ident, shard_accounts) in cache.iter() {
-            if !contains_account(shard_ident, account) {
+            if shard_ident.workchain_id() != address.workchain_id()
+                || !contains_account(shard_ident, &account)
+            {
                 continue;
             }
-            return shard_accounts.get(account);
+            return shard_accounts.get(&account);
         }
         Ok(None)
     }
 
+    /// Returns the id of the latest known block of the shard containing the account
+    pub fn get_shard_block_id(&self, address: &MsgAddressInt) -> Option<ton_block::BlockIdExt> {
+        let account = UInt256::from_be_bytes(&address.address().get_bytestring(0));
+
+        let cache = self.shards_accounts_cache.read();
+        cache
+            .iter()
+            .find(|(shard_ident, _)| {
+                shard_ident.workchain_id() == address.workchain_id()
+                    && contains_account(shard_ident, &account)
+            })
+            .map(|(_, shard_accounts)| shard_accounts.block_id.clone())
+    }
+
     pub fn update_shards_accounts_cache(
         &self,
         shard_id: ShardIdent,
@@ -157,6 +209,7 @@ impl TonSubscriber {
             ShardAccounts {
                 accounts: shard_accounts,
                 state_handle,
+                block_id: shard_state.block_id().clone(),
             },
         );
 
@@ -173,7 +226,7 @@ impl TonSubscriber {
 
         let block_info = block.info.read_struct()?;
         if block_info.key_block() {
-            self.update_signature_id(block)?;
+            self.update_key_block_params(block)?;
         }
 
         if !self.ready.load(Ordering::Acquire) {
@@ -203,6 +256,7 @@ impl TonSubscriber {
         let block_info = block.info.read_struct()?;
         let extra = block.extra.read_struct()?;
         let account_blocks = extra.read_account_blocks()?;
+        let workchain_id = block_info.shard().workchain_id();
 
         if let Some(shard_state) = shard_state {
             let shard_accounts = shard_state.state().read_accounts()?;
@@ -214,6 +268,7 @@ impl TonSubscriber {
                 ShardAccounts {
                     accounts: shard_accounts,
                     state_handle,
+                    block_id: shard_state.block_id().clone(),
                 },
             );
             if block_info.after_merge() || block_info.after_split() {
@@ -242,16 +297,21 @@ impl TonSubscriber {
 
         let state_subscriptions = self.state_subscriptions.read();
         let token_subscription = self.token_subscription.read();
+        let root_tokens = self.root_tokens.read();
         let shards_accounts_cache = self.shards_accounts_cache.read();
 
         account_blocks.iterate_with_keys(|account, account_block| {
-            match state_subscriptions.get(&account) {
+            let address =
+                MsgAddressInt::with_standart(None, workchain_id as i8, AccountId::from(account))?;
+
+            match state_subscriptions.get(&address) {
                 Some(subscription) => {
                     match subscription.handle_block(
                         &self.messages_queue,
                         &block_info,
                         &account_block,
                         &account,
+                        &address,
                         block_hash,
                     ) {
                         Ok(rx_states) => {
@@ -263,16 +323,34 @@ impl TonSubscriber {
                             log::error!("Failed to handle block: {:?}", e);
                         }
                     };
+
+                    match make_balance_snapshot(
+                        &shards_accounts_cache,
+                        &block_info,
+                        &account,
+                        &address,
+                    ) {
+                        Ok(Some(snapshot)) => {
+                            self.balance_snapshot_producer.send(snapshot).ok();
+                        }
+                        Ok(None) => {}
+                        Err(e) => {
+                            log::error!("Failed to make balance snapshot: {:?}", e);
+                        }
+                    }
                 }
-                None => {
+                // Token wallets are tracked in the base workchain only
+                None if workchain_id == ton_block::BASE_WORKCHAIN_ID => {
                     let token_subscription = token_subscription.as_ref().trust_me();
 
                     match token_subscription.handle_block(
                         &state_subscriptions,
+                        &root_tokens,
                         &shards_accounts_cache,
                         &block_info,
                         &account_block,
                         &account,
+                        &address,
                         block_hash,
                     ) {
                         Ok(rx_states) => {
@@ -285,6 +363,7 @@ impl TonSubscriber {
                         }
                     }
                 }
+                None => {}
             };
 
             Ok(true)
@@ -309,6 +388,7 @@ impl TonSubscriber {
             ShardAccounts {
                 accounts: shard_accounts,
                 state_handle,
+                block_id: state.block_id().clone(),
             },
         );
         drop(shards_accounts);
@@ -320,7 +400,7 @@ impl TonSubscriber {
             .handle_full_state(state)
     }
 
-    fn update_signature_id(&self, key_block: &ton_block::Block) -> Result<()> {
+    fn update_key_block_params(&self, key_block: &ton_block::Block) -> Result<()> {
         let extra = key_block.read_extra()?;
         let custom = extra
             .read_custom()?
@@ -332,6 +412,11 @@ impl TonSubscriber {
         self.signature_id
             .store(config.capabilities(), key_block.global_id);
 
+        *self.blockchain_config.write() = Some(ton_executor::BlockchainConfig::with_config(
+            config.clone(),
+            key_block.global_id,
+        )?);
+
         Ok(())
     }
 
@@ -356,16 +441,18 @@ impl ton_indexer::Subscriber for TonSubscriber {
     async fn process_block(&self, ctx: ProcessBlockContext<'_>) -> Result<()> {
         if ctx.block_stuff().id().is_masterchain() {
             self.handle_masterchain_block(ctx.meta(), ctx.block())?;
-        } else {
-            let mut states = self.handle_shard_block(
-                ctx.block(),
-                ctx.shard_state_stuff(),
-                &ctx.block_stuff().id().root_hash,
-            )?;
-            while let Some(status) = states.next().await {
-                if let Err(err) = status {
-                    log::error!("Failed to receive transaction status: {}", err);
-                }
+        }
+
+        // Masterchain state is cached like the shard ones, so that its accounts
+        // can be estimated and sent from
+        let mut states = self.handle_shard_block(
+            ctx.block(),
+            ctx.shard_state_stuff(),
+            &ctx.block_stuff().id().root_hash,
+        )?;
+        while let Some(status) = states.next().await {
+            if let Err(err) = status {
+                log::error!("Failed to receive transaction status: {}", err);
             }
         }
 
@@ -405,6 +492,7 @@ impl StateSubscription {
         block_info: &ton_block::BlockInfo,
         account_block: &ton_block::AccountBlock,
         account: &UInt256,
+        address: &MsgAddressInt,
         block_hash: &UInt256,
     ) -> Result<FuturesUnordered<HandleTransactionStatusRx>> {
         let states = FuturesUnordered::new();
@@ -447,7 +535,7 @@ impl StateSubscription {
             {
                 Some((message_cell, Ok(message))) => {
                     if matches!(message.header(), ton_block::CommonMsgInfo::ExtInMsgInfo(_)) {
-                        messages_queue.deliver_message(*account, message_cell.hash());
+                        messages_queue.deliver_message(address.clone(), message_cell.hash());
                     }
                     message
                 }
@@ -457,7 +545,7 @@ impl StateSubscription {
             let ctx = TxContext {
                 block_info,
                 block_hash,
-                account,
+                account: address,
                 transaction_hash: &hash,
                 transaction_info: &transaction_info,
                 transaction: &transaction,
@@ -504,11 +592,13 @@ struct TokenSubscription {
 impl TokenSubscription {
     fn handle_block(
         &self,
-        state_subscriptions: &RwLockReadGuard<FxHashMap<UInt256, StateSubscription>>,
+        state_subscriptions: &RwLockReadGuard<FxHashMap<MsgAddressInt, StateSubscription>>,
+        root_tokens: &FxHashSet<MsgAddressInt>,
         shards_accounts_cache: &FxHashMap<ShardIdent, ShardAccounts>,
         block_info: &ton_block::BlockInfo,
         account_block: &ton_block::AccountBlock,
         account: &UInt256,
+        address: &MsgAddressInt,
         block_hash: &UInt256,
     ) -> Result<FuturesUnordered<HandleTransactionStatusRx>> {
         let states = FuturesUnordered::new();
@@ -559,14 +649,12 @@ impl TokenSubscription {
                     .ok_or_else(|| TonCoreError::AccountNotExist(account.to_string()))?;
 
                 let (token_wallet_details, ..) = get_token_wallet_details(&token_contract)?;
-                let owner_account = UInt256::from_be_bytes(
-                    &token_wallet_details
-                        .owner_address
-                        .address()
-                        .get_bytestring(0),
-                );
-
-                if state_subscriptions.get(&owner_account).is_some() {
+
+                if root_tokens.contains(&token_wallet_details.root_address)
+                    && state_subscriptions
+                        .get(&token_wallet_details.owner_address)
+                        .is_some()
+                {
                     let in_msg = match transaction
                         .in_msg
                         .as_ref()
@@ -579,7 +667,7 @@ impl TokenSubscription {
                     let ctx = TxContext {
                         block_info,
                         block_hash,
-                        account,
+                        account: address,
                         transaction_hash: &hash,
                         transaction_info: &transaction_info,
                         transaction: &transaction,
@@ -728,6 +816,41 @@ pub struct ShardAccount {
     _state_handle: Arc<RefMcStateHandle>,
 }
 
+/// State of the account after the block
+fn make_balance_snapshot(
+    shards_accounts_cache: &FxHashMap<ShardIdent, ShardAccounts>,
+    block_info: &ton_block::BlockInfo,
+    account: &UInt256,
+    address: &MsgAddressInt,
+) -> Result<Option<CreateBalanceSnapshot>> {
+    // The account may be in the masterchain, unlike the token wallets
+    let shard = shards_accounts_cache.iter().find(|(shard_ident, _)| {
+        shard_ident.workchain_id() == address.workchain_id()
+            && contains_account(shard_ident, account)
+    });
+    let contract = match shard {
+        Some((_, shard)) => make_existing_contract(shard.get(account)?)?,
+        None => None,
+    };
+    let contract = match contract {
+        Some(contract) => contract,
+        None => return Ok(None),
+    };
+
+    let storage = contract.account.storage;
+
+    Ok(Some(CreateBalanceSnapshot {
+        account_workchain_id: address.workchain_id(),
+        account_hex: account.to_hex_string(),
+        root_address: None,
+        balance: BigDecimal::from_u128(storage.balance.grams.as_u128()).trust_me(),
+        last_transaction_lt: BigDecimal::from_u64(storage.last_trans_lt).trust_me(),
+        account_status: storage.state.into(),
+        block_time: NaiveDateTime::from_timestamp_opt(block_info.gen_utime().as_u32() as i64, 0)
+            .trust_me(),
+    }))
+}
+
 pub fn make_existing_contract(state: Option<ShardAccount>) -> Result<Option<ExistingContract>> {
     let state = match state {
         Some(this) => this,
@@ -747,6 +870,7 @@ pub fn make_existing_contract(state: Option<ShardAccount>) -> Result<Option<Exis
 pub struct ShardAccounts {
     pub accounts: ton_block::ShardAccounts,
     pub state_handle: Arc<RefMcStateHandle>,
+    pub block_id: ton_block::BlockIdExt,
 }
 
 impl ShardAccounts {
@@ -765,11 +889,13 @@ impl ShardAccounts {
     }
 }
 
+/// Looks for the account in the base workchain shards
 impl ShardAccountsMapExt for FxHashMap<ShardIdent, ShardAccounts> {
     fn find_account(&self, account: &UInt256) -> Result<Option<ExistingContract>> {
-        let item = self
-            .iter()
-            .find(|(shard_ident, _)| contains_account(shard_ident, account));
+        let item = self.iter().find(|(shard_ident, _)| {
+            shard_ident.workchain_id() == ton_block::BASE_WORKCHAIN_ID
+                && contains_account(shard_ident, account)
+        });
 
         match item {
             Some((_, shard)) => shard.accounts.find_account(account),
@@ -1,4 +1,6 @@
-use anyhow::{Error, Result};
+use std::collections::HashMap;
+
+use anyhow::{Context, Error, Result};
 use chacha20poly1305::aead::AeadMut;
 use chacha20poly1305::{ChaCha20Poly1305, Nonce};
 
@@ -20,3 +22,207 @@ pub fn decrypt_private_key(private_key: &str, key: [u8; 32], id: &uuid::Uuid) ->
         .decrypt(nonce, base64::decode(private_key)?.as_slice())
         .map_err(Error::msg)
 }
+
+/// Set of keys to encrypt/decrypt private keys with.
+///
+/// Private keys are stored as `<key id>:<ciphertext>`. Ciphertexts without
+/// the key id were encrypted with the legacy key.
+#[derive(Clone)]
+pub struct Keyring {
+    legacy: Option<[u8; 32]>,
+    keys: HashMap<String, [u8; 32]>,
+    active: Option<String>,
+}
+
+impl Keyring {
+    pub fn new(
+        legacy: Option<Vec<u8>>,
+        keys: Vec<(String, Vec<u8>)>,
+        active: Option<String>,
+    ) -> Result<Self> {
+        let legacy: Option<[u8; 32]> = legacy
+            .map(|key| key.as_slice().try_into())
+            .transpose()
+            .context("Invalid legacy key size")?;
+
+        let active = active.or_else(|| keys.last().map(|(id, _)| id.clone()));
+
+        let mut map = HashMap::with_capacity(keys.len());
+        for (id, key) in keys {
+            if id.is_empty() || id.contains(KEY_ID_DELIMITER) {
+                anyhow::bail!("Invalid key id `{}`", id);
+            }
+
+            let key: [u8; 32] = key
+                .as_slice()
+                .try_into()
+                .with_context(|| format!("Invalid size of key `{}`", id))?;
+            if map.insert(id.clone(), key).is_some() {
+                anyhow::bail!("Duplicate key id `{}`", id);
+            }
+        }
+
+        match &active {
+            Some(id) if !map.contains_key(id) => anyhow::bail!("Unknown active key `{}`", id),
+            None if legacy.is_none() => anyhow::bail!("No key to encrypt private keys"),
+            _ => {}
+        }
+
+        Ok(Self {
+            legacy,
+            keys: map,
+            active,
+        })
+    }
+
+    /// Id of the key new private keys are encrypted with, `None` for the legacy one
+    pub fn active_key_id(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Returns a copy of the keyring which encrypts with the specified key
+    pub fn with_active_key(&self, id: &str) -> Result<Self> {
+        if !self.keys.contains_key(id) {
+            anyhow::bail!("Unknown key `{}`", id);
+        }
+
+        Ok(Self {
+            active: Some(id.to_owned()),
+            ..self.clone()
+        })
+    }
+
+    /// Whether the private key is encrypted with the active key
+    pub fn is_active(&self, private_key: &str) -> bool {
+        split_key_id(private_key).0 == self.active.as_deref()
+    }
+
+    pub fn encrypt(&self, private_key: &[u8], id: &uuid::Uuid) -> Result<String> {
+        match &self.active {
+            Some(key_id) => {
+                let key = self.keys[key_id];
+                let encrypted = encrypt_private_key(private_key, key, id)?;
+                Ok(format!("{}{}{}", key_id, KEY_ID_DELIMITER, encrypted))
+            }
+            None => {
+                let key = self.legacy.context("Legacy key not set")?;
+                encrypt_private_key(private_key, key, id)
+            }
+        }
+    }
+
+    pub fn decrypt(&self, private_key: &str, id: &uuid::Uuid) -> Result<Vec<u8>> {
+        let (key_id, encrypted) = split_key_id(private_key);
+        let key = match key_id {
+            Some(key_id) => *self
+                .keys
+                .get(key_id)
+                .with_context(|| format!("Unknown key `{}`", key_id))?,
+            None => self.legacy.context("Legacy key not set")?,
+        };
+
+        decrypt_private_key(encrypted, key, id)
+    }
+}
+
+const KEY_ID_DELIMITER: char = ':';
+
+fn split_key_id(private_key: &str) -> (Option<&str>, &str) {
+    match private_key.split_once(KEY_ID_DELIMITER) {
+        Some((key_id, encrypted)) => (Some(key_id), encrypted),
+        None => (None, private_key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn keyring() -> Keyring {
+        Keyring::new(
+            Some(vec![1; 32]),
+            vec![
+                ("v1".to_owned(), vec![2; 32]),
+                ("v2".to_owned(), vec![3; 32]),
+            ],
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn versioned_key_roundtrip() {
+        let keyring = keyring();
+        assert_eq!(keyring.active_key_id(), Some("v2"));
+
+        let id = Uuid::new_v4();
+        let encrypted = keyring.encrypt(b"private key", &id).unwrap();
+        assert!(encrypted.starts_with("v2:"));
+        assert_eq!(keyring.decrypt(&encrypted, &id).unwrap(), b"private key");
+
+        // The nonce depends on the address id
+        assert!(keyring.decrypt(&encrypted, &Uuid::new_v4()).is_err());
+    }
+
+    #[test]
+    fn legacy_ciphertext_is_decrypted() {
+        let id = Uuid::new_v4();
+        let encrypted = encrypt_private_key(b"private key", [1; 32], &id).unwrap();
+
+        let keyring = keyring();
+        assert!(!keyring.is_active(&encrypted));
+        assert_eq!(keyring.decrypt(&encrypted, &id).unwrap(), b"private key");
+
+        // Without versioned keys the legacy one is active
+        let keyring = Keyring::new(Some(vec![1; 32]), Vec::new(), None).unwrap();
+        assert_eq!(keyring.active_key_id(), None);
+        assert!(keyring.is_active(&encrypted));
+        assert_eq!(keyring.encrypt(b"private key", &id).unwrap(), encrypted);
+    }
+
+    #[test]
+    fn unknown_key_id_is_rejected() {
+        let id = Uuid::new_v4();
+        let encrypted = encrypt_private_key(b"private key", [4; 32], &id).unwrap();
+
+        let keyring = keyring();
+        assert!(keyring.decrypt(&format!("v3:{}", encrypted), &id).is_err());
+        assert!(keyring.with_active_key("v3").is_err());
+
+        let keyring = Keyring::new(None, vec![("v1".to_owned(), vec![2; 32])], None).unwrap();
+        assert!(keyring.decrypt(&encrypted, &id).is_err());
+    }
+
+    #[test]
+    fn invalid_keys_are_rejected() {
+        let key = |id: &str, size: usize| (id.to_owned(), vec![2; size]);
+
+        assert!(Keyring::new(None, vec![key("v1", 32), key("v1", 32)], None).is_err());
+        assert!(Keyring::new(None, vec![key("v:1", 32)], None).is_err());
+        assert!(Keyring::new(None, vec![key("", 32)], None).is_err());
+        assert!(Keyring::new(None, vec![key("v1", 31)], None).is_err());
+        assert!(Keyring::new(Some(vec![1; 33]), Vec::new(), None).is_err());
+        assert!(Keyring::new(None, vec![key("v1", 32)], Some("v2".to_owned())).is_err());
+        assert!(Keyring::new(None, Vec::new(), None).is_err());
+    }
+
+    #[test]
+    fn active_key_is_switched() {
+        let keyring = keyring();
+        let id = Uuid::new_v4();
+        let encrypted = keyring.encrypt(b"private key", &id).unwrap();
+        assert!(keyring.is_active(&encrypted));
+
+        let rotated = keyring.with_active_key("v1").unwrap();
+        assert_eq!(rotated.active_key_id(), Some("v1"));
+        assert!(!rotated.is_active(&encrypted));
+        assert_eq!(rotated.decrypt(&encrypted, &id).unwrap(), b"private key");
+
+        let reencrypted = rotated.encrypt(b"private key", &id).unwrap();
+        assert!(reencrypted.starts_with("v1:"));
+        assert!(rotated.is_active(&reencrypted));
+        assert!(!keyring.is_active(&reencrypted));
+    }
+}
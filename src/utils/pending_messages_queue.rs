@@ -6,6 +6,7 @@ use anyhow::Result;
 use parking_lot::Mutex;
 use rustc_hash::FxHashMap;
 use tokio::sync::oneshot;
+use ton_block::MsgAddressInt;
 use ton_types::UInt256;
 
 use super::shard_utils::*;
@@ -38,7 +39,7 @@ impl PendingMessagesQueue {
 
     pub fn add_message(
         &self,
-        account: UInt256,
+        account: MsgAddressInt,
         message_hash: UInt256,
         expire_at: u32,
     ) -> Result<MessageStatusRx> {
@@ -64,7 +65,7 @@ impl PendingMessagesQueue {
         }
     }
 
-    pub fn deliver_message(&self, account: UInt256, message_hash: UInt256) {
+    pub fn deliver_message(&self, account: MsgAddressInt, message_hash: UInt256) {
         let mut entries = self.entries.lock();
         let mut message = match entries.remove(&PendingMessageId {
             account,
@@ -105,7 +106,7 @@ impl PendingMessagesQueue {
 
         let mut entries = self.entries.lock();
         entries.retain(|id, item| {
-            if current_utime <= item.expire_at || !contains_account(shard, &id.account) {
+            if current_utime <= item.expire_at || !id.is_in_shard(shard) {
                 if item.expire_at < min_expire_at {
                     min_expire_at = item.expire_at;
                 }
@@ -129,12 +130,22 @@ pub enum MessageStatus {
     Expired,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 struct PendingMessageId {
-    account: UInt256,
+    account: MsgAddressInt,
     message_hash: UInt256,
 }
 
+impl PendingMessageId {
+    fn is_in_shard(&self, shard: &ton_block::ShardIdent) -> bool {
+        shard.workchain_id() == self.account.workchain_id()
+            && contains_account(
+                shard,
+                &UInt256::from_be_bytes(&self.account.address().get_bytestring(0)),
+            )
+    }
+}
+
 struct PendingMessage {
     tx: Option<MessageStatusTx>,
     expire_at: u32,
@@ -159,6 +170,10 @@ mod tests {
         UInt256::from(hash)
     }
 
+    fn make_address(id: u8) -> MsgAddressInt {
+        MsgAddressInt::with_standart(None, 0, make_hash(id).into()).unwrap()
+    }
+
     fn make_queue() -> Arc<PendingMessagesQueue> {
         let queue = PendingMessagesQueue::new(10);
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), u32::MAX);
@@ -170,15 +185,19 @@ mod tests {
         let queue = make_queue();
 
         // Add message
-        let rx = queue.add_message(make_hash(0), make_hash(0), 10).unwrap();
+        let rx = queue
+            .add_message(make_address(0), make_hash(0), 10)
+            .unwrap();
 
         // (Adding same message should fail)
-        assert!(queue.add_message(make_hash(0), make_hash(0), 20).is_err());
+        assert!(queue
+            .add_message(make_address(0), make_hash(0), 20)
+            .is_err());
         // Adding new message must update expiration
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), 10);
 
         // Deliver message
-        queue.deliver_message(make_hash(0), make_hash(0));
+        queue.deliver_message(make_address(0), make_hash(0));
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), u32::MAX);
         assert_eq!(rx.await.unwrap(), MessageStatus::Delivered);
     }
@@ -188,14 +207,16 @@ mod tests {
         let queue = make_queue();
 
         // Add message
-        let rx = queue.add_message(make_hash(0), make_hash(0), 10).unwrap();
+        let rx = queue
+            .add_message(make_address(0), make_hash(0), 10)
+            .unwrap();
 
         // Update before expiration time must not do anything
-        queue.update(&ton_block::ShardIdent::masterchain(), 5);
+        queue.update(&ton_block::ShardIdent::full(0), 5);
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), 10);
 
         // Update after expiration time must remove message
-        queue.update(&ton_block::ShardIdent::masterchain(), 15);
+        queue.update(&ton_block::ShardIdent::full(0), 15);
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), u32::MAX);
         assert_eq!(rx.await.unwrap(), MessageStatus::Expired);
     }
@@ -205,19 +226,23 @@ mod tests {
         let queue = make_queue();
 
         // Add messages
-        let rx2 = queue.add_message(make_hash(1), make_hash(1), 20).unwrap();
-        let rx1 = queue.add_message(make_hash(0), make_hash(0), 10).unwrap();
-
-        queue.update(&ton_block::ShardIdent::masterchain(), 5);
+        let rx2 = queue
+            .add_message(make_address(1), make_hash(1), 20)
+            .unwrap();
+        let rx1 = queue
+            .add_message(make_address(0), make_hash(0), 10)
+            .unwrap();
+
+        queue.update(&ton_block::ShardIdent::full(0), 5);
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), 10);
 
-        queue.update(&ton_block::ShardIdent::masterchain(), 10);
+        queue.update(&ton_block::ShardIdent::full(0), 10);
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), 10);
 
-        queue.update(&ton_block::ShardIdent::masterchain(), 15);
+        queue.update(&ton_block::ShardIdent::full(0), 15);
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), 20);
 
-        queue.update(&ton_block::ShardIdent::masterchain(), 25);
+        queue.update(&ton_block::ShardIdent::full(0), 25);
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), u32::MAX);
 
         assert_eq!(rx1.await.unwrap(), MessageStatus::Expired);
@@ -229,32 +254,63 @@ mod tests {
         let queue = make_queue();
 
         // Add messages
-        let rx2 = queue.add_message(make_hash(1), make_hash(1), 20).unwrap();
-        let rx1 = queue.add_message(make_hash(0), make_hash(0), 10).unwrap();
-
-        queue.update(&ton_block::ShardIdent::masterchain(), 5);
+        let rx2 = queue
+            .add_message(make_address(1), make_hash(1), 20)
+            .unwrap();
+        let rx1 = queue
+            .add_message(make_address(0), make_hash(0), 10)
+            .unwrap();
+
+        queue.update(&ton_block::ShardIdent::full(0), 5);
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), 10);
 
-        queue.deliver_message(make_hash(1), make_hash(1));
+        queue.deliver_message(make_address(1), make_hash(1));
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), 10);
 
-        queue.update(&ton_block::ShardIdent::masterchain(), 15);
+        queue.update(&ton_block::ShardIdent::full(0), 15);
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), u32::MAX);
 
         assert_eq!(rx1.await.unwrap(), MessageStatus::Expired);
         assert_eq!(rx2.await.unwrap(), MessageStatus::Delivered);
 
         // Add messages
-        let rx1 = queue.add_message(make_hash(0), make_hash(0), 10).unwrap();
-        let rx2 = queue.add_message(make_hash(1), make_hash(1), 20).unwrap();
-
-        queue.deliver_message(make_hash(0), make_hash(0));
+        let rx1 = queue
+            .add_message(make_address(0), make_hash(0), 10)
+            .unwrap();
+        let rx2 = queue
+            .add_message(make_address(1), make_hash(1), 20)
+            .unwrap();
+
+        queue.deliver_message(make_address(0), make_hash(0));
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), 20);
 
-        queue.deliver_message(make_hash(1), make_hash(1));
+        queue.deliver_message(make_address(1), make_hash(1));
         assert_eq!(queue.min_expire_at.load(Ordering::Acquire), u32::MAX);
 
         assert_eq!(rx1.await.unwrap(), MessageStatus::Delivered);
         assert_eq!(rx2.await.unwrap(), MessageStatus::Delivered);
     }
+
+    #[tokio::test]
+    async fn messages_expire_in_their_workchain_only() {
+        let queue = make_queue();
+
+        let masterchain_address =
+            MsgAddressInt::with_standart(None, -1, make_hash(0).into()).unwrap();
+        let rx1 = queue
+            .add_message(make_address(0), make_hash(0), 10)
+            .unwrap();
+        let rx2 = queue
+            .add_message(masterchain_address, make_hash(0), 10)
+            .unwrap();
+
+        // Masterchain blocks must not expire messages of the base workchain
+        queue.update(&ton_block::ShardIdent::masterchain(), 15);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(rx2.await.unwrap(), MessageStatus::Expired);
+
+        queue.update(&ton_block::ShardIdent::full(0), 15);
+        assert!(queue.is_empty());
+        assert_eq!(rx1.await.unwrap(), MessageStatus::Expired);
+    }
 }
@@ -232,6 +232,17 @@ pub fn get_root_token_version(root_contract: &ExistingContract) -> Result<TokenW
     Ok(version)
 }
 
+pub fn get_root_token_details(
+    root_contract: &ExistingContract,
+) -> Result<RootTokenContractDetails> {
+    let root_contract_state = RootTokenContractState(ExecutionContext {
+        clock: &SimpleClock,
+        account_stuff: &root_contract.account,
+    });
+
+    root_contract_state.guess_details()
+}
+
 #[derive(thiserror::Error, Debug)]
 enum TokenWalletError {
     #[error("Mint not supported by OldTip3v4 tokens")]
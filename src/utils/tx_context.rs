@@ -20,7 +20,7 @@ pub struct StateContext<'a> {
 pub struct TxContext<'a> {
     pub block_info: &'a ton_block::BlockInfo,
     pub block_hash: &'a UInt256,
-    pub account: &'a UInt256,
+    pub account: &'a ton_block::MsgAddressInt,
     pub transaction_hash: &'a UInt256,
     pub transaction_info: &'a ton_block::TransactionDescrOrdinary,
     pub transaction: &'a ton_block::Transaction,